@@ -0,0 +1,236 @@
+use super::Value;
+use super::ModuleType;
+use super::{sys, object, Method};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+use ::context;
+use ::exception::{self, Exception};
+
+// Native `ExitStack` method, given the stack's callbacks
+type StackMethod = fn(&Rc<RefCell<Vec<Value>>>, Vec<Value>,
+    HashMap<String, Value>) -> Value;
+
+pub fn import_module() -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("contextmanager".to_string(),
+        Value::Function(Rc::new(contextmanager)));
+    tbl.insert("suppress".to_string(), Value::Function(Rc::new(suppress)));
+    tbl.insert("closing".to_string(), Value::Function(Rc::new(closing)));
+    tbl.insert("ExitStack".to_string(), Value::Function(Rc::new(exit_stack)));
+    tbl.insert("redirect_stdout".to_string(),
+        Value::Function(Rc::new(redirect_stdout)));
+    Value::Module(ModuleType::new("contextlib", tbl))
+}
+
+/// Decorator turning a generator function into a context manager factory,
+/// the code before the `yield` runs on enter and the rest on exit
+fn contextmanager(params: Vec<Value>, _kwargs: HashMap<String, Value>)
+    -> Value {
+    let func = match params.into_iter().next() {
+        Some(func) => func,
+        None => panic!("TypeError: contextmanager() missing 1 required \
+            positional argument: 'func'")
+    };
+
+    Value::Function(Rc::new(move |args, kwargs| {
+        let gen = match func.call(args, kwargs) {
+            Value::Generator(gen) => gen,
+            value => panic!("TypeError: contextmanager expects a generator \
+                function, got '{}'", value.type_name())
+        };
+        let enter_gen = gen.clone();
+        let enter: Method = Rc::new(move |_, _| {
+            match enter_gen.send(Value::None) {
+                Some(value) => value,
+                None => panic!("RuntimeError: generator didn't yield")
+            }
+        });
+        let exit: Method = Rc::new(move |args, _| {
+            let exc = match context::exc_from_info(&args[1..]) {
+                Some(exc) => exc,
+                None => {
+                    if gen.send(Value::None).is_some() {
+                        panic!("RuntimeError: generator didn't stop")
+                    }
+                    return Value::Bool(false)
+                }
+            };
+
+            match exception::catch(|| gen.throw(exc.clone())) {
+                Ok(Some(_)) => {
+                    panic!("RuntimeError: generator didn't stop after throw()")
+                },
+                // The generator caught the exception and finished
+                Ok(None) => Value::Bool(true),
+                Err(ref raised) if *raised == exc => Value::Bool(false),
+                Err(raised) => raised.raise()
+            }
+        });
+
+        object("_GeneratorContextManager",
+            vec![("__enter__", enter), ("__exit__", exit)])
+    }))
+}
+
+/// `suppress(*exceptions)`, exceptions are given by name (or as classes)
+/// and their subclasses are suppressed as well
+fn suppress(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let names: Vec<String> = params.iter().map(|exc| match *exc {
+        Value::Str(ref name) => name.clone(),
+        Value::Class { ref tbl } if tbl.contains_key("__name__") => {
+            tbl["__name__"].to_string()
+        },
+        _ => panic!("TypeError: suppress() arguments must be exception names, \
+            not '{}'", exc.type_name())
+    }).collect();
+
+    let enter: Method = Rc::new(|_, _| Value::None);
+    let exit: Method = Rc::new(move |args, _| {
+        let suppressed = match context::exc_from_info(&args[1..]) {
+            Some(exc) => names.iter().any(|name| exc.is(name)),
+            None => false
+        };
+        Value::Bool(suppressed)
+    });
+    object("suppress", vec![("__enter__", enter), ("__exit__", exit)])
+}
+
+/// `closing(thing)` calls `thing.close()` when the block exits
+fn closing(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let thing = match params.into_iter().next() {
+        Some(thing) => thing,
+        None => panic!("TypeError: closing() missing 1 required positional \
+            argument: 'thing'")
+    };
+    let target = thing.clone();
+
+    let enter: Method = Rc::new(move |_, _| target.clone());
+    let exit: Method = Rc::new(move |_, _| {
+        ::call_member(thing.clone(), "close", vec![], HashMap::new());
+        Value::Bool(false)
+    });
+    object("closing", vec![("__enter__", enter), ("__exit__", exit)])
+}
+
+/// `redirect_stdout(target)` sends `print()` output to `target` for the
+/// duration of the block
+fn redirect_stdout(params: Vec<Value>, _kwargs: HashMap<String, Value>)
+    -> Value {
+    let target = match params.into_iter().next() {
+        Some(target) => target,
+        None => panic!("TypeError: redirect_stdout() missing 1 required \
+            positional argument: 'new_target'")
+    };
+    // Redirections nest, each one restores what it replaced
+    let previous = Rc::new(RefCell::new(vec![]));
+    let restore = previous.clone();
+
+    let enter: Method = Rc::new(move |_, _| {
+        previous.borrow_mut().push(sys::set_stdout(target.clone()));
+        target.clone()
+    });
+    let exit: Method = Rc::new(move |_, _| {
+        if let Some(stdout) = restore.borrow_mut().pop() {
+            sys::set_stdout(stdout);
+        }
+        Value::Bool(false)
+    });
+    object("redirect_stdout", vec![("__enter__", enter), ("__exit__", exit)])
+}
+
+/// `ExitStack()`, a context manager combining a dynamic number of context
+/// managers and cleanup callbacks, unwound in reverse order on exit
+fn exit_stack(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    new_exit_stack(vec![])
+}
+
+fn new_exit_stack(callbacks: Vec<Value>) -> Value {
+    // Each callback takes the `__exit__` arguments and returns whether the
+    // exception is suppressed
+    let callbacks = Rc::new(RefCell::new(callbacks));
+    let stack = |f: StackMethod| -> Method {
+        let callbacks = callbacks.clone();
+        Rc::new(move |args, kwargs| f(&callbacks, args, kwargs))
+    };
+
+    object("ExitStack", vec![
+        ("__enter__", Rc::new(|args: Vec<Value>, _| args[0].clone())),
+        ("__exit__", stack(|callbacks, args, _| {
+            let exc = context::exc_from_info(&args[1..]);
+            Value::Bool(unwind(callbacks, exc))
+        })),
+        ("close", stack(|callbacks, _, _| {
+            unwind(callbacks, None);
+            Value::None
+        })),
+        ("enter_context", stack(|callbacks, args, _| {
+            let manager = args[1].clone();
+            let target = context::enter(&manager);
+            callbacks.borrow_mut().push(Value::Function(Rc::new(
+                move |args, _| {
+                    let exc = context::exc_from_info(&args);
+                    Value::Bool(context::exit(&manager, exc.as_ref()))
+                })));
+            target
+        })),
+        ("push", stack(|callbacks, args, _| {
+            let exit = args[1].clone();
+            let callback = match exit {
+                Value::Object { .. } => {
+                    let manager = exit.clone();
+                    Value::Function(Rc::new(move |args, kwargs| {
+                        ::call_member(manager.clone(), "__exit__", args, kwargs)
+                    }))
+                },
+                _ => exit.clone()
+            };
+            callbacks.borrow_mut().push(callback);
+            exit
+        })),
+        ("callback", stack(|callbacks, mut args, kwargs| {
+            let func = args.remove(1);
+            let result = func.clone();
+            let args = args.split_off(1);
+            callbacks.borrow_mut().push(Value::Function(Rc::new(
+                move |_, _| {
+                    func.call(args.clone(), kwargs.clone());
+                    Value::Bool(false)
+                })));
+            result
+        })),
+        ("pop_all", stack(|callbacks, _, _| {
+            let moved = callbacks.borrow_mut().drain(..).collect();
+            new_exit_stack(moved)
+        }))
+    ])
+}
+
+// Calls the callbacks last to first. A suppressed exception isn't seen by
+// the remaining callbacks and an exception raised by a callback replaces the
+// current one. Returns whether the original exception was suppressed, a new
+// exception is raised instead.
+fn unwind(callbacks: &Rc<RefCell<Vec<Value>>>, original: Option<Exception>)
+    -> bool {
+    let mut exc = original.clone();
+
+    loop {
+        let callback = match callbacks.borrow_mut().pop() {
+            Some(callback) => callback,
+            None => break
+        };
+        let args = context::exc_info(exc.as_ref());
+
+        match exception::catch(|| callback.call(args, HashMap::new())) {
+            Ok(ref result) if result.to_bool() => exc = None,
+            Ok(_) => (),
+            Err(raised) => exc = Some(raised)
+        }
+    }
+
+    match exc {
+        Some(ref exc) if Some(exc) != original.as_ref() => exc.raise(),
+        Some(_) => false,
+        None => original.is_some()
+    }
+}
@@ -0,0 +1,416 @@
+use super::Value;
+use super::ModuleType;
+use super::NumericType;
+use super::DateTimeType;
+use super::Function;
+use super::time;
+use types::datetime::{self, TimeDelta, Date, Time, DateTime, TimeZone, MINYEAR,
+    MAXYEAR, MICROS_PER_DAY, bind_args, int_arg, str_arg, tzinfo_arg,
+    timestamp_arg};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub fn import_module() -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("MINYEAR".to_string(), Value::Number(NumericType::Integer(MINYEAR)));
+    tbl.insert("MAXYEAR".to_string(), Value::Number(NumericType::Integer(MAXYEAR)));
+
+    let max_days = 999_999_999 * MICROS_PER_DAY;
+    tbl.insert("timedelta".to_string(), class("timedelta", timedelta, vec![], vec![
+        ("min", DateTimeType::TimeDelta(TimeDelta::new(-max_days))),
+        ("max", DateTimeType::TimeDelta(TimeDelta::new(max_days + MICROS_PER_DAY - 1))),
+        ("resolution", DateTimeType::TimeDelta(TimeDelta::new(1)))
+    ]));
+    tbl.insert("date".to_string(), class("date", date, vec![
+        ("today", date_today), ("fromtimestamp", date_fromtimestamp),
+        ("fromordinal", date_fromordinal), ("fromisoformat", date_fromisoformat)
+    ], vec![
+        ("min", DateTimeType::Date(Date::new(MINYEAR, 1, 1))),
+        ("max", DateTimeType::Date(Date::new(MAXYEAR, 12, 31))),
+        ("resolution", DateTimeType::TimeDelta(TimeDelta::new(MICROS_PER_DAY)))
+    ]));
+    tbl.insert("time".to_string(), class("time", time_of_day, vec![
+        ("fromisoformat", time_fromisoformat)
+    ], vec![
+        ("min", DateTimeType::Time(Time::new(0, 0, 0, 0, None))),
+        ("max", DateTimeType::Time(Time::new(23, 59, 59, 999_999, None))),
+        ("resolution", DateTimeType::TimeDelta(TimeDelta::new(1)))
+    ]));
+    tbl.insert("datetime".to_string(), class("datetime", datetime, vec![
+        ("now", now), ("utcnow", utcnow), ("today", now),
+        ("fromtimestamp", fromtimestamp), ("utcfromtimestamp", utcfromtimestamp),
+        ("fromisoformat", fromisoformat), ("strptime", strptime),
+        ("combine", combine)
+    ], vec![
+        ("min", DateTimeType::DateTime(DateTime::new(Date::new(MINYEAR, 1, 1),
+            Time::new(0, 0, 0, 0, None)))),
+        ("max", DateTimeType::DateTime(DateTime::new(Date::new(MAXYEAR, 12, 31),
+            Time::new(23, 59, 59, 999_999, None)))),
+        ("resolution", DateTimeType::TimeDelta(TimeDelta::new(1)))
+    ]));
+    tbl.insert("timezone".to_string(), class("timezone", timezone, vec![], vec![
+        ("utc", DateTimeType::TimeZone(TimeZone::utc())),
+        ("min", DateTimeType::TimeZone(TimeZone::new(-86_340, None))),
+        ("max", DateTimeType::TimeZone(TimeZone::new(86_340, None)))
+    ]));
+    Value::Module(ModuleType::new("datetime", tbl))
+}
+
+// A native class, calling it constructs an instance. The class methods are
+// called without the class like the methods of a module.
+fn class(name: &str, new: Function, methods: Vec<(&str, Function)>,
+    attrs: Vec<(&str, DateTimeType)>) -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("__name__".to_string(), Value::Str(name.to_string()));
+    tbl.insert("__new__".to_string(), Value::Function(Rc::new(new)));
+    for (attr, method) in methods {
+        tbl.insert(attr.to_string(), Value::Function(Rc::new(method)));
+    }
+    for (attr, value) in attrs {
+        tbl.insert(attr.to_string(), Value::DateTime(value));
+    }
+    Value::Class { tbl }
+}
+
+// The value of an argument `bind_args` made sure was given
+fn required(arg: &Option<Value>) -> &Value {
+    arg.as_ref().unwrap()
+}
+
+/// `timedelta(days=0, seconds=0, microseconds=0, milliseconds=0, minutes=0,
+/// hours=0, weeks=0)`, floats are rounded to the nearest microsecond
+fn timedelta(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let names = ["days", "seconds", "microseconds", "milliseconds", "minutes",
+        "hours", "weeks"];
+    let units = [MICROS_PER_DAY, 1_000_000, 1, 1000, 60_000_000, 3_600_000_000,
+        7 * MICROS_PER_DAY];
+    let args = bind_args("timedelta", params, kwargs, &names, 0);
+
+    let (mut micros, mut fraction) = (0, 0.0);
+    for ((arg, name), unit) in args.iter().zip(&names).zip(&units) {
+        match *arg {
+            Some(Value::Number(NumericType::Float(value))) => {
+                fraction += value as f64 * *unit as f64
+            },
+            Some(ref value) => match value.as_index() {
+                Some(value) => micros += value as i128 * unit,
+                None => panic!("TypeError: unsupported type for timedelta {} \
+                    component: {}", name, value.type_name())
+            },
+            None => ()
+        }
+    }
+    datetime::delta(micros + datetime::round_half_even(fraction))
+}
+
+/// `date(year, month, day)`
+fn date(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("date", params, kwargs, &["year", "month", "day"], 3);
+    let field = |idx: usize| int_arg(required(&args[idx]));
+    Value::DateTime(DateTimeType::Date(Date::new(field(0), field(1), field(2))))
+}
+
+// The time fields starting at `args[0]`, missing ones are zero
+fn time_args(args: &[Option<Value>]) -> Time {
+    let field = |idx: usize| args[idx].as_ref().map_or(0, int_arg);
+    Time::new(field(0), field(1), field(2), field(3), tzinfo_arg(args[4].clone()))
+}
+
+/// `time(hour=0, minute=0, second=0, microsecond=0, tzinfo=None)`
+fn time_of_day(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("time", params, kwargs, &["hour", "minute", "second",
+        "microsecond", "tzinfo"], 0);
+    Value::DateTime(DateTimeType::Time(time_args(&args)))
+}
+
+/// `datetime(year, month, day, hour=0, minute=0, second=0, microsecond=0,
+/// tzinfo=None)`
+fn datetime(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("datetime", params, kwargs, &["year", "month", "day",
+        "hour", "minute", "second", "microsecond", "tzinfo"], 3);
+    let field = |idx: usize| int_arg(required(&args[idx]));
+    let date = Date::new(field(0), field(1), field(2));
+    Value::DateTime(DateTimeType::DateTime(DateTime::new(date, time_args(&args[3..]))))
+}
+
+/// `timezone(offset, name=None)`, a fixed offset of whole seconds
+fn timezone(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("timezone", params, kwargs, &["offset", "name"], 1);
+    let offset = match *required(&args[0]) {
+        Value::DateTime(DateTimeType::TimeDelta(ref td)) => td.micros(),
+        ref value => panic!("TypeError: timezone() argument 1 must be \
+            datetime.timedelta, not {}", value.type_name())
+    };
+    if offset % 1_000_000 != 0 {
+        panic!("ValueError: offset must be a timedelta representing a whole \
+            number of seconds")
+    }
+    if offset.abs() >= MICROS_PER_DAY {
+        panic!("ValueError: offset must be a timedelta strictly between \
+            -timedelta(hours=24) and timedelta(hours=24).")
+    }
+    let name = args[1].clone().map(|name| str_arg(Some(name), "timezone", ""));
+    Value::DateTime(DateTimeType::TimeZone(TimeZone::new((offset / 1_000_000) as i32,
+        name)))
+}
+
+fn local_date(secs: i64, micros: i32) -> Value {
+    Value::DateTime(DateTimeType::Date(DateTime::from_timestamp(secs, micros, None)
+        .date()))
+}
+
+/// `date.today()`, the local date
+fn date_today(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let (secs, micros) = time::now();
+    local_date(secs, micros)
+}
+
+/// `date.fromtimestamp(timestamp)`, the local date
+fn date_fromtimestamp(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("fromtimestamp", params, kwargs, &["timestamp"], 1);
+    let (secs, micros) = timestamp_arg(required(&args[0]));
+    local_date(secs, micros)
+}
+
+/// `date.fromordinal(ordinal)`, 1 is the 1st of January of year 1
+fn date_fromordinal(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("fromordinal", params, kwargs, &["ordinal"], 1);
+    let ordinal = int_arg(required(&args[0]));
+    if ordinal < 1 {
+        panic!("ValueError: ordinal must be >= 1")
+    }
+    let days = ordinal as i64 - 1 + time::days_from_civil(1, 1, 1);
+    Value::DateTime(DateTimeType::Date(Date::from_days(days)))
+}
+
+fn string_arg(func: &str, params: Vec<Value>, kwargs: HashMap<String, Value>)
+    -> String {
+    let args = bind_args(func, params, kwargs, &["date_string"], 1);
+    str_arg(args[0].clone(), func, "")
+}
+
+/// `date.fromisoformat(date_string)`, `YYYY-MM-DD`
+fn date_fromisoformat(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let string = string_arg("fromisoformat", params, kwargs);
+    Value::DateTime(DateTimeType::Date(datetime::parse_iso_date(&string)))
+}
+
+/// `time.fromisoformat(time_string)`, the output of `time.isoformat()`
+fn time_fromisoformat(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let string = string_arg("fromisoformat", params, kwargs);
+    Value::DateTime(DateTimeType::Time(datetime::parse_iso_time(&string)))
+}
+
+fn from_timestamp(secs: i64, micros: i32, tz: Option<TimeZone>) -> Value {
+    Value::DateTime(DateTimeType::DateTime(DateTime::from_timestamp(secs, micros, tz)))
+}
+
+/// `datetime.now(tz=None)`, naive local time without `tz`. Also
+/// `datetime.today()`.
+fn now(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("now", params, kwargs, &["tz"], 0);
+    let (secs, micros) = time::now();
+    from_timestamp(secs, micros, tzinfo_arg(args[0].clone()))
+}
+
+// A naive UTC datetime
+fn utc_naive(secs: i64, micros: i32) -> Value {
+    let utc = DateTime::from_timestamp(secs, micros, Some(TimeZone::utc()));
+    Value::DateTime(DateTimeType::DateTime(utc.with_tzinfo(None)))
+}
+
+/// `datetime.utcnow()`, a naive UTC time
+fn utcnow(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let (secs, micros) = time::now();
+    utc_naive(secs, micros)
+}
+
+/// `datetime.fromtimestamp(timestamp, tz=None)`
+fn fromtimestamp(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("fromtimestamp", params, kwargs, &["timestamp", "tz"], 1);
+    let (secs, micros) = timestamp_arg(required(&args[0]));
+    from_timestamp(secs, micros, tzinfo_arg(args[1].clone()))
+}
+
+/// `datetime.utcfromtimestamp(timestamp)`, a naive UTC time
+fn utcfromtimestamp(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("utcfromtimestamp", params, kwargs, &["timestamp"], 1);
+    let (secs, micros) = timestamp_arg(required(&args[0]));
+    utc_naive(secs, micros)
+}
+
+/// `datetime.fromisoformat(date_string)`, the output of `isoformat()`
+fn fromisoformat(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let string = string_arg("fromisoformat", params, kwargs);
+    Value::DateTime(DateTimeType::DateTime(datetime::parse_iso_datetime(&string)))
+}
+
+/// `datetime.strptime(date_string, format)`, aware when `%z` is parsed
+fn strptime(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("strptime", params, kwargs, &["date_string", "format"], 2);
+    let string = str_arg(args[0].clone(), "strptime", "");
+    let tm = time::parse(&string, &str_arg(args[1].clone(), "strptime", ""));
+    let tzinfo = tm.utcoffset.map(|offset| TimeZone::new(offset, tm.tzname.clone()));
+    let date = Date::new(tm.year, tm.month, tm.day);
+    let time = Time::new(tm.hour, tm.minute, tm.second, tm.microsecond, tzinfo);
+    Value::DateTime(DateTimeType::DateTime(DateTime::new(date, time)))
+}
+
+/// `datetime.combine(date, time, tzinfo=time.tzinfo)`
+fn combine(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let args = bind_args("combine", params, kwargs, &["date", "time", "tzinfo"], 2);
+    let date = match *required(&args[0]) {
+        Value::DateTime(DateTimeType::Date(date)) => date,
+        Value::DateTime(DateTimeType::DateTime(ref dt)) => dt.date(),
+        ref value => panic!("TypeError: combine() argument 1 must be \
+            datetime.date, not {}", value.type_name())
+    };
+    let time = match *required(&args[1]) {
+        Value::DateTime(DateTimeType::Time(ref time)) => time.clone(),
+        ref value => panic!("TypeError: combine() argument 2 must be \
+            datetime.time, not {}", value.type_name())
+    };
+    let tzinfo = match args[2] {
+        Some(ref tzinfo) => tzinfo_arg(Some(tzinfo.clone())),
+        None => time.tzinfo()
+    };
+    let dt = DateTime::new(date, time).with_tzinfo(tzinfo);
+    Value::DateTime(DateTimeType::DateTime(dt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::exception;
+    use builtin::{int_value, string, call_method};
+
+    fn kwargs(pairs: Vec<(&str, Value)>) -> HashMap<String, Value> {
+        pairs.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+    }
+
+    fn error<F: FnOnce() -> Value>(func: F) -> Option<String> {
+        exception::catch(func).err().map(|exc| exc.to_string())
+    }
+
+    #[test]
+    fn datetime_timedelta() {
+        let td = timedelta(vec![int_value(1)], kwargs(vec![
+            ("hours", int_value(2)), ("minutes", int_value(3)),
+            ("seconds", int_value(4)), ("microseconds", int_value(5))]));
+        assert_eq!(td.to_string(), "1 day, 2:03:04.000005");
+        assert_eq!(format!("{:?}", td), "datetime.timedelta(1, 7384, 5)");
+        assert_eq!(td.get_attr("seconds"), int_value(7384));
+
+        let minus = -timedelta(vec![], kwargs(vec![("seconds", int_value(1))]));
+        assert_eq!(minus.to_string(), "-1 day, 23:59:59");
+        assert_eq!(minus.get_attr("days"), int_value(-1));
+        let half = timedelta(vec![], kwargs(vec![("microseconds",
+            Value::Number(NumericType::Float(2.5)))]));
+        assert_eq!(half.get_attr("microseconds"), int_value(2));
+
+        let hour = timedelta(vec![], kwargs(vec![("hours", int_value(1))]));
+        let minute = timedelta(vec![], kwargs(vec![("minutes", int_value(1))]));
+        assert_eq!(hour.clone() / minute.clone(), Value::Number(NumericType::Float(60.0)));
+        assert_eq!(minute.clone() * int_value(60), hour);
+        assert_eq!(hour.clone() / int_value(60), minute);
+        assert_eq!((hour.clone() + minute.clone()) % hour.clone(), minute);
+        assert_eq!(hour > minute, true);
+        assert_eq!(::builtin::abs(vec![minus.clone()], HashMap::new()),
+            timedelta(vec![], kwargs(vec![("seconds", int_value(1))])));
+        assert_eq!(call_method(&hour, "total_seconds", vec![]),
+            Value::Number(NumericType::Float(3600.0)));
+
+        assert_eq!(error(|| timedelta(vec![int_value(1_000_000_000)], HashMap::new())),
+            Some("OverflowError: days=1000000000; must have magnitude <= 999999999"
+                .to_string()));
+        assert_eq!(error(|| hour.clone() + int_value(1)),
+            Some("TypeError: unsupported operand type(s) for +: \
+                'datetime.timedelta' and 'int'".to_string()));
+    }
+
+    #[test]
+    fn datetime_dates_and_times() {
+        let module = import_module();
+        let new = |class: &str, args: Vec<i32>| {
+            module.get_attr(class).call(args.into_iter().map(int_value).collect(),
+                HashMap::new())
+        };
+        let day = new("date", vec![2024, 2, 28]);
+        let week = timedelta(vec![], kwargs(vec![("weeks", int_value(1))]));
+        assert_eq!((day.clone() + week.clone()).to_string(), "2024-03-06");
+        assert_eq!((day.clone() - new("date", vec![2023, 2, 28])).get_attr("days"),
+            int_value(365));
+        assert_eq!(call_method(&day, "weekday", vec![]), int_value(2));
+        assert_eq!(call_method(&day, "isocalendar", vec![]).to_string(), "(2024, 9, 3)");
+        assert_eq!(call_method(&new("date", vec![1, 1, 1]), "toordinal", vec![]),
+            int_value(1));
+        assert_eq!(error(|| new("date", vec![2023, 2, 29])),
+            Some("ValueError: day is out of range for month".to_string()));
+
+        let dt = new("datetime", vec![2024, 1, 2, 3, 4, 5]);
+        assert_eq!(format!("{:?}", dt), "datetime.datetime(2024, 1, 2, 3, 4, 5)");
+        assert_eq!(dt.to_string(), "2024-01-02 03:04:05");
+        assert_eq!(call_method(&dt, "strftime", vec![string("%A %d %B %Y %H:%M")]),
+            string("Tuesday 02 January 2024 03:04"));
+        assert_eq!(::call_member(dt.clone(), "replace", vec![],
+            kwargs(vec![("day", int_value(31)), ("microsecond", int_value(500))]))
+            .to_string(),
+            "2024-01-31 03:04:05.000500");
+        assert_eq!(call_method(&dt, "date", vec![]), new("date", vec![2024, 1, 2]));
+        assert_eq!(call_method(&dt, "time", vec![]).to_string(), "03:04:05");
+
+        let utc = module.get_attr("timezone").get_attr("utc");
+        let aware = ::call_member(dt.clone(), "replace", vec![],
+            kwargs(vec![("tzinfo", utc.clone())]));
+        assert_eq!(format!("{:?}", aware), "datetime.datetime(2024, 1, 2, 3, 4, 5, \
+            tzinfo=datetime.timezone.utc)");
+        assert_eq!(call_method(&aware, "isoformat", vec![]),
+            string("2024-01-02T03:04:05+00:00"));
+        assert_eq!(call_method(&aware, "timestamp", vec![]), int_value(1704164645));
+        let fromtimestamp = module.get_attr("datetime").get_attr("fromtimestamp");
+        assert_eq!(fromtimestamp.call(vec![int_value(1704164645), utc.clone()],
+            HashMap::new()), aware);
+
+        let india = module.get_attr("timezone").call(vec![timedelta(vec![],
+            kwargs(vec![("hours", int_value(5)), ("minutes", int_value(30))]))],
+            HashMap::new());
+        let shifted = call_method(&aware, "astimezone", vec![india]);
+        assert_eq!(shifted.to_string(), "2024-01-02 08:34:05+05:30");
+        assert_eq!(shifted, aware);
+        assert_eq!(call_method(&shifted, "tzname", vec![]), string("UTC+05:30"));
+        assert_eq!((shifted.clone() - aware.clone()).to_string(), "0:00:00");
+        assert_eq!(dt == aware, false);
+        assert_eq!(error(|| Value::Bool(dt < aware)),
+            Some("TypeError: can't compare offset-naive and offset-aware \
+                datetimes".to_string()));
+    }
+
+    #[test]
+    fn datetime_parsing() {
+        let module = import_module();
+        let class = module.get_attr("datetime");
+        let parse = |text: &str| call_method(&class, "fromisoformat", vec![string(text)]);
+
+        let dt = parse("2024-01-02T03:04:05.123456+01:00");
+        assert_eq!(call_method(&dt, "isoformat", vec![]),
+            string("2024-01-02T03:04:05.123456+01:00"));
+        assert_eq!(::call_member(dt.clone(), "isoformat", vec![string(" ")],
+            kwargs(vec![("timespec", string("milliseconds"))])),
+            string("2024-01-02 03:04:05.123+01:00"));
+        assert_eq!(parse("2024-01-02").to_string(), "2024-01-02 00:00:00");
+        assert_eq!(error(|| parse("2024-1-02")),
+            Some("ValueError: Invalid isoformat string: '2024-1-02'".to_string()));
+        assert_eq!(call_method(&module.get_attr("time"), "fromisoformat",
+            vec![string("12:30")]).to_string(), "12:30:00");
+
+        let parsed = call_method(&class, "strptime", vec![string("02/01/24 10:30 +0200"),
+            string("%d/%m/%y %H:%M %z")]);
+        assert_eq!(parsed.to_string(), "2024-01-02 10:30:00+02:00");
+        assert_eq!(call_method(&parsed, "utcoffset", vec![]).to_string(), "2:00:00");
+        let naive = call_method(&class, "strptime", vec![string("2024-01-02"),
+            string("%Y-%m-%d")]);
+        assert_eq!(naive.get_attr("tzinfo"), Value::None);
+        assert_eq!(call_method(&naive, "timetuple", vec![]).to_string(),
+            "(2024, 1, 2, 0, 0, 0, 1, 2, -1)");
+    }
+}
@@ -0,0 +1,51 @@
+use super::Value;
+use super::ModuleType;
+use super::NumericType;
+use super::IOWrapper;
+use super::MemoryFileType;
+use types::{bytes, DEFAULT_BUFFER_SIZE};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+pub fn import_module() -> Value {
+    let int = |value: i32| Value::Number(NumericType::Integer(value));
+    let mut tbl = HashMap::new();
+    tbl.insert("StringIO".to_string(), Value::Function(Rc::new(string_io)));
+    tbl.insert("BytesIO".to_string(), Value::Function(Rc::new(bytes_io)));
+    tbl.insert("open".to_string(), Value::Function(Rc::new(super::open)));
+    tbl.insert("DEFAULT_BUFFER_SIZE".to_string(),
+        int(DEFAULT_BUFFER_SIZE as i32));
+    tbl.insert("SEEK_SET".to_string(), int(0));
+    tbl.insert("SEEK_CUR".to_string(), int(1));
+    tbl.insert("SEEK_END".to_string(), int(2));
+    Value::Module(ModuleType::new("io", tbl))
+}
+
+/// `StringIO(initial_value='')`, an in-memory text stream
+fn string_io(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let initial = match params.first().or_else(|| kwargs.get("initial_value")) {
+        Some(&Value::Str(ref text)) => text.clone(),
+        Some(&Value::None) | None => String::new(),
+        Some(value) => panic!("TypeError: initial_value must be str or None, \
+            not {}", value.type_name())
+    };
+    memory_file(MemoryFileType::new_text(&initial))
+}
+
+/// `BytesIO(initial_bytes=b'')`, an in-memory binary stream
+fn bytes_io(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let initial = match params.first().or_else(|| kwargs.get("initial_bytes")) {
+        Some(&Value::None) | None => vec![],
+        Some(value) => match bytes::as_bytes(value) {
+            Some(data) => data,
+            None => panic!("TypeError: a bytes-like object is required, not \
+                '{}'", value.type_name())
+        }
+    };
+    memory_file(MemoryFileType::new_bytes(initial))
+}
+
+fn memory_file(buf: MemoryFileType) -> Value {
+    Value::TextIOWrapper(IOWrapper::Memory(Rc::new(RefCell::new(buf))))
+}
@@ -1,29 +1,542 @@
 use super::Value;
+use super::ModuleType;
 use super::NumericType;
+use super::TupleType;
+use super::Function;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::f64::consts;
+
+type Unary = fn(f64) -> f64;
 
 pub fn import_module() -> Value {
     let mut tbl = HashMap::new();
-    tbl.insert("__name__".to_string(), Value::Str("math".to_string()));
-    tbl.insert("sqrt".to_string(), Value::Function(Rc::new(sqrt)));
-    Value::Class { tbl }
+    tbl.insert("pi".to_string(), float(consts::PI));
+    tbl.insert("e".to_string(), float(consts::E));
+    tbl.insert("tau".to_string(), float(consts::TAU));
+    tbl.insert("inf".to_string(), float(f64::INFINITY));
+    tbl.insert("nan".to_string(), float(f64::NAN));
+
+    let unary: Vec<(&str, Unary)> = vec![
+        ("sqrt", f64::sqrt), ("cbrt", f64::cbrt), ("exp", f64::exp),
+        ("exp2", f64::exp2), ("expm1", f64::exp_m1), ("fabs", f64::abs),
+        ("sin", f64::sin), ("cos", f64::cos), ("tan", f64::tan),
+        ("asin", f64::asin), ("acos", f64::acos), ("atan", f64::atan),
+        ("sinh", f64::sinh), ("cosh", f64::cosh), ("tanh", f64::tanh),
+        ("asinh", f64::asinh), ("acosh", f64::acosh),
+        ("degrees", f64::to_degrees), ("radians", f64::to_radians)
+    ];
+    for (name, func) in unary {
+        tbl.insert(name.to_string(), unary_function(name, func, |_| false));
+    }
+    // Poles of these functions are domain errors rather than infinities
+    tbl.insert("log2".to_string(),
+        unary_function("log2", f64::log2, |x| x <= 0.0));
+    tbl.insert("log10".to_string(),
+        unary_function("log10", f64::log10, |x| x <= 0.0));
+    tbl.insert("log1p".to_string(),
+        unary_function("log1p", f64::ln_1p, |x| x <= -1.0));
+    tbl.insert("atanh".to_string(),
+        unary_function("atanh", f64::atanh, |x| x.abs() >= 1.0));
+
+    let functions: Vec<(&str, Function)> = vec![
+        ("floor", floor), ("ceil", ceil), ("trunc", trunc), ("fmod", fmod),
+        ("log", log), ("pow", pow), ("atan2", atan2), ("hypot", hypot),
+        ("copysign", copysign), ("isclose", isclose), ("isfinite", isfinite),
+        ("isinf", isinf), ("isnan", isnan), ("gcd", gcd), ("lcm", lcm),
+        ("factorial", factorial), ("comb", comb), ("perm", perm),
+        ("isqrt", isqrt), ("frexp", frexp), ("ldexp", ldexp), ("modf", modf),
+        ("fsum", fsum), ("prod", prod)
+    ];
+    for (name, func) in functions {
+        tbl.insert(name.to_string(), Value::Function(Rc::new(func)));
+    }
+    Value::Module(ModuleType::new("math", tbl))
+}
+
+fn float(value: f64) -> Value {
+    Value::Number(NumericType::Float(value as f32))
+}
+
+// Integer results that don't fit the runtime's integers overflow
+fn int(name: &str, value: i128) -> Value {
+    if value > i32::MAX as i128 || value < i32::MIN as i128 {
+        panic!("OverflowError: {}() result out of range", name)
+    }
+    Value::Number(NumericType::Integer(value as i32))
+}
+
+fn tuple(values: Vec<Value>) -> Value {
+    Value::Tuple(TupleType::new(values))
 }
 
-fn sqrt(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
-    if params.is_empty() {
-        panic!("sqrt() takes exactly one argument")
+fn check_args(name: &str, params: &[Value], count: usize) {
+    if params.len() != count {
+        match count {
+            1 => panic!("TypeError: {}() takes exactly one argument ({} given)",
+                name, params.len()),
+            _ => panic!("TypeError: {} expected {} arguments, got {}", name,
+                count, params.len())
+        }
     }
-    let mut params_iter = params.iter();
-    let value = params_iter.next().unwrap();
+}
 
+fn float_arg(value: &Value) -> f64 {
     match *value {
-        Value::Number(NumericType::Integer(val)) => {
-            Value::Number(NumericType::Float((val as f32).sqrt()))
-        },
-        Value::Number(NumericType::Float(val)) => {
-            Value::Number(NumericType::Float(val.sqrt()))
-        },
-        _ => panic!("sqrt() argument must be real number")
+        Value::Number(NumericType::Integer(val)) => val as f64,
+        Value::Number(NumericType::Float(val)) => val as f64,
+        Value::Bool(val) => val as i32 as f64,
+        _ => panic!("TypeError: must be real number, not {}", value.type_name())
+    }
+}
+
+fn int_arg(value: &Value) -> i128 {
+    match value.as_index() {
+        Some(val) => val as i128,
+        None => panic!("TypeError: '{}' object cannot be interpreted as an \
+            integer", value.type_name())
+    }
+}
+
+// Checks a float result the way CPython's libm wrappers do: a NaN out of
+// non-NaN input is a domain error, an infinity out of finite input overflowed.
+// Results are stored as f32, so the range is checked after narrowing.
+fn checked(inputs: &[f64], result: f64) -> Value {
+    if result.is_nan() && !inputs.iter().any(|x| x.is_nan()) {
+        panic!("ValueError: math domain error")
+    }
+    if (result as f32).is_infinite() && inputs.iter().all(|x| x.is_finite()) {
+        panic!("OverflowError: math range error")
+    }
+    float(result)
+}
+
+fn unary_function(name: &str, func: fn(f64) -> f64, domain_error: fn(f64) -> bool)
+    -> Value {
+    let name = name.to_string();
+    Value::Function(Rc::new(move |params: Vec<Value>, _| {
+        check_args(&name, &params, 1);
+        let x = float_arg(&params[0]);
+        if domain_error(x) {
+            panic!("ValueError: math domain error")
+        }
+        checked(&[x], func(x))
+    }))
+}
+
+// Shared by floor, ceil and trunc, integers are returned unchanged
+fn to_integer(name: &str, params: &[Value], func: fn(f64) -> f64) -> Value {
+    check_args(name, params, 1);
+    match params[0] {
+        Value::Number(NumericType::Integer(_)) => params[0].clone(),
+        Value::Bool(val) => Value::Number(NumericType::Integer(val as i32)),
+        ref value => {
+            let x = float_arg(value);
+            if x.is_nan() {
+                panic!("ValueError: cannot convert float NaN to integer")
+            }
+            if x.is_infinite() {
+                panic!("OverflowError: cannot convert float infinity to integer")
+            }
+            int(name, func(x) as i128)
+        }
+    }
+}
+
+fn floor(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    to_integer("floor", &params, f64::floor)
+}
+
+fn ceil(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    to_integer("ceil", &params, f64::ceil)
+}
+
+fn trunc(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    to_integer("trunc", &params, f64::trunc)
+}
+
+/// `fmod(x, y)`, the remainder has the sign of `x` unlike `x % y`
+fn fmod(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("fmod", &params, 2);
+    let (x, y) = (float_arg(&params[0]), float_arg(&params[1]));
+    if y == 0.0 || x.is_infinite() {
+        panic!("ValueError: math domain error")
+    }
+    checked(&[x, y], x % y)
+}
+
+/// `log(x[, base])`, the natural logarithm without a base
+fn log(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.is_empty() || params.len() > 2 {
+        panic!("TypeError: log expected 1 or 2 arguments, got {}", params.len())
+    }
+    let args: Vec<f64> = params.iter().map(float_arg).collect();
+    if args.iter().any(|x| *x <= 0.0) {
+        panic!("ValueError: math domain error")
+    }
+
+    let result = match args.get(1) {
+        Some(base) => args[0].ln() / base.ln(),
+        None => args[0].ln()
+    };
+    // ln(1) == 0 so a base of one divides by zero
+    if args.get(1) == Some(&1.0) {
+        panic!("ZeroDivisionError: float division by zero")
+    }
+    checked(&args, result)
+}
+
+/// `pow(x, y)`, always a float unlike the builtin
+fn pow(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("pow", &params, 2);
+    let (x, y) = (float_arg(&params[0]), float_arg(&params[1]));
+    if x == 0.0 && y < 0.0 {
+        panic!("ValueError: math domain error")
+    }
+    checked(&[x, y], x.powf(y))
+}
+
+fn atan2(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("atan2", &params, 2);
+    float(float_arg(&params[0]).atan2(float_arg(&params[1])))
+}
+
+/// `hypot(*coordinates)`, the Euclidean norm of any number of coordinates
+fn hypot(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let coords: Vec<f64> = params.iter().map(float_arg).collect();
+    if coords.iter().any(|x| x.is_infinite()) {
+        return float(f64::INFINITY)
+    }
+    let norm = coords.iter().fold(0.0, |norm: f64, x| norm.hypot(*x));
+    checked(&coords, norm)
+}
+
+fn copysign(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("copysign", &params, 2);
+    float(float_arg(&params[0]).copysign(float_arg(&params[1])))
+}
+
+/// `isclose(a, b, *, rel_tol=1e-09, abs_tol=0.0)`
+fn isclose(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    check_args("isclose", &params, 2);
+    let (a, b) = (float_arg(&params[0]), float_arg(&params[1]));
+    let rel_tol = kwargs.get("rel_tol").map_or(1e-09, float_arg);
+    let abs_tol = kwargs.get("abs_tol").map_or(0.0, float_arg);
+    if rel_tol < 0.0 || abs_tol < 0.0 {
+        panic!("ValueError: tolerances must be non-negative")
+    }
+
+    if a == b {
+        return Value::Bool(true)
+    }
+    if a.is_infinite() || b.is_infinite() {
+        return Value::Bool(false)
+    }
+    let diff = (b - a).abs();
+    Value::Bool(diff <= (rel_tol * b).abs() || diff <= (rel_tol * a).abs() ||
+        diff <= abs_tol)
+}
+
+fn isfinite(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("isfinite", &params, 1);
+    Value::Bool(float_arg(&params[0]).is_finite())
+}
+
+fn isinf(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("isinf", &params, 1);
+    Value::Bool(float_arg(&params[0]).is_infinite())
+}
+
+fn isnan(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("isnan", &params, 1);
+    Value::Bool(float_arg(&params[0]).is_nan())
+}
+
+fn gcd_of(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        let rem = a % b;
+        a = b;
+        b = rem;
+    }
+    a.abs()
+}
+
+/// `gcd(*integers)`, zero without arguments
+fn gcd(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let result = params.iter().map(int_arg).fold(0, gcd_of);
+    int("gcd", result)
+}
+
+/// `lcm(*integers)`, one without arguments
+fn lcm(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let result = params.iter().map(int_arg).fold(1, |acc, x| {
+        if acc == 0 || x == 0 {
+            0
+        } else {
+            (acc / gcd_of(acc, x) * x).abs()
+        }
+    });
+    int("lcm", result)
+}
+
+fn factorial(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("factorial", &params, 1);
+    let n = int_arg(&params[0]);
+    if n < 0 {
+        panic!("ValueError: factorial() not defined for negative values")
+    }
+    int("factorial", falling_factorial("factorial", n, n))
+}
+
+// n * (n - 1) * ... * (n - k + 1)
+fn falling_factorial(name: &str, n: i128, k: i128) -> i128 {
+    (n - k + 1..=n).fold(1i128, |acc, x| match acc.checked_mul(x) {
+        Some(acc) if acc <= i32::MAX as i128 => acc,
+        _ => panic!("OverflowError: {}() result out of range", name)
+    })
+}
+
+fn check_non_negative(n: i128, k: i128) {
+    if n < 0 {
+        panic!("ValueError: n must be a non-negative integer")
+    }
+    if k < 0 {
+        panic!("ValueError: k must be a non-negative integer")
+    }
+}
+
+/// `comb(n, k)`, the number of ways to choose `k` of `n` items
+fn comb(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("comb", &params, 2);
+    let (n, k) = (int_arg(&params[0]), int_arg(&params[1]));
+    check_non_negative(n, k);
+    if k > n {
+        return int("comb", 0)
+    }
+
+    // Each partial product is itself a binomial coefficient so the division
+    // is exact and the intermediate values stay small
+    let k = k.min(n - k);
+    let mut result: i128 = 1;
+    for i in 0..k {
+        result = result * (n - i) / (i + 1);
+        if result > i32::MAX as i128 {
+            panic!("OverflowError: comb() result out of range")
+        }
+    }
+    int("comb", result)
+}
+
+/// `perm(n, k=None)`, the number of ordered arrangements of `k` of `n` items
+fn perm(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.is_empty() || params.len() > 2 {
+        panic!("TypeError: perm expected 1 or 2 arguments, got {}",
+            params.len())
+    }
+    let n = int_arg(&params[0]);
+    let k = match params.get(1) {
+        Some(&Value::None) | None => n,
+        Some(k) => int_arg(k)
+    };
+    check_non_negative(n, k);
+    if k > n {
+        return int("perm", 0)
+    }
+    int("perm", falling_factorial("perm", n, k))
+}
+
+/// `isqrt(n)`, the largest integer whose square doesn't exceed `n`
+fn isqrt(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("isqrt", &params, 1);
+    let n = int_arg(&params[0]);
+    if n < 0 {
+        panic!("ValueError: isqrt() argument must be nonnegative")
+    }
+    // The float estimate can be off by one for large values
+    let mut root = (n as f64).sqrt() as i128;
+    while root * root > n {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= n {
+        root += 1;
+    }
+    int("isqrt", root)
+}
+
+/// `frexp(x)`, the mantissa and exponent with `x == m * 2**e` and
+/// `0.5 <= abs(m) < 1`
+fn frexp(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("frexp", &params, 1);
+    let x = float_arg(&params[0]);
+    if x == 0.0 || !x.is_finite() {
+        return tuple(vec![float(x), int("frexp", 0)])
+    }
+
+    let mut exp = x.abs().log2().floor() as i32 + 1;
+    let mut mantissa = x / 2f64.powi(exp);
+    // Rounding in log2 can leave the mantissa just outside its range
+    if mantissa.abs() >= 1.0 {
+        mantissa /= 2.0;
+        exp += 1;
+    } else if mantissa.abs() < 0.5 {
+        mantissa *= 2.0;
+        exp -= 1;
+    }
+    tuple(vec![float(mantissa), int("frexp", exp as i128)])
+}
+
+/// `ldexp(x, i)`, `x * 2**i`
+fn ldexp(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("ldexp", &params, 2);
+    let x = float_arg(&params[0]);
+    let exp = int_arg(&params[1]);
+    checked(&[x], x * 2f64.powi(exp as i32))
+}
+
+/// `modf(x)`, the fractional and integer parts, both with the sign of `x`
+fn modf(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("modf", &params, 1);
+    let x = float_arg(&params[0]);
+    if x.is_infinite() {
+        return tuple(vec![float(0.0f64.copysign(x)), float(x)])
+    }
+    tuple(vec![float(x.fract()), float(x.trunc())])
+}
+
+/// `fsum(iterable)`, a float sum that doesn't lose precision to rounding.
+/// Uses Shewchuk's algorithm: the running total is kept as a list of
+/// non-overlapping partial sums.
+fn fsum(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    check_args("fsum", &params, 1);
+    let mut partials: Vec<f64> = vec![];
+    let mut special = 0.0;
+
+    for item in params[0].clone() {
+        let mut x = float_arg(&item);
+        if !x.is_finite() {
+            special += x;
+            continue
+        }
+        let mut i = 0;
+        for j in 0..partials.len() {
+            let mut y = partials[j];
+            if x.abs() < y.abs() {
+                ::std::mem::swap(&mut x, &mut y);
+            }
+            let high = x + y;
+            let low = y - (high - x);
+            if low != 0.0 {
+                partials[i] = low;
+                i += 1;
+            }
+            x = high;
+        }
+        if x.is_infinite() {
+            panic!("OverflowError: intermediate overflow in fsum")
+        }
+        partials.truncate(i);
+        partials.push(x);
+    }
+
+    if special != 0.0 || special.is_nan() {
+        if special.is_nan() {
+            panic!("ValueError: -inf + inf in fsum")
+        }
+        return float(special)
+    }
+    float(partials.iter().sum())
+}
+
+/// `prod(iterable, *, start=1)`, the product of the items
+fn prod(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    check_args("prod", &params, 1);
+    let start = kwargs.get("start").cloned()
+        .unwrap_or(Value::Number(NumericType::Integer(1)));
+    params[0].clone().into_iter().fold(start, |acc, item| acc * item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::exception;
+
+    fn call(attr: &str, args: Vec<Value>) -> Value {
+        ::call_member(import_module(), attr, args, HashMap::new())
+    }
+
+    fn error(attr: &str, args: Vec<Value>) -> Option<String> {
+        exception::catch(|| call(attr, args)).err().map(|exc| exc.to_string())
+    }
+
+    fn i(value: i32) -> Value {
+        Value::Number(NumericType::Integer(value))
+    }
+
+    fn f(value: f32) -> Value {
+        Value::Number(NumericType::Float(value))
+    }
+
+    #[test]
+    fn math_float_functions() {
+        assert_eq!(call("sqrt", vec![i(16)]), f(4.0));
+        assert_eq!(call("floor", vec![f(-1.5)]), i(-2));
+        assert_eq!(call("ceil", vec![f(1.2)]), i(2));
+        assert_eq!(call("trunc", vec![f(-1.7)]), i(-1));
+        assert_eq!(call("fmod", vec![i(-7), i(3)]), f(-1.0));
+        assert_eq!(call("log", vec![i(8), i(2)]), f(3.0));
+        assert_eq!(call("log10", vec![i(1000)]), f(3.0));
+        assert_eq!(call("pow", vec![i(2), i(10)]), f(1024.0));
+        assert_eq!(call("hypot", vec![i(3), i(4)]), f(5.0));
+        assert_eq!(call("degrees", vec![import_module().get_attr("pi")]),
+            f(180.0));
+        assert_eq!(call("copysign", vec![i(2), f(-0.0)]), f(-2.0));
+        assert_eq!(call("frexp", vec![i(8)]), tuple(vec![f(0.5), i(4)]));
+        assert_eq!(call("ldexp", vec![f(0.5), i(4)]), f(8.0));
+        assert_eq!(call("modf", vec![f(-2.5)]), tuple(vec![f(-0.5), f(-2.0)]));
+        let mut tolerance = HashMap::new();
+        tolerance.insert("rel_tol".to_string(), f(0.1));
+        assert_eq!(isclose(vec![f(1.0), f(1.05)], tolerance), Value::Bool(true));
+        assert_eq!(call("isclose", vec![f(1.0), f(1.05)]), Value::Bool(false));
+        assert_eq!(call("isnan", vec![import_module().get_attr("nan")]),
+            Value::Bool(true));
+        let items = Value::Tuple(TupleType::new(vec![f(0.1); 10]));
+        assert_eq!(call("fsum", vec![items]), f(1.0));
+
+        assert_eq!(error("sqrt", vec![i(-1)]),
+            Some("ValueError: math domain error".to_string()));
+        assert_eq!(error("log", vec![i(0)]),
+            Some("ValueError: math domain error".to_string()));
+        assert_eq!(error("exp", vec![i(1000)]),
+            Some("OverflowError: math range error".to_string()));
+        // Finite as an f64 but out of range once stored as a float
+        for (name, args) in [("exp", vec![i(100)]), ("cosh", vec![i(100)]),
+            ("pow", vec![i(10), i(50)])] {
+            assert_eq!(error(name, args),
+                Some("OverflowError: math range error".to_string()));
+        }
+        assert_eq!(error("floor", vec![import_module().get_attr("inf")]),
+            Some("OverflowError: cannot convert float infinity to integer"
+                .to_string()));
+    }
+
+    #[test]
+    fn math_integer_functions() {
+        assert_eq!(call("gcd", vec![i(12), i(-18), i(30)]), i(6));
+        assert_eq!(call("lcm", vec![i(4), i(6)]), i(12));
+        assert_eq!(call("factorial", vec![i(5)]), i(120));
+        assert_eq!(call("comb", vec![i(30), i(15)]), i(155117520));
+        assert_eq!(call("comb", vec![i(3), i(5)]), i(0));
+        assert_eq!(call("perm", vec![i(5), i(2)]), i(20));
+        assert_eq!(call("perm", vec![i(4)]), i(24));
+        assert_eq!(call("isqrt", vec![i(99)]), i(9));
+        let items = Value::Tuple(TupleType::new(vec![i(2), i(3), i(4)]));
+        assert_eq!(call("prod", vec![items]), i(24));
+
+        assert_eq!(error("factorial", vec![i(-1)]), Some("ValueError: \
+            factorial() not defined for negative values".to_string()));
+        assert_eq!(error("factorial", vec![i(20)]),
+            Some("OverflowError: factorial() result out of range".to_string()));
+        assert_eq!(error("gcd", vec![f(1.5)]), Some("TypeError: 'float' \
+            object cannot be interpreted as an integer".to_string()));
     }
 }
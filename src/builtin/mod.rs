@@ -1,56 +1,152 @@
 pub mod sys;
 pub mod math;
+pub mod contextlib;
+pub mod datetime;
+pub mod io;
+pub mod os;
+pub mod pathlib;
+pub mod time;
 
 use super::Value;
 use super::NumericType;
 use super::ListType;
 use super::TupleType;
+use super::RangeType;
+use super::BytesType;
+use types::bytes;
+use types::input_line;
 use super::IOWrapper;
+use super::FileType;
+use super::MemoryFileType;
+use super::IteratorType;
+use super::ModuleType;
+use super::PathType;
+use super::DateTimeType;
+use super::GeneratorType;
+use super::{GenSignal, GenState};
+use std::io::stdin;
 use std::collections::HashMap;
-use std::fs::OpenOptions;
-use std::io::{Write, BufReader, BufRead};
 use std::rc::Rc;
 use std::cell::RefCell;
 
+type Method = Rc<dyn Fn(Vec<Value>, HashMap<String, Value>) -> Value>;
+type Function = fn(Vec<Value>, HashMap<String, Value>) -> Value;
+type Loader = fn() -> Value;
+
+// Builds a native object for the modules, methods receive the object as their
+// first argument like any other method called through `call_member`
+fn object(name: &str, methods: Vec<(&str, Method)>) -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("__name__".to_string(), Value::Str(name.to_string()));
+    for (attr, method) in methods {
+        tbl.insert(attr.to_string(), Value::Function(method));
+    }
+    Value::Object { tbl: Rc::new(RefCell::new(tbl)) }
+}
+
+// A native object with plain attributes, e.g. `sys.float_info`
+fn record(name: &str, fields: Vec<(&str, Value)>) -> Value {
+    let record = object(name, vec![]);
+    if let Value::Object { ref tbl } = record {
+        for (attr, value) in fields {
+            tbl.borrow_mut().insert(attr.to_string(), value);
+        }
+    }
+    record
+}
+
+// An int for the modules, sizes and timestamps can exceed the runtime's
+// 32-bit integers
+pub(crate) fn int_value<T: Into<i64>>(value: T) -> Value {
+    let value = value.into();
+    if value < i32::MIN as i64 || value > i32::MAX as i64 {
+        panic!("OverflowError: Python int too large to convert to C long")
+    }
+    Value::Number(NumericType::Integer(value as i32))
+}
+
+pub(crate) fn string<S: Into<String>>(value: S) -> Value {
+    Value::Str(value.into())
+}
+
+// Calls a method the way compiled code does, for the module tests
+#[cfg(test)]
+pub(crate) fn call_method(value: &Value, attr: &str, args: Vec<Value>) -> Value {
+    ::call_member(value.clone(), attr, args, HashMap::new())
+}
+
+/// The natively implemented modules, registered with `::module` by name
+pub fn native_modules() -> Vec<(&'static str, Loader)> {
+    vec![
+        ("contextlib", contextlib::import_module),
+        ("datetime", datetime::import_module),
+        ("io", io::import_module),
+        ("math", math::import_module),
+        ("os", os::import_module),
+        ("os.path", os::import_path_module),
+        ("pathlib", pathlib::import_module),
+        ("sys", sys::import_module),
+        ("time", time::import_module)
+    ]
+}
+
 pub fn get_scope() -> HashMap<String, Value> {
     let mut tbl = HashMap::new();
     tbl.insert("print".to_string(), Value::Function(Rc::new(print)));
     tbl.insert("str".to_string(), Value::Function(Rc::new(py_str)));
     tbl.insert("len".to_string(), Value::Function(Rc::new(len)));
     tbl.insert("min".to_string(), Value::Function(Rc::new(min)));
+    tbl.insert("max".to_string(), Value::Function(Rc::new(max)));
+    tbl.insert("sum".to_string(), Value::Function(Rc::new(sum)));
+    tbl.insert("any".to_string(), Value::Function(Rc::new(any)));
+    tbl.insert("all".to_string(), Value::Function(Rc::new(all)));
+    tbl.insert("abs".to_string(), Value::Function(Rc::new(abs)));
+    tbl.insert("round".to_string(), Value::Function(Rc::new(round)));
+    tbl.insert("sorted".to_string(), Value::Function(Rc::new(sorted)));
     tbl.insert("int".to_string(), Value::Function(Rc::new(int)));
     tbl.insert("float".to_string(), Value::Function(Rc::new(float)));
+    tbl.insert("bytes".to_string(), bytes_class("bytes", py_bytes));
+    tbl.insert("bytearray".to_string(), bytes_class("bytearray", bytearray));
     tbl.insert("enumerate".to_string(), Value::Function(Rc::new(enumerate)));
+    tbl.insert("zip".to_string(), Value::Function(Rc::new(zip)));
+    tbl.insert("map".to_string(), Value::Function(Rc::new(map)));
+    tbl.insert("filter".to_string(), Value::Function(Rc::new(filter)));
+    tbl.insert("reversed".to_string(), Value::Function(Rc::new(reversed)));
     tbl.insert("open".to_string(), Value::Function(Rc::new(open)));
+    tbl.insert("input".to_string(), Value::Function(Rc::new(input)));
+    tbl.insert("__import__".to_string(), Value::Function(Rc::new(import)));
+    tbl.insert("range".to_string(), Value::Function(Rc::new(range)));
+    tbl.insert("id".to_string(), Value::Function(Rc::new(id)));
+    tbl.insert("hash".to_string(), Value::Function(Rc::new(hash)));
+    tbl.insert("iter".to_string(), Value::Function(Rc::new(iter)));
+    tbl.insert("next".to_string(), Value::Function(Rc::new(next)));
     tbl
 }
 
+/// `print(*objects, sep=' ', end='\n', file=sys.stdout, flush=False)`,
+/// `file` can be any object with a `write` method
 fn print(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
-    let mut params_iter = params.iter();
-    let mut output = String::new();
-    let value = params_iter.next();
-
-    match value {
-        Some(val) => output.push_str(&format!("{}", val)),
-        None => ()
-    }
+    let text_arg = |name: &str, default: &str| match kwargs.get(name) {
+        Some(&Value::Str(ref string)) => string.clone(),
+        Some(&Value::None) | None => default.to_string(),
+        Some(value) => panic!("TypeError: {} must be None or a string, not {}",
+            name, value.type_name())
+    };
+    let sep = text_arg("sep", " ");
+    let end = text_arg("end", "\n");
 
-    for value in params_iter {
-        output.push_str(&format!(" {}", value))
-    }
+    let mut output = params.iter().map(|value| value.to_string())
+        .collect::<Vec<_>>().join(&sep);
+    output.push_str(&end);
 
-    match kwargs.get("file") {
-        Some(&Value::TextIOWrapper(IOWrapper::File(ref file))) => {
-            file.borrow_mut().write_all(format!("{}\n", output)
-                .as_bytes()).unwrap();
-        },
-        Some(&Value::TextIOWrapper(IOWrapper::Stderr)) => {
-            eprintln!("{}", output)
-        },
-        Some(&Value::TextIOWrapper(IOWrapper::Stdout)) | None => {
-            println!("{}", output)
-        },
-        _ => panic!("print() invalid 'file' argument")
+    let file = match kwargs.get("file") {
+        Some(&Value::None) | None => sys::stdout(),
+        Some(file) => file.clone()
+    };
+    ::call_member(file.clone(), "write", vec![Value::Str(output)],
+        HashMap::new());
+    if kwargs.get("flush").is_some_and(|flush| flush.to_bool()) {
+        ::call_member(file, "flush", vec![], HashMap::new());
     }
     Value::None
 }
@@ -77,46 +173,285 @@ pub fn len(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
             Value::Number(NumericType::Integer(string.len() as i32)),
         Value::List(ref list) => list.borrow().len(),
         Value::Tuple(ref tup) => tup.len(),
+        Value::Range(ref range) => range.len(),
+        Value::Bytes(ref bytes) => bytes.len(),
+        Value::ByteArray(ref bytes) => bytes.borrow().len(),
+        Value::Object { .. } => {
+            ::call_member(value.clone(), "__len__", vec![], HashMap::new())
+        },
         _ => panic!("value has no len()")
     }
 }
 
 // If one positional argument is provided, it should be an iterable, otherwise
-// each positional argument will be compared to each other.
-// TODO probably should implement std::cmp::Ord on Value to get min/max
-pub fn min(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
-    if params.len() == 1 {
-        let mut params_iter = params.iter();
-        let value = params_iter.next().unwrap();
-
-        match *value {
-            Value::Str(ref string) => {
-                if string.is_empty() {
-                    panic!("min() arg is an empty sequence")
-                }
-                Value::Str(string.chars().min().unwrap().to_string())
-            },
-            Value::List(ref list) => list.borrow().min(),
-            Value::Tuple(ref tup) => tup.min(),
-            _ => panic!("min(): value is not iterable")
+// each positional argument will be compared to each other. Accepts `key=` and,
+// for a single iterable, `default=` returned when the iterable is empty.
+pub fn min(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    extremum("min", params, kwargs, |a, b| a < b)
+}
+
+/// Counterpart of `min()` with the same argument handling
+pub fn max(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    extremum("max", params, kwargs, |a, b| a > b)
+}
+
+// Returns the first item for which `better(item, best)` holds against every
+// other item, comparing the results of the `key` function if one is given.
+fn extremum<F>(name: &str, params: Vec<Value>, kwargs: HashMap<String, Value>,
+    better: F) -> Value where F: Fn(&Value, &Value) -> bool {
+    let key = match kwargs.get("key") {
+        Some(&Value::None) | None => None,
+        Some(func) => Some(func.clone())
+    };
+    let default = kwargs.get("default");
+    let items: Vec<Value> = match params.len() {
+        0 => panic!("TypeError: {} expected 1 argument, got 0", name),
+        1 => params[0].clone_seq(),
+        _ => {
+            if default.is_some() {
+                panic!("TypeError: Cannot specify a default for {}() with \
+                    multiple positional arguments", name)
+            }
+            params
         }
-    } else {
-        if params.is_empty() {
-            panic!("min expected 1 argument, 0 were supplied")
+    };
+
+    let mut best: Option<(Value, Value)> = None;
+    for item in items.into_iter() {
+        let item_key = match key {
+            Some(ref func) => func.call(vec![item.clone()], HashMap::new()),
+            None => item.clone()
+        };
+        let replace = match best {
+            Some((ref best_key, _)) => better(&item_key, best_key),
+            None => true
+        };
+
+        if replace {
+            best = Some((item_key, item));
         }
-        let mut iter = params.iter();
-        let mut min_val = iter.next().unwrap();
+    }
+
+    match (best, default) {
+        (Some((_, item)), _) => item,
+        (None, Some(default)) => default.clone(),
+        (None, None) => panic!("ValueError: {}() arg is an empty sequence", name)
+    }
+}
+
+/// Sums the items of an iterable left to right, starting with `start` (0 by
+/// default). Numbers are accumulated in a wider type so long float sums don't
+/// lose precision to the intermediate `f32` results.
+pub fn sum(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let mut params_iter = params.into_iter();
+    let iterable = match params_iter.next() {
+        Some(value) => value,
+        None => panic!("TypeError: sum() takes at least 1 positional argument \
+            (0 given)")
+    };
+    let start = match params_iter.next().or_else(|| kwargs.get("start").cloned()) {
+        Some(Value::Str(_)) => {
+            panic!("TypeError: sum() can't sum strings [use ''.join(seq) \
+                instead]")
+        },
+        Some(start) => start,
+        None => Value::Number(NumericType::Integer(0))
+    };
+
+    let mut int_total: i64 = 0;
+    let mut float_total: Option<f64> = None;
+    // Holds the running total when summing non-numeric values, e.g. lists
+    let mut total: Option<Value> = None;
 
-        for value in iter {
-            if value < min_val {
-                min_val = value
+    match start {
+        Value::Number(NumericType::Integer(val)) => int_total = val as i64,
+        Value::Bool(val) => int_total = val as i64,
+        Value::Number(NumericType::Float(val)) => float_total = Some(val as f64),
+        start => total = Some(start)
+    }
+
+    for item in iterable {
+        if let Some(acc) = total.take() {
+            total = Some(acc + item);
+            continue
+        }
+
+        match item {
+            Value::Number(NumericType::Integer(_)) | Value::Bool(_) => {
+                let val = item.as_index().unwrap();
+                match float_total {
+                    Some(ref mut acc) => *acc += val as f64,
+                    None => int_total += val as i64
+                }
+            },
+            Value::Number(NumericType::Float(val)) => {
+                let acc = float_total.unwrap_or(int_total as f64);
+                float_total = Some(acc + val as f64);
+            },
+            item => {
+                let acc = match float_total {
+                    Some(acc) => Value::Number(NumericType::Float(acc as f32)),
+                    None => sum_integer(int_total)
+                };
+                total = Some(acc + item);
             }
         }
+    }
+
+    match (total, float_total) {
+        (Some(total), _) => total,
+        (None, Some(acc)) => Value::Number(NumericType::Float(acc as f32)),
+        (None, None) => sum_integer(int_total)
+    }
+}
+
+fn sum_integer(total: i64) -> Value {
+    if total > i32::MAX as i64 || total < i32::MIN as i64 {
+        panic!("OverflowError: sum() result out of range")
+    }
+    Value::Number(NumericType::Integer(total as i32))
+}
+
+/// Returns True if any item of the iterable is truthy, short-circuiting
+pub fn any(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: any() takes exactly one argument ({} given)",
+            params.len())
+    }
+    Value::Bool(params[0].iter().into_iter().any(|item| item.to_bool()))
+}
+
+/// Returns True if every item of the iterable is truthy, short-circuiting
+pub fn all(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: all() takes exactly one argument ({} given)",
+            params.len())
+    }
+    Value::Bool(params[0].iter().into_iter().all(|item| item.to_bool()))
+}
 
-        min_val.clone()
+pub fn abs(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: abs() takes exactly one argument ({} given)",
+            params.len())
+    }
+
+    match params[0] {
+        Value::Number(NumericType::Integer(val)) => match val.checked_abs() {
+            Some(val) => Value::Number(NumericType::Integer(val)),
+            None => panic!("OverflowError: abs() result out of range")
+        },
+        Value::Number(NumericType::Float(val)) => {
+            Value::Number(NumericType::Float(val.abs()))
+        },
+        Value::Bool(val) => Value::Number(NumericType::Integer(val as i32)),
+        Value::DateTime(DateTimeType::TimeDelta(ref td)) if td.micros() < 0 => {
+            -params[0].clone()
+        },
+        Value::DateTime(DateTimeType::TimeDelta(_)) => params[0].clone(),
+        Value::Object { .. } => {
+            ::call_member(params[0].clone(), "__abs__", vec![], HashMap::new())
+        },
+        ref value => panic!("TypeError: bad operand type for abs(): '{}'",
+            value.type_name())
+    }
+}
+
+/// Rounds half to even like Python 3. Without `ndigits` the result is an
+/// int, otherwise it keeps the type of the number being rounded.
+pub fn round(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let mut params_iter = params.into_iter();
+    let number = match params_iter.next() {
+        Some(value) => value,
+        None => panic!("TypeError: round() missing required argument \
+            'number' (pos 1)")
+    };
+    let ndigits = match params_iter.next().or_else(|| kwargs.get("ndigits").cloned()) {
+        Some(Value::None) | None => None,
+        Some(ref value) if value.as_index().is_some() => value.as_index(),
+        Some(value) => panic!("TypeError: '{}' object cannot be interpreted \
+            as an integer", value.type_name())
+    };
+
+    match (number, ndigits) {
+        (Value::Number(NumericType::Integer(val)), None) => {
+            Value::Number(NumericType::Integer(val))
+        },
+        (Value::Number(NumericType::Integer(val)), Some(digits)) => {
+            if digits >= 0 {
+                return Value::Number(NumericType::Integer(val))
+            }
+            let pow = match 10i64.checked_pow(-digits as u32) {
+                Some(pow) => pow,
+                None => return Value::Number(NumericType::Integer(0))
+            };
+            let val = val as i64;
+            let (quot, rem) = (val.div_euclid(pow), val.rem_euclid(pow));
+            let quot = if rem * 2 > pow || (rem * 2 == pow && quot % 2 != 0) {
+                quot + 1
+            } else {
+                quot
+            };
+            match quot.checked_mul(pow).filter(|val| {
+                *val >= i32::MIN as i64 && *val <= i32::MAX as i64
+            }) {
+                Some(val) => Value::Number(NumericType::Integer(val as i32)),
+                None => panic!("OverflowError: round() result out of range")
+            }
+        },
+        (Value::Number(NumericType::Float(val)), None) => {
+            if !val.is_finite() {
+                panic!("OverflowError: cannot convert float infinity or NaN \
+                    to integer")
+            }
+            let val = (val as f64).round_ties_even();
+            if val < i32::MIN as f64 || val > i32::MAX as f64 {
+                panic!("OverflowError: round() result out of range")
+            }
+            Value::Number(NumericType::Integer(val as i32))
+        },
+        (Value::Number(NumericType::Float(val)), Some(digits)) => {
+            let pow = 10f64.powi(digits);
+            let rounded = ((val as f64) * pow).round_ties_even() / pow;
+            if rounded.is_finite() {
+                Value::Number(NumericType::Float(rounded as f32))
+            } else {
+                Value::Number(NumericType::Float(val))
+            }
+        },
+        (Value::Bool(val), digits) => {
+            let number = Value::Number(NumericType::Integer(val as i32));
+            let digits = match digits {
+                Some(digits) => Value::Number(NumericType::Integer(digits)),
+                None => Value::None
+            };
+            round(vec![number, digits], HashMap::new())
+        },
+        (value, _) => panic!("TypeError: type {} doesn't define __round__ \
+            method", value.type_name())
     }
 }
 
+/// Returns a new sorted list from the items of any iterable, the sort is
+/// stable and accepts the same `key` and `reverse` keywords as `list.sort()`.
+pub fn sorted(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: sorted expected 1 argument, got {}", params.len())
+    }
+    let key = match kwargs.get("key") {
+        Some(&Value::None) | None => None,
+        Some(func) => Some(func.clone())
+    };
+    let reverse = match kwargs.get("reverse") {
+        Some(value) => value.to_bool(),
+        None => false
+    };
+
+    let mut list = ListType::new(params[0].clone_seq());
+    list.sort(key, reverse);
+    Value::List(Rc::new(RefCell::new(list)))
+}
+
 // TODO implement base keyword arg
 pub fn int(params:Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
     if params.is_empty() {
@@ -139,6 +474,7 @@ pub fn int(params:Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
             }
         },
         Value::Number(NumericType::Integer(_)) => value.clone(),
+        Value::Bool(val) => Value::Number(NumericType::Integer(val as i32)),
         Value::Number(NumericType::Float(val)) => {
             Value::Number(NumericType::Integer(val as i32))
         },
@@ -181,109 +517,347 @@ pub fn float(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
             Value::Number(NumericType::Float(val as f32))
         },
         Value::Number(NumericType::Float(_)) => value.clone(),
+        Value::Bool(val) => Value::Number(NumericType::Float(val as i32 as f32)),
         _ => panic!("float() argument must be a string or a number")
     }
 }
 
-/// This function is currently very different from the Python3 implementation
-/// it is not a generator. It will output a Value::List instead of an enumerate
-/// object that calls the iterable's '__next__' function. Ideally it will be
-/// changed to function like Python3.
-pub fn enumerate(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+// `bytes` and `bytearray` are classes rather than functions so that the
+// `fromhex` class method can be called on them
+fn bytes_class(name: &str, new: fn(Vec<Value>, HashMap<String, Value>) -> Value)
+    -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("__name__".to_string(), Value::Str(name.to_string()));
+    tbl.insert("__new__".to_string(), Value::Function(Rc::new(new)));
+    let fromhex = if name == "bytes" { fromhex } else { bytearray_fromhex };
+    tbl.insert("fromhex".to_string(), Value::Function(Rc::new(fromhex)));
+    Value::Class { tbl }
+}
+
+/// `bytes(source, encoding, errors)`, see `BytesType::from_source`
+pub fn py_bytes(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    BytesType::new_bytes(bytes_source(params, kwargs))
+}
+
+/// `bytearray(source, encoding, errors)`, a mutable `bytes`
+pub fn bytearray(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    BytesType::new_bytearray(bytes_source(params, kwargs))
+}
+
+fn bytes_source(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Vec<u8> {
+    let mut params_iter = params.iter();
+    let source = params_iter.next().or_else(|| kwargs.get("source"));
+    let encoding = params_iter.next().or_else(|| kwargs.get("encoding"));
+    let errors = params_iter.next().or_else(|| kwargs.get("errors"));
+
+    BytesType::from_source(source, encoding, errors)
+}
+
+fn fromhex(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    BytesType::new_bytes(bytes::fromhex(&fromhex_arg(params)))
+}
+
+fn bytearray_fromhex(params: Vec<Value>, _kwargs: HashMap<String, Value>)
+    -> Value {
+    BytesType::new_bytearray(bytes::fromhex(&fromhex_arg(params)))
+}
+
+fn fromhex_arg(params: Vec<Value>) -> String {
+    match params.into_iter().next() {
+        Some(Value::Str(string)) => string,
+        Some(value) => panic!("TypeError: fromhex() argument must be str, \
+            not {}", value.type_name()),
+        None => panic!("TypeError: fromhex() takes exactly one argument \
+            (0 given)")
+    }
+}
+
+/// Returns a lazy enumerate object yielding `(index, value)` tuples, the
+/// iterable (including files) is only advanced as the result is consumed.
+pub fn enumerate(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
     if params.is_empty() {
         panic!("enumerate() takes at most 2 arguments, 0 were given")
     }
     let mut params_iter = params.iter();
     let value = params_iter.next().unwrap();
-    let mut start: i32 = if let Some(val) = params_iter.next() {
-        match *val {
-            Value::Number(NumericType::Integer(i)) => i,
-            _ => panic!("enumerate() 'start' must be integer")
-        }
-    } else {
-        0
+    let count: i32 = match params_iter.next().or_else(|| kwargs.get("start")) {
+        Some(start) => match start.as_index() {
+            Some(start) => start,
+            None => panic!("enumerate() 'start' must be integer")
+        },
+        None => 0
     };
 
-    let vec: Vec<Value> = match *value {
-        Value::Str(ref string) => {
-            string.chars().map(|c| {
-                let tup = Value::Tuple(TupleType::new(vec![
-                    Value::Number(NumericType::Integer(start)),
-                    Value::Str(c.to_string())
-                ]));
-                start += 1;
-                tup
-            }).collect()
-        },
-        Value::List(ref list) => {
-            list.borrow().clone_seq().iter().map(|x| {
-                let tup = Value::Tuple(TupleType::new(vec![
-                    Value::Number(NumericType::Integer(start)),
-                    x.clone()
-                ]));
-                start += 1;
-                tup
-            }).collect()
+    let iter = IteratorType::Enumerate { iter: value.iter(), count };
+    Value::Iterator(Rc::new(RefCell::new(iter)))
+}
+
+/// Aggregates elements from each iterable into tuples, stopping at the
+/// shortest. `strict=True` raises `ValueError` if the lengths differ.
+pub fn zip(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let strict = match kwargs.get("strict") {
+        Some(value) => value.to_bool(),
+        None => false
+    };
+    let iters = params.iter().map(|value| value.iter()).collect();
+
+    let iter = IteratorType::Zip { iters, strict };
+    Value::Iterator(Rc::new(RefCell::new(iter)))
+}
+
+/// Lazily applies the function to the items of the iterables, with multiple
+/// iterables the function receives one argument from each.
+pub fn map(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() < 2 {
+        panic!("TypeError: map() must have at least two arguments.")
+    }
+    let mut params_iter = params.into_iter();
+    let func = params_iter.next().unwrap();
+    let iters = params_iter.map(|value| value.iter()).collect();
+
+    let iter = IteratorType::Map { func, iters };
+    Value::Iterator(Rc::new(RefCell::new(iter)))
+}
 
+/// Lazily yields the items for which the function is true, a `None` function
+/// keeps the items that are truthy themselves.
+pub fn filter(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 2 {
+        panic!("TypeError: filter expected 2 arguments, got {}", params.len())
+    }
+    let mut params_iter = params.into_iter();
+    let func = params_iter.next().unwrap();
+    let iter = params_iter.next().unwrap().iter();
+
+    let iter = IteratorType::Filter { func, iter };
+    Value::Iterator(Rc::new(RefCell::new(iter)))
+}
+
+/// Returns a reverse iterator, objects may provide `__reversed__` or
+/// implement the sequence protocol (`__len__` and `__getitem__`).
+pub fn reversed(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: reversed expected 1 argument, got {}", params.len())
+    }
+    let seq = params.into_iter().next().unwrap();
+
+    let iter = match seq {
+        Value::List(ref list) => {
+            let pos = list.borrow().length();
+            IteratorType::Reversed { seq: seq.clone(), pos }
         },
         Value::Tuple(ref tup) => {
-            tup.clone_seq().iter().map(|x| {
-                let tup = Value::Tuple(TupleType::new(vec![
-                    Value::Number(NumericType::Integer(start)),
-                    x.clone()
-                ]));
-                start += 1;
-                tup
-            }).collect()
+            let pos = tup.length();
+            IteratorType::Reversed { seq: seq.clone(), pos }
+        },
+        Value::Bytes(ref bytes) => {
+            let pos = bytes.length();
+            IteratorType::Reversed { seq: seq.clone(), pos }
+        },
+        Value::ByteArray(ref bytes) => {
+            let pos = bytes.borrow().length();
+            IteratorType::Reversed { seq: seq.clone(), pos }
         },
-        Value::TextIOWrapper(ref iow) => {
-            let file = match *iow {
-                IOWrapper::File(ref file) => file,
-                _ => panic!("unsupported operation")
+        Value::Str(ref string) => {
+            IteratorType::new_str(string.chars().rev().collect())
+        },
+        Value::Range(ref range) => IteratorType::new_range(range.reversed()),
+        Value::Object { ref tbl } => {
+            let (has_reversed, has_seq) = {
+                let tbl = tbl.borrow();
+                (tbl.contains_key("__reversed__"),
+                    tbl.contains_key("__len__") && tbl.contains_key("__getitem__"))
             };
-            let mut f = file.borrow_mut();
-            let reader = BufReader::new(&mut *f);
-            reader.lines().map(|x| {
-                let mut string = x.unwrap().to_string();
-                string.push_str("\n");
-                let tup = Value::Tuple(TupleType::new(vec![
-                    Value::Number(NumericType::Integer(start)),
-                    Value::Str(string)
-                ]));
-                start += 1;
-                tup
-            }).collect()
+
+            if has_reversed {
+                return ::call_member(seq.clone(), "__reversed__", vec![],
+                    HashMap::new())
+            } else if has_seq {
+                let pos = match ::call_member(seq.clone(), "__len__", vec![],
+                    HashMap::new()) {
+                    Value::Number(NumericType::Integer(len)) if len >= 0 => {
+                        len as usize
+                    },
+                    _ => panic!("TypeError: __len__() should return an int \
+                        >= 0")
+                };
+                IteratorType::Reversed { seq: seq.clone(), pos }
+            } else {
+                panic!("TypeError: '{}' object is not reversible",
+                    seq.type_name())
+            }
         },
-        _ => panic!("enumerate() value not iterable")
+        _ => panic!("TypeError: '{}' object is not reversible", seq.type_name())
     };
 
-    Value::List(Rc::new(RefCell::new(ListType::new(vec))))
+    Value::Iterator(Rc::new(RefCell::new(iter)))
 }
 
-pub fn open(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
-    let mut params_iter = params.iter();
-    let filename = match params_iter.next() {
-        Some(&Value::Str(ref s)) => s,
-        Some(_) => unimplemented!(),
-        None => panic!("bad 'file' parameter specified")
+/// `open(file, mode='r', buffering=-1, encoding=None, errors=None,
+/// newline=None)`, see `FileType::open` for the accepted modes
+pub fn open(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let names = ["file", "mode", "buffering", "encoding", "errors", "newline"];
+    if params.len() > names.len() {
+        panic!("TypeError: open() takes at most {} arguments ({} given)",
+            names.len(), params.len())
+    }
+    let mut args: Vec<Option<Value>> = names.iter().enumerate().map(|(i, name)| {
+        match params.get(i).or_else(|| kwargs.get(*name)) {
+            Some(&Value::None) | None => None,
+            Some(value) => Some(value.clone())
+        }
+    }).collect();
+    let filename = match args[0].take() {
+        Some(file) => os::path_arg(&file),
+        None => panic!("TypeError: open() missing required argument 'file' \
+            (pos 1)")
     };
-    let mode = match params_iter.next() {
-        Some(&Value::Str(ref s)) => s,
-        Some(_) => panic!("'mode' must be a str"),
-        None => "r"
+    let mut string_arg = |pos: usize| match args[pos].take() {
+        Some(Value::Str(string)) => Some(string),
+        Some(value) => panic!("TypeError: open() argument '{}' must be str, \
+            not {}", names[pos], value.type_name()),
+        None => None
     };
-    let mode_r = mode.contains("r");
-    let mode_w = mode.contains("w");
-    let result = OpenOptions::new()
-        .read(mode_r)
-        .write(mode_w)
-        .truncate(mode_w)
-        .create(mode_w)
-        .open(filename);
-    let file = match result {
-        Ok(file) => file,
-        Err(err) => panic!("error opening file '{}': {}", filename, err)
+
+    let mode = string_arg(1).unwrap_or_else(|| "r".to_string());
+    let (encoding, errors, newline) = (string_arg(3), string_arg(4),
+        string_arg(5));
+    let buffering = match args[2] {
+        Some(ref value) => match value.as_index() {
+            Some(buffering) => buffering,
+            None => panic!("TypeError: '{}' object cannot be interpreted as \
+                an integer", value.type_name())
+        },
+        None => -1
     };
 
-    Value::TextIOWrapper(IOWrapper::File(Rc::new(RefCell::new(file))))
+    let file = FileType::open(&filename, &mode, buffering, encoding, errors,
+        newline);
+    Value::TextIOWrapper(IOWrapper::new_file(file))
+}
+
+/// `__import__(name, globals=None, locals=None, fromlist=(), level=0)`,
+/// returns the top-level package unless `fromlist` names something to
+/// import from the module itself
+pub fn import(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let name = match params.first().or_else(|| kwargs.get("name")) {
+        Some(&Value::Str(ref name)) => name.clone(),
+        Some(value) => panic!("TypeError: __import__() argument 1 must be str, \
+            not {}", value.type_name()),
+        None => panic!("TypeError: __import__() missing required argument \
+            'name' (pos 1)")
+    };
+    let fromlist = params.get(3).or_else(|| kwargs.get("fromlist"))
+        .is_some_and(|fromlist| fromlist.to_bool());
+
+    if fromlist {
+        ::module::import_module(&name)
+    } else {
+        ::module::import(&name)
+    }
+}
+
+/// `input(prompt='')`, writes the prompt to `sys.stdout` and reads one line
+/// from standard input without its trailing newline. Raises `EOFError` once
+/// the input is exhausted.
+pub fn input(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    if params.len() > 1 {
+        panic!("TypeError: input expected at most 1 argument, got {}",
+            params.len())
+    }
+    let prompt = params.into_iter().next().or_else(|| kwargs.get("prompt").cloned());
+    let stdout = sys::stdout();
+
+    if let Some(prompt) = prompt {
+        ::call_member(stdout.clone(), "write", vec![Value::Str(prompt.to_string())],
+            HashMap::new());
+    }
+    // The prompt has to be visible before blocking on the read
+    ::call_member(stdout, "flush", vec![], HashMap::new());
+
+    match input_line(&mut stdin().lock()) {
+        Some(line) => Value::Str(line),
+        None => panic!("EOFError: EOF when reading a line")
+    }
+}
+
+/// `range(stop)` or `range(start, stop[, step])`, the returned range is lazy
+/// and never materializes its elements.
+pub fn range(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let mut bounds = vec![];
+    for param in params.iter() {
+        match param.as_index() {
+            Some(val) => bounds.push(val),
+            None => panic!("TypeError: '{}' object cannot be interpreted as an \
+                integer", param.type_name())
+        }
+    }
+
+    let range = match bounds.len() {
+        1 => RangeType::new(0, bounds[0], 1),
+        2 => RangeType::new(bounds[0], bounds[1], 1),
+        3 => RangeType::new(bounds[0], bounds[1], bounds[2]),
+        0 => panic!("TypeError: range expected 1 argument, got 0"),
+        n => panic!("TypeError: range expected at most 3 arguments, got {}", n)
+    };
+    Value::Range(range)
+}
+
+/// Returns an integer identifying the object for as long as it is alive
+pub fn id(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: id() takes exactly one argument ({} given)",
+            params.len())
+    }
+    Value::Number(NumericType::Integer(params[0].id()))
+}
+
+/// Returns the hash of a hashable value, numbers that compare equal (including
+/// `True == 1` and `1.0 == 1`) hash the same
+pub fn hash(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    if params.len() != 1 {
+        panic!("TypeError: hash() takes exactly one argument ({} given)",
+            params.len())
+    }
+    Value::Number(NumericType::Integer(params[0].hash_value()))
+}
+
+/// `iter(iterable)` returns a lazy iterator over the value, `iter(callable,
+/// sentinel)` calls `callable` until it returns `sentinel`.
+pub fn iter(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let mut params_iter = params.into_iter();
+    let value = match params_iter.next() {
+        Some(value) => value,
+        None => panic!("TypeError: iter expected at least 1 argument, got 0")
+    };
+
+    match params_iter.next() {
+        Some(sentinel) => {
+            match value {
+                Value::Function(_) | Value::Class { .. } => (),
+                _ => panic!("TypeError: iter(v, w): v must be callable")
+            }
+            let iter = IteratorType::Callable { func: value, sentinel };
+            Value::Iterator(Rc::new(RefCell::new(iter)))
+        },
+        None => value.iter()
+    }
+}
+
+/// Retrieves the next item from the iterator, if the iterator is exhausted
+/// the default is returned when given, otherwise `StopIteration` is raised.
+pub fn next(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let mut params_iter = params.iter();
+    let value = match params_iter.next() {
+        Some(value) => value,
+        None => panic!("TypeError: next expected at least 1 argument, got 0")
+    };
+
+    match value.next() {
+        Some(value) => value,
+        None => match params_iter.next() {
+            Some(default) => default.clone(),
+            None => panic!("StopIteration")
+        }
+    }
 }
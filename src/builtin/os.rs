@@ -0,0 +1,661 @@
+use super::Value;
+use super::ModuleType;
+use super::ListType;
+use super::TupleType;
+use super::GeneratorType;
+use super::{GenSignal, GenState};
+use super::{object, record, int_value, string, Method, Function};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::time::UNIX_EPOCH;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+use ::exception::Exception;
+use module;
+
+pub fn import_module() -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("name".to_string(), string(if cfg!(windows) { "nt" } else { "posix" }));
+    tbl.insert("sep".to_string(), string("/"));
+    tbl.insert("linesep".to_string(), string(if cfg!(windows) { "\r\n" } else { "\n" }));
+    tbl.insert("pathsep".to_string(), string(if cfg!(windows) { ";" } else { ":" }));
+    tbl.insert("curdir".to_string(), string("."));
+    tbl.insert("pardir".to_string(), string(".."));
+    tbl.insert("environ".to_string(), environ());
+
+    let functions: Vec<(&str, Function)> = vec![
+        ("getcwd", getcwd), ("chdir", chdir), ("listdir", listdir),
+        ("mkdir", mkdir), ("makedirs", makedirs), ("remove", remove),
+        ("unlink", remove), ("rename", rename), ("rmdir", rmdir),
+        ("walk", walk), ("stat", stat), ("getenv", getenv), ("getpid", getpid),
+        ("fspath", fspath)
+    ];
+    for (name, func) in functions {
+        tbl.insert(name.to_string(), Value::Function(Rc::new(func)));
+    }
+    // The submodule is bound by the registry once this loader returns
+    tbl.insert("path".to_string(), module::import_module("os.path"));
+    Value::Module(ModuleType::new("os", tbl))
+}
+
+/// `os.path`, Python's `posixpath`
+pub fn import_path_module() -> Value {
+    let mut tbl = HashMap::new();
+    tbl.insert("sep".to_string(), Value::Str("/".to_string()));
+    let functions: Vec<(&str, Function)> = vec![
+        ("join", join), ("exists", exists), ("isfile", isfile),
+        ("isdir", isdir), ("isabs", isabs), ("basename", basename),
+        ("dirname", dirname), ("split", split), ("splitext", splitext),
+        ("abspath", abspath), ("getsize", getsize),
+        ("expanduser", expanduser), ("normpath", normpath),
+        ("relpath", relpath)
+    ];
+    for (name, func) in functions {
+        tbl.insert(name.to_string(), Value::Function(Rc::new(func)));
+    }
+    Value::Module(ModuleType::new("os.path", tbl))
+}
+
+/// Converts a path argument, a str or an `os.PathLike` object implementing
+/// `__fspath__`
+pub fn path_arg(value: &Value) -> String {
+    match *value {
+        Value::Str(ref path) => path.clone(),
+        Value::Path(ref path) => path.as_str().to_string(),
+        Value::Object { ref tbl } if tbl.borrow().contains_key("__fspath__") => {
+            path_arg(&::call_member(value.clone(), "__fspath__", vec![],
+                HashMap::new()))
+        },
+        _ => panic!("TypeError: expected str, bytes or os.PathLike object, \
+            not {}", value.type_name())
+    }
+}
+
+// The path argument at `pos`, `default` when it's missing
+fn path_param(name: &str, params: &[Value], pos: usize, default: Option<&str>)
+    -> String {
+    match (params.get(pos), default) {
+        (Some(value), _) => path_arg(value),
+        (None, Some(default)) => default.to_string(),
+        (None, None) => panic!("TypeError: {}() missing required argument \
+            'path' (pos {})", name, pos + 1)
+    }
+}
+
+
+fn list(values: Vec<Value>) -> Value {
+    Value::List(Rc::new(RefCell::new(ListType::new(values))))
+}
+
+/// Raises the `OSError` subclass matching an I/O error on `path`
+pub fn check<T>(result: io::Result<T>, path: &str) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) => Exception::from_io(&err, Some(path)).raise()
+    }
+}
+
+fn getcwd(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(check(env::current_dir(), ".").display().to_string())
+}
+
+fn chdir(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("chdir", &params, 0, None);
+    check(env::set_current_dir(&path), &path);
+    Value::None
+}
+
+/// `listdir(path='.')`, the names of the entries sorted by name
+fn listdir(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("listdir", &params, 0, Some("."));
+    let mut names = entry_names(&path);
+    names.sort();
+    list(names.into_iter().map(string).collect())
+}
+
+fn entry_names(path: &str) -> Vec<String> {
+    check(fs::read_dir(path), path).map(|entry| {
+        check(entry, path).file_name().to_string_lossy().into_owned()
+    }).collect()
+}
+
+/// `mkdir(path, mode=0o777)`
+fn mkdir(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("mkdir", &params, 0, None);
+    let mode = params.get(1).or_else(|| kwargs.get("mode"));
+    check(create_dir(&path, mode, false), &path);
+    Value::None
+}
+
+/// `makedirs(name, mode=0o777, exist_ok=False)`, creates the missing parent
+/// directories as well
+fn makedirs(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("makedirs", &params, 0, None);
+    let mode = params.get(1).or_else(|| kwargs.get("mode"));
+    let exist_ok = params.get(2).or_else(|| kwargs.get("exist_ok"))
+        .is_some_and(|exist_ok| exist_ok.to_bool());
+
+    if !exist_ok && Path::new(&path).exists() {
+        let err = io::Error::from(io::ErrorKind::AlreadyExists);
+        check(Err::<(), _>(err), &path);
+    }
+    check(create_dir(&path, mode, true), &path);
+    Value::None
+}
+
+/// Creates `path` with the permissions in `mode`, `0o777` by default
+#[cfg(unix)]
+pub fn create_dir(path: &str, mode: Option<&Value>, recursive: bool)
+    -> io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+    let mode = mode.and_then(|mode| mode.as_index()).unwrap_or(0o777);
+    fs::DirBuilder::new().recursive(recursive).mode(mode as u32).create(path)
+}
+
+#[cfg(not(unix))]
+pub fn create_dir(path: &str, _mode: Option<&Value>, recursive: bool)
+    -> io::Result<()> {
+    fs::DirBuilder::new().recursive(recursive).create(path)
+}
+
+fn remove(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("remove", &params, 0, None);
+    check(fs::remove_file(&path), &path);
+    Value::None
+}
+
+fn rename(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let src = path_param("rename", &params, 0, None);
+    let dst = path_param("rename", &params, 1, None);
+    check(fs::rename(&src, &dst), &src);
+    Value::None
+}
+
+fn rmdir(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("rmdir", &params, 0, None);
+    check(fs::remove_dir(&path), &path);
+    Value::None
+}
+
+// The subdirectory and file names of `path`, `None` if it can't be read
+fn scan_dir(path: &str) -> Option<(Vec<String>, Vec<String>)> {
+    let mut dirs = vec![];
+    let mut files = vec![];
+    for entry in fs::read_dir(path).ok()? {
+        let entry = entry.ok()?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.path().is_dir() {
+            dirs.push(name);
+        } else {
+            files.push(name);
+        }
+    }
+    dirs.sort();
+    files.sort();
+    Some((dirs, files))
+}
+
+/// `walk(top, topdown=True)`, a generator of `(dirpath, dirnames,
+/// filenames)` tuples. Top-down, the caller may remove names from
+/// `dirnames` to skip those directories. Unreadable directories are skipped.
+fn walk(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let top = path_param("walk", &params, 0, None);
+    let topdown = params.get(1).or_else(|| kwargs.get("topdown"))
+        .is_none_or(|topdown| topdown.to_bool());
+    let entry = |path: &str, dirs: Vec<String>, files: Vec<String>| {
+        let dirs = list(dirs.into_iter().map(string).collect());
+        Value::Tuple(TupleType::new(vec![string(path.to_string()), dirs,
+            list(files.into_iter().map(string).collect())]))
+    };
+
+    if !topdown {
+        // Every directory is listed after its subdirectories
+        fn visit(path: &str, entries: &mut Vec<(String, Vec<String>, Vec<String>)>) {
+            if let Some((dirs, files)) = scan_dir(path) {
+                for dir in &dirs {
+                    visit(&join_paths(path, dir), entries);
+                }
+                entries.push((path.to_string(), dirs, files));
+            }
+        }
+        let mut entries = vec![];
+        visit(&top, &mut entries);
+        let entries = entries.into_iter()
+            .map(|(path, dirs, files)| entry(&path, dirs, files)).collect();
+        return list(entries).iter()
+    }
+
+    let mut pending = vec![top];
+    // The last yielded entry, its `dirnames` are read back on resume
+    let mut current: Option<(String, Value)> = None;
+    let body = move |signal| {
+        if let GenSignal::Throw(exc) = signal {
+            exc.raise()
+        }
+        if let Some((path, dirs)) = current.take() {
+            let dirs: Vec<Value> = dirs.into_iter().collect();
+            for dir in dirs.iter().rev() {
+                pending.push(join_paths(&path, &dir.to_string()));
+            }
+        }
+        while let Some(path) = pending.pop() {
+            if let Some((dirs, files)) = scan_dir(&path) {
+                let value = entry(&path, dirs, files);
+                current = Some((path, value.index(int_value(1))));
+                return GenState::Yield(value)
+            }
+        }
+        GenState::Return(Value::None)
+    };
+    Value::Generator(GeneratorType::new("walk", Box::new(body)))
+}
+
+/// `stat(path)`, a `stat_result` with the `st_*` attributes. Times are whole
+/// seconds since the epoch.
+pub fn stat(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("stat", &params, 0, None);
+    let meta = check(fs::metadata(&path), &path);
+    let seconds = |time: io::Result<::std::time::SystemTime>| {
+        let time = time.ok().and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+        int_value(time.map_or(0, |time| time.as_secs() as i64))
+    };
+
+    let mut fields = vec![
+        ("st_size", int_value(meta.len() as i64)),
+        ("st_atime", seconds(meta.accessed())),
+        ("st_mtime", seconds(meta.modified()))
+    ];
+    fields.extend(platform_stat(&meta));
+    record("os.stat_result", fields)
+}
+
+#[cfg(unix)]
+fn platform_stat(meta: &fs::Metadata) -> Vec<(&'static str, Value)> {
+    use std::os::unix::fs::MetadataExt;
+    vec![("st_mode", int_value(meta.mode())), ("st_ino", int_value(meta.ino() as i64)),
+        ("st_dev", int_value(meta.dev() as i64)),
+        ("st_nlink", int_value(meta.nlink() as i64)),
+        ("st_uid", int_value(meta.uid())), ("st_gid", int_value(meta.gid())),
+        ("st_ctime", int_value(meta.ctime()))]
+}
+
+#[cfg(not(unix))]
+fn platform_stat(meta: &fs::Metadata) -> Vec<(&'static str, Value)> {
+    let mode = if meta.is_dir() { 0o040777 } else { 0o100666 };
+    vec![("st_mode", int_value(mode)), ("st_ctime", int_value(0))]
+}
+
+/// `os.environ`, a mapping whose changes are applied to the process'
+/// environment
+fn environ() -> Value {
+    let key = |key: &Value| match *key {
+        Value::Str(ref key) => key.clone(),
+        _ => panic!("TypeError: str expected, not {}", key.type_name())
+    };
+    let lookup = move |args: &[Value]| env::var(key(&args[1])).ok();
+    let pairs = || {
+        let mut pairs: Vec<(String, String)> = env::vars().collect();
+        pairs.sort();
+        pairs
+    };
+
+    object("os._Environ", vec![
+        ("__getitem__", Rc::new(move |args: Vec<Value>, _| {
+            match lookup(&args) {
+                Some(value) => string(value),
+                None => panic!("KeyError: {:?}", args[1])
+            }
+        }) as Method),
+        ("__setitem__", Rc::new(move |args: Vec<Value>, _| {
+            match args[2] {
+                Value::Str(ref value) => env::set_var(key(&args[1]), value),
+                ref value => panic!("TypeError: str expected, not {}",
+                    value.type_name())
+            }
+            Value::None
+        })),
+        ("__delitem__", Rc::new(move |args: Vec<Value>, _| {
+            if lookup(&args).is_none() {
+                panic!("KeyError: {:?}", args[1])
+            }
+            env::remove_var(key(&args[1]));
+            Value::None
+        })),
+        ("__contains__", Rc::new(move |args: Vec<Value>, _| {
+            Value::Bool(lookup(&args).is_some())
+        })),
+        ("get", Rc::new(move |args: Vec<Value>, _| {
+            lookup(&args).map(string)
+                .unwrap_or_else(|| args.get(2).cloned().unwrap_or(Value::None))
+        })),
+        ("__len__", Rc::new(|_, _| int_value(env::vars().count() as i64))),
+        ("__iter__", Rc::new(move |_, _| {
+            list(pairs().into_iter().map(|(key, _)| string(key)).collect()).iter()
+        })),
+        ("keys", Rc::new(move |_, _| {
+            list(pairs().into_iter().map(|(key, _)| string(key)).collect())
+        })),
+        ("values", Rc::new(move |_, _| {
+            list(pairs().into_iter().map(|(_, value)| string(value)).collect())
+        })),
+        ("items", Rc::new(move |_, _| {
+            list(pairs().into_iter().map(|(key, value)| {
+                Value::Tuple(TupleType::new(vec![string(key), string(value)]))
+            }).collect())
+        }))
+    ])
+}
+
+/// `getenv(key, default=None)`
+fn getenv(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let key = match params.first().or_else(|| kwargs.get("key")) {
+        Some(&Value::Str(ref key)) => key.clone(),
+        Some(value) => panic!("TypeError: str expected, not {}",
+            value.type_name()),
+        None => panic!("TypeError: getenv() missing 1 required positional \
+            argument: 'key'")
+    };
+    match env::var(&key) {
+        Ok(value) => string(value),
+        Err(_) => params.get(1).or_else(|| kwargs.get("default")).cloned()
+            .unwrap_or(Value::None)
+    }
+}
+
+fn getpid(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    int_value(process::id() as i64)
+}
+
+fn fspath(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(path_param("fspath", &params, 0, None))
+}
+
+// posixpath.join for two components
+fn join_paths(path: &str, name: &str) -> String {
+    if name.starts_with('/') || path.is_empty() {
+        name.to_string()
+    } else if path.ends_with('/') {
+        format!("{}{}", path, name)
+    } else {
+        format!("{}/{}", path, name)
+    }
+}
+
+/// `join(a, *p)`, an absolute component discards everything before it
+fn join(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let first = path_param("join", &params, 0, None);
+    string(params[1..].iter().fold(first, |path, name| {
+        join_paths(&path, &path_arg(name))
+    }))
+}
+
+fn exists(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    Value::Bool(Path::new(&path_param("exists", &params, 0, None)).exists())
+}
+
+fn isfile(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    Value::Bool(Path::new(&path_param("isfile", &params, 0, None)).is_file())
+}
+
+fn isdir(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    Value::Bool(Path::new(&path_param("isdir", &params, 0, None)).is_dir())
+}
+
+fn isabs(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    Value::Bool(path_param("isabs", &params, 0, None).starts_with('/'))
+}
+
+// Splits after the last slash, trailing slashes of the head are dropped
+// unless it consists of nothing else
+fn split_path(path: &str) -> (String, String) {
+    let idx = path.rfind('/').map_or(0, |idx| idx + 1);
+    let (head, tail) = path.split_at(idx);
+    let head = if head.chars().all(|c| c == '/') {
+        head
+    } else {
+        head.trim_end_matches('/')
+    };
+    (head.to_string(), tail.to_string())
+}
+
+fn basename(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(split_path(&path_param("basename", &params, 0, None)).1)
+}
+
+fn dirname(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(split_path(&path_param("dirname", &params, 0, None)).0)
+}
+
+fn split(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let (head, tail) = split_path(&path_param("split", &params, 0, None));
+    Value::Tuple(TupleType::new(vec![string(head), string(tail)]))
+}
+
+/// Splits off the extension, leading dots of the file name don't start one
+pub fn split_ext(path: &str) -> (String, String) {
+    let name_start = path.rfind('/').map_or(0, |idx| idx + 1);
+    match path.rfind('.') {
+        Some(dot) if dot > name_start &&
+            path[name_start..dot].chars().any(|c| c != '.') => {
+            (path[..dot].to_string(), path[dot..].to_string())
+        },
+        _ => (path.to_string(), String::new())
+    }
+}
+
+fn splitext(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let (root, ext) = split_ext(&path_param("splitext", &params, 0, None));
+    Value::Tuple(TupleType::new(vec![string(root), string(ext)]))
+}
+
+/// Normalizes an absolute version of `path`
+pub fn absolute_path(path: &str) -> String {
+    if path.starts_with('/') {
+        normalize(path)
+    } else {
+        let cwd = check(env::current_dir(), ".").display().to_string();
+        normalize(&join_paths(&cwd, path))
+    }
+}
+
+fn abspath(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(absolute_path(&path_param("abspath", &params, 0, None)))
+}
+
+fn getsize(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("getsize", &params, 0, None);
+    int_value(check(fs::metadata(&path), &path).len() as i64)
+}
+
+/// Replaces a leading `~` with the home directory, `~user` is left as is
+pub fn expand_user(path: &str) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => return path.to_string()
+    };
+    match env::var("HOME") {
+        Ok(home) => {
+            let home = home.trim_end_matches('/');
+            format!("{}{}", if home.is_empty() { "/" } else { home }, rest)
+        },
+        Err(_) => path.to_string()
+    }
+}
+
+fn expanduser(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(expand_user(&path_param("expanduser", &params, 0, None)))
+}
+
+/// Collapses redundant separators and `.` and `..` components without
+/// looking at the file system. POSIX keeps a leading `//` distinct.
+pub fn normalize(path: &str) -> String {
+    if path.is_empty() {
+        return ".".to_string()
+    }
+    let slashes = if path.starts_with("//") && !path.starts_with("///") {
+        2
+    } else if path.starts_with('/') {
+        1
+    } else {
+        0
+    };
+
+    let mut comps: Vec<&str> = vec![];
+    for comp in path.split('/') {
+        match comp {
+            "" | "." => (),
+            ".." if slashes == 0 && comps.last().is_none_or(|last| *last == "..") => {
+                comps.push(comp)
+            },
+            ".." => {
+                comps.pop();
+            },
+            _ => comps.push(comp)
+        }
+    }
+    let normal = format!("{}{}", "/".repeat(slashes), comps.join("/"));
+    if normal.is_empty() { ".".to_string() } else { normal }
+}
+
+fn normpath(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    string(normalize(&path_param("normpath", &params, 0, None)))
+}
+
+/// `relpath(path, start=os.curdir)`
+fn relpath(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let path = path_param("relpath", &params, 0, None);
+    if path.is_empty() {
+        panic!("ValueError: no path specified")
+    }
+    let start = match params.get(1).or_else(|| kwargs.get("start")) {
+        Some(&Value::None) | None => ".".to_string(),
+        Some(start) => path_arg(start)
+    };
+    let (path, start) = (absolute_path(&path), absolute_path(&start));
+    let path: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    let start: Vec<&str> = start.split('/').filter(|c| !c.is_empty()).collect();
+
+    let common = path.iter().zip(start.iter()).take_while(|&(a, b)| a == b)
+        .count();
+    let mut rel = vec![".."; start.len() - common];
+    rel.extend(&path[common..]);
+    string(if rel.is_empty() { ".".to_string() } else { rel.join("/") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::exception;
+
+    fn call(module: &Value, attr: &str, args: Vec<&str>) -> Value {
+        let args = args.into_iter().map(string).collect();
+        ::call_member(module.clone(), attr, args, HashMap::new())
+    }
+
+    fn strings(values: Vec<&str>) -> Value {
+        list(values.into_iter().map(string).collect())
+    }
+
+    #[test]
+    fn os_path_strings() {
+        let path = import_path_module();
+        let tuple = |a: &str, b: &str| Value::Tuple(TupleType::new(vec![
+            string(a.to_string()), string(b.to_string())]));
+
+        assert_eq!(call(&path, "join", vec!["a", "b/", "c"]), string("a/b/c"));
+        assert_eq!(call(&path, "join", vec!["a", "/b", "c"]), string("/b/c"));
+        assert_eq!(call(&path, "basename", vec!["/a/b.txt"]), string("b.txt"));
+        assert_eq!(call(&path, "dirname", vec!["/a//b"]), string("/a"));
+        assert_eq!(call(&path, "dirname", vec!["//b"]), string("//"));
+        assert_eq!(call(&path, "splitext", vec!["a/b.tar.gz"]), tuple("a/b.tar", ".gz"));
+        assert_eq!(call(&path, "splitext", vec!["a/.bashrc"]), tuple("a/.bashrc", ""));
+        assert_eq!(call(&path, "normpath", vec!["/a/./b/../../..//c/"]),
+            string("/c"));
+        assert_eq!(call(&path, "normpath", vec!["../a/../.."]), string("../.."));
+        assert_eq!(call(&path, "relpath", vec!["/a/b/c", "/a/d"]),
+            string("../b/c"));
+        assert_eq!(call(&path, "relpath", vec!["/a", "/a"]), string("."));
+        assert_eq!(expand_user("~user/x"), "~user/x");
+    }
+
+    #[test]
+    fn os_file_system() {
+        let os = module::import_module("os");
+        let root = env::temp_dir().join(format!("cannolib_os_{}", process::id()));
+        let root = root.display().to_string();
+        let _ = fs::remove_dir_all(&root);
+        let nested = join_paths(&root, "a/b");
+
+        call(&os, "makedirs", vec![&nested]);
+        fs::write(join_paths(&root, "a/f.txt"), "data").unwrap();
+        call(&os, "mkdir", vec![&join_paths(&root, "c")]);
+        assert_eq!(call(&os, "listdir", vec![&root]), strings(vec!["a", "c"]));
+        assert_eq!(call(&os.get_attr("path"), "getsize",
+            vec![&join_paths(&root, "a/f.txt")]), int_value(4));
+        assert_eq!(call(&os, "stat", vec![&root]).get_attr("st_size").type_name(),
+            "int");
+        let big = join_paths(&root, "big");
+        fs::File::create(&big).unwrap().set_len(1 << 32).unwrap();
+        let too_large = exception::catch(|| {
+            call(&os.get_attr("path"), "getsize", vec![&big])
+        });
+        assert_eq!(too_large.err().map(|exc| exc.to_string()),
+            Some("OverflowError: Python int too large to convert to C long"
+                .to_string()));
+
+        // Top-down walks can prune directories through `dirnames`
+        let walk = call(&os, "walk", vec![&root]);
+        let mut seen = vec![];
+        for entry in walk {
+            seen.push(entry.index(int_value(0)).to_string());
+            if entry.index(int_value(0)).to_string().ends_with("/a") {
+                entry.index(int_value(1)).set_index(int_value(0), string("missing"));
+            }
+        }
+        assert_eq!(seen, vec![root.clone(), join_paths(&root, "a"),
+            join_paths(&root, "c")]);
+        let bottom_up: Vec<String> = ::call_member(os.clone(), "walk",
+            vec![string(root.clone()), Value::Bool(false)], HashMap::new())
+            .into_iter().map(|entry| entry.index(int_value(0)).to_string()).collect();
+        assert_eq!(bottom_up, vec![nested.clone(), join_paths(&root, "a"),
+            join_paths(&root, "c"), root.clone()]);
+
+        call(&os, "rename", vec![&join_paths(&root, "a/f.txt"),
+            &join_paths(&root, "g.txt")]);
+        call(&os, "remove", vec![&join_paths(&root, "g.txt")]);
+        let missing = exception::catch(|| {
+            call(&os, "remove", vec![&join_paths(&root, "g.txt")])
+        }).err().unwrap();
+        assert_eq!(missing.name(), "FileNotFoundError");
+        let exists = exception::catch(|| call(&os, "makedirs", vec![&nested]));
+        assert_eq!(exists.err().map(|exc| exc.name().to_string()),
+            Some("FileExistsError".to_string()));
+        let not_empty = exception::catch(|| call(&os, "rmdir", vec![&root]));
+        assert_eq!(not_empty.err().map(|exc| exc.is("OSError")), Some(true));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn os_environ() {
+        let os = module::import_module("os");
+        let environ = os.get_attr("environ");
+        let key = string("CANNOLIB_OS_TEST".to_string());
+
+        environ.set_index(key.clone(), string("value".to_string()));
+        assert_eq!(call(&os, "getenv", vec!["CANNOLIB_OS_TEST"]),
+            string("value".to_string()));
+        assert_eq!(key.contained_in(&environ), true);
+        environ.del_index(key.clone());
+        assert_eq!(environ.index(string("PATH".to_string())),
+            string(env::var("PATH").unwrap()));
+        assert_eq!(::call_member(environ, "get", vec![key, int_value(0)],
+            HashMap::new()), int_value(0));
+        assert_eq!(os.get_attr("path").is(&module::import_module("os.path")),
+            true);
+    }
+}
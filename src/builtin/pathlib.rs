@@ -0,0 +1,110 @@
+use super::Value;
+use super::ModuleType;
+use super::PathType;
+use super::os;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub fn import_module() -> Value {
+    let mut tbl = HashMap::new();
+    for name in &["Path", "PosixPath", "PurePath", "PurePosixPath"] {
+        tbl.insert(name.to_string(), Value::Function(Rc::new(path)));
+    }
+    Value::Module(ModuleType::new("pathlib", tbl))
+}
+
+/// `Path(*pathsegments)`, the segments are joined like with `/`
+fn path(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    Value::Path(params.iter().fold(PathType::new(""), |path, segment| {
+        path.join(&os::path_arg(segment))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+    use ::{NumericType, TupleType, exception};
+    use builtin::{string, call_method};
+
+    fn names(paths: Value) -> Vec<String> {
+        paths.into_iter().map(|path| path.get_attr("name").to_string()).collect()
+    }
+
+    #[test]
+    fn pathlib_pure_paths() {
+        let root = path(vec![string("/usr"), string("lib")], HashMap::new());
+        let path = root.clone() / string("python3.6") / string("os.py");
+
+        assert_eq!(path.to_string(), "/usr/lib/python3.6/os.py");
+        assert_eq!(format!("{:?}", path), "PosixPath('/usr/lib/python3.6/os.py')");
+        assert_eq!(path.get_attr("stem"), string("os"));
+        assert_eq!(path.get_attr("suffix"), string(".py"));
+        assert_eq!(path.get_attr("parent").get_attr("parent"), root);
+        assert_eq!(path.get_attr("parts"), Value::Tuple(TupleType::new(vec![
+            string("/"), string("usr"), string("lib"), string("python3.6"),
+            string("os.py")])));
+        assert_eq!(string("a") / Value::Path(PathType::new("/b")),
+            Value::Path(PathType::new("/b")));
+        assert_eq!(call_method(&path, "with_suffix", vec![string(".pyc")]).to_string(),
+            "/usr/lib/python3.6/os.pyc");
+        assert_eq!(os::path_arg(&path), "/usr/lib/python3.6/os.py");
+
+        let error = exception::catch(|| {
+            path.clone() / Value::Number(NumericType::Integer(1))
+        });
+        assert_eq!(error.err().map(|exc| exc.name().to_string()),
+            Some("TypeError".to_string()));
+    }
+
+    #[test]
+    fn pathlib_file_system() {
+        let dir = env::temp_dir().join(format!("cannolib_pathlib_{}",
+            process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let root = Value::Path(PathType::new(&dir.display().to_string()));
+        let nested = root.clone() / string("a") / string("b");
+        let kwargs = |names: Vec<&str>| names.into_iter()
+            .map(|name| (name.to_string(), Value::Bool(true))).collect();
+
+        ::call_member(nested.clone(), "mkdir", vec![],
+            kwargs(vec!["parents", "exist_ok"]));
+        ::call_member(nested.clone(), "mkdir", vec![], kwargs(vec!["exist_ok"]));
+        let file = root.clone() / string("a") / string("notes.txt");
+        assert_eq!(call_method(&file, "write_text", vec![string("héllo")]),
+            Value::Number(NumericType::Integer(5)));
+        assert_eq!(call_method(&file, "read_text", vec![]), string("héllo"));
+        assert_eq!(call_method(&file, "read_bytes", vec![]).to_string(),
+            "b'h\\xc3\\xa9llo'");
+        assert_eq!(call_method(&file, "is_file", vec![]), Value::Bool(true));
+        assert_eq!(call_method(&nested, "is_dir", vec![]), Value::Bool(true));
+
+        fs::write(dir.join("a/b/deep.txt"), "").unwrap();
+        assert_eq!(names(call_method(&root, "iterdir", vec![])), vec!["a"]);
+        assert_eq!(names(call_method(&root, "glob", vec![string("*/*.txt")])),
+            vec!["notes.txt"]);
+        assert_eq!(names(call_method(&root, "rglob", vec![string("*.txt")])),
+            vec!["notes.txt", "deep.txt"]);
+        let resolved = call_method(&(nested.clone() / string("..")), "resolve", vec![]);
+        assert_eq!(resolved.to_string(),
+            fs::canonicalize(dir.join("a")).unwrap().display().to_string());
+
+        let opened = call_method(&file, "open", vec![string("a")]);
+        call_method(&opened, "write", vec![string("!")]);
+        call_method(&opened, "close", vec![]);
+        assert_eq!(::builtin::open(vec![file.clone()], HashMap::new())
+            .into_iter().next(), Some(string("héllo!")));
+
+        call_method(&file, "unlink", vec![]);
+        let missing = exception::catch(|| call_method(&file, "unlink", vec![]));
+        assert_eq!(missing.err().map(|exc| exc.name().to_string()),
+            Some("FileNotFoundError".to_string()));
+        let exists = exception::catch(|| call_method(&nested, "mkdir", vec![]));
+        assert_eq!(exists.err().map(|exc| exc.name().to_string()),
+            Some("FileExistsError".to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
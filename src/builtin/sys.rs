@@ -1,20 +1,333 @@
 use super::Value;
+use super::ModuleType;
+use super::NumericType;
 use super::ListType;
+use super::TupleType;
 use super::IOWrapper;
+use super::{object, record, int_value, Method};
 use std::env;
+use std::mem;
+use std::path::Path;
+use std::process;
+use std::io::{self, Write};
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use ::exception::{self, Exception};
+use types;
+use module;
+
+thread_local! {
+    // Only reported back by `getrecursionlimit`, compiled code runs on the
+    // native stack and doesn't count its frames
+    static RECURSION_LIMIT: Cell<i32> = const { Cell::new(1000) };
+    // The message and status of the last `sys.exit` call, the exception only
+    // carries the message so `sys.exit(2)` and `sys.exit("2")` look the same
+    static EXIT_CODE: RefCell<Option<(String, Option<i32>)>> =
+        const { RefCell::new(None) };
+}
+
+// The Python version whose semantics Cannoli follows
+const VERSION_INFO: (i32, i32, i32) = (3, 6, 5);
 
 pub fn import_module() -> Value {
+    let (major, minor, micro) = VERSION_INFO;
+    let version = format!("{}.{}.{} (cannolib {})", major, minor, micro,
+        env!("CARGO_PKG_VERSION"));
+    let byteorder = if cfg!(target_endian = "little") { "little" } else { "big" };
     let mut tbl = HashMap::new();
-    tbl.insert("__name__".to_string(), Value::Str("sys".to_string()));
     tbl.insert("argv".to_string(), setup_argv());
     tbl.insert("stderr".to_string(), Value::TextIOWrapper(IOWrapper::Stderr));
-    Value::Class { tbl }
+    tbl.insert("stdout".to_string(), Value::TextIOWrapper(IOWrapper::Stdout));
+    tbl.insert("__stdout__".to_string(), Value::TextIOWrapper(IOWrapper::Stdout));
+    tbl.insert("stdin".to_string(), Value::TextIOWrapper(IOWrapper::Stdin));
+    tbl.insert("exit".to_string(), Value::Function(Rc::new(exit)));
+    tbl.insert("version".to_string(), Value::Str(version));
+    tbl.insert("version_info".to_string(), Value::Tuple(TupleType::new(vec![
+        int_value(major), int_value(minor), int_value(micro),
+        Value::Str("final".to_string()), int_value(0)])));
+    tbl.insert("platform".to_string(), Value::Str(platform().to_string()));
+    tbl.insert("byteorder".to_string(), Value::Str(byteorder.to_string()));
+    tbl.insert("maxsize".to_string(), int_value(i32::MAX));
+    tbl.insert("path".to_string(), setup_path());
+    tbl.insert("modules".to_string(), modules());
+    tbl.insert("float_info".to_string(), float_info());
+    tbl.insert("getrecursionlimit".to_string(),
+        Value::Function(Rc::new(getrecursionlimit)));
+    tbl.insert("setrecursionlimit".to_string(),
+        Value::Function(Rc::new(setrecursionlimit)));
+    tbl.insert("getsizeof".to_string(), Value::Function(Rc::new(getsizeof)));
+    Value::Module(ModuleType::new("sys", tbl))
+}
+
+/// The current `sys.stdout`, where `print()` writes by default
+pub fn stdout() -> Value {
+    match module::get("sys") {
+        Some(sys) => sys.get_attr("stdout"),
+        None => Value::TextIOWrapper(IOWrapper::Stdout)
+    }
+}
+
+/// Replaces `sys.stdout`, returning the previous value
+pub fn set_stdout(value: Value) -> Value {
+    let sys = module::import_module("sys");
+    let previous = sys.get_attr("stdout");
+    ::attr_assign(sys, "stdout", value);
+    previous
 }
 
 fn setup_argv() -> Value {
     let args: Vec<_> = env::args().map(|x| Value::Str(x)).collect();
     Value::List(Rc::new(RefCell::new(ListType::new(args))))
 }
+
+// The script's directory followed by the entries of PYTHONPATH
+fn setup_path() -> Value {
+    let script_dir = env::args().next().and_then(|script| {
+        Path::new(&script).parent().map(|dir| dir.display().to_string())
+    }).unwrap_or_default();
+    let mut path = vec![Value::Str(script_dir)];
+    if let Some(paths) = env::var_os("PYTHONPATH") {
+        path.extend(env::split_paths(&paths)
+            .map(|dir| Value::Str(dir.display().to_string())));
+    }
+    Value::List(Rc::new(RefCell::new(ListType::new(path))))
+}
+
+// CPython's names for the operating systems
+fn platform() -> &'static str {
+    match env::consts::OS {
+        "macos" => "darwin",
+        "windows" => "win32",
+        os => os
+    }
+}
+
+/// `sys.exit(code=None)` raises `SystemExit`, `with` blocks and `finally`
+/// clauses run on the way out. Once uncaught the process ends through
+/// `system_exit`.
+fn exit(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    exception::install_hook();
+    let (msg, status) = match params.into_iter().next() {
+        Some(Value::None) | None => (String::new(), Some(0)),
+        Some(Value::Bool(code)) => ((code as i32).to_string(), Some(code as i32)),
+        Some(Value::Number(NumericType::Integer(code))) =>
+            (code.to_string(), Some(code)),
+        Some(code) => (code.to_string(), None)
+    };
+    EXIT_CODE.with(|exit| *exit.borrow_mut() = Some((msg.clone(), status)));
+
+    if msg.is_empty() {
+        panic!("SystemExit")
+    } else {
+        panic!("SystemExit: {}", msg)
+    }
+}
+
+/// The exit status for a `SystemExit`, `None` when the code isn't an integer
+/// and gets printed to stderr instead. Exceptions not raised by `sys.exit`
+/// only have their message to go by.
+fn exit_status(exc: &Exception) -> Option<i32> {
+    let recorded = EXIT_CODE.with(|exit| exit.borrow().clone());
+    match recorded {
+        Some((ref msg, status)) if msg == exc.msg() => status,
+        _ if exc.msg().is_empty() => Some(0),
+        _ => exc.msg().parse().ok()
+    }
+}
+
+/// Ends the process for an uncaught `SystemExit`. Buffered output is flushed
+/// first, an integer code is the exit status and any other code is printed
+/// to stderr with a status of 1.
+pub fn system_exit(exc: &Exception) -> ! {
+    types::flush_all();
+    let status = exit_status(exc).unwrap_or_else(|| {
+        eprintln!("{}", exc.msg());
+        1
+    });
+    let _ = io::stderr().flush();
+    process::exit(status)
+}
+
+// A mapping of module names to the modules imported so far, a view of the
+// `::module` cache
+fn modules() -> Value {
+    let names = || {
+        let names = module::loaded().into_iter().map(Value::Str).collect();
+        Value::List(Rc::new(RefCell::new(ListType::new(names))))
+    };
+    let key = |key: &Value| match *key {
+        Value::Str(ref name) => name.clone(),
+        _ => panic!("KeyError: {:?}", key)
+    };
+
+    object("dict", vec![
+        ("__getitem__", Rc::new(move |args: Vec<Value>, _| {
+            match module::get(&key(&args[1])) {
+                Some(module) => module,
+                None => panic!("KeyError: {:?}", args[1])
+            }
+        }) as Method),
+        ("__setitem__", Rc::new(move |args: Vec<Value>, _| {
+            module::insert(&key(&args[1]), args[2].clone());
+            Value::None
+        })),
+        ("__delitem__", Rc::new(move |args: Vec<Value>, _| {
+            match module::remove(&key(&args[1])) {
+                Some(_) => Value::None,
+                None => panic!("KeyError: {:?}", args[1])
+            }
+        })),
+        ("get", Rc::new(|args: Vec<Value>, _| {
+            let module = match args[1] {
+                Value::Str(ref name) => module::get(name),
+                _ => None
+            };
+            module.unwrap_or_else(|| args.get(2).cloned().unwrap_or(Value::None))
+        })),
+        ("__contains__", Rc::new(|args: Vec<Value>, _| {
+            Value::Bool(match args[1] {
+                Value::Str(ref name) => module::get(name).is_some(),
+                _ => false
+            })
+        })),
+        ("__len__", Rc::new(|_, _| int_value(module::loaded().len() as i32))),
+        ("__iter__", Rc::new(move |_, _| names().iter())),
+        ("keys", Rc::new(move |_, _| names()))
+    ])
+}
+
+// Cannoli floats are single precision, their limits are reported
+fn float_info() -> Value {
+    let float = |value: f32| Value::Number(NumericType::Float(value));
+    record("sys.float_info", vec![
+        ("max", float(f32::MAX)), ("max_exp", int_value(f32::MAX_EXP)),
+        ("max_10_exp", int_value(f32::MAX_10_EXP)),
+        ("min", float(f32::MIN_POSITIVE)), ("min_exp", int_value(f32::MIN_EXP)),
+        ("min_10_exp", int_value(f32::MIN_10_EXP)), ("dig", int_value(f32::DIGITS)),
+        ("mant_dig", int_value(f32::MANTISSA_DIGITS as i32)),
+        ("epsilon", float(f32::EPSILON)), ("radix", int_value(f32::RADIX as i32)),
+        ("rounds", int_value(1))
+    ])
+}
+
+fn getrecursionlimit(_params: Vec<Value>, _kwargs: HashMap<String, Value>)
+    -> Value {
+    int_value(RECURSION_LIMIT.with(|limit| limit.get()))
+}
+
+fn setrecursionlimit(params: Vec<Value>, _kwargs: HashMap<String, Value>)
+    -> Value {
+    let limit = match params.first() {
+        Some(value) => match value.as_index() {
+            Some(limit) => limit,
+            None => panic!("TypeError: '{}' object cannot be interpreted as \
+                an integer", value.type_name())
+        },
+        None => panic!("TypeError: setrecursionlimit() takes exactly one \
+            argument (0 given)")
+    };
+    if limit < 1 {
+        panic!("ValueError: recursion limit must be greater or equal than 1")
+    }
+    RECURSION_LIMIT.with(|cell| cell.set(limit));
+    Value::None
+}
+
+/// `getsizeof(object[, default])`. There's no comparable object layout, the
+/// sizes CPython reports on 64-bit platforms are given for built-in types
+/// and `default` (or the size of a `Value`) for everything else.
+fn getsizeof(params: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+    let value = match params.first() {
+        Some(value) => value,
+        None => panic!("TypeError: getsizeof() missing required argument \
+            'object' (pos 1)")
+    };
+    let size = match *value {
+        Value::None => 16,
+        Value::Bool(val) => 24 + 4 * val as usize,
+        Value::Number(NumericType::Integer(0)) => 24,
+        Value::Number(NumericType::Integer(val)) => {
+            if val.unsigned_abs() < 1 << 30 { 28 } else { 32 }
+        },
+        Value::Number(NumericType::Float(_)) => 24,
+        Value::Str(ref string) => {
+            // Strings store every character in the width of the widest one
+            let len = string.chars().count();
+            match string.chars().map(|c| c as u32).max().unwrap_or(0) {
+                0..=0x7f => 49 + len,
+                0x80..=0xff => 73 + len,
+                0x100..=0xffff => 74 + 2 * len,
+                _ => 76 + 4 * len
+            }
+        },
+        Value::Bytes(ref bytes) => 33 + bytes.length(),
+        Value::ByteArray(ref bytes) => 57 + bytes.borrow().length(),
+        Value::List(ref list) => 64 + 8 * list.borrow().length(),
+        Value::Tuple(ref tup) => 48 + 8 * tup.length(),
+        Value::Range(_) => 48,
+        _ => match params.get(1).or_else(|| kwargs.get("default")) {
+            Some(default) => return default.clone(),
+            None => mem::size_of::<Value>()
+        }
+    };
+    int_value(size as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attr(name: &str) -> Value {
+        import_module().get_attr(name)
+    }
+
+    fn call(name: &str, args: Vec<Value>) -> Value {
+        ::call_member(import_module(), name, args, HashMap::new())
+    }
+
+    #[test]
+    fn sys_runtime_information() {
+        let modules = attr("modules");
+        let math = Value::Str("math".to_string());
+        module::import_module("math");
+
+        assert_eq!(attr("version_info").index(int_value(0)), int_value(3));
+        assert_eq!(attr("maxsize"), int_value(i32::MAX));
+        assert_eq!(attr("float_info").get_attr("mant_dig"), int_value(24));
+        assert_eq!(math.contained_in(&modules), true);
+        assert_eq!(math.not_contained_in(&modules), false);
+        assert_eq!(Value::Str("missing".to_string()).not_contained_in(&modules),
+            true);
+        assert_eq!(modules.index(math).get_attr("__name__"),
+            Value::Str("math".to_string()));
+        assert_eq!(::builtin::len(vec![modules], HashMap::new()),
+            int_value(module::loaded().len() as i32));
+
+        call("setrecursionlimit", vec![int_value(50)]);
+        assert_eq!(call("getrecursionlimit", vec![]), int_value(50));
+        assert_eq!(call("getsizeof", vec![Value::Str("abc".to_string())]),
+            int_value(52));
+        assert_eq!(call("getsizeof", vec![Value::Bool(true)]), int_value(28));
+    }
+
+    #[test]
+    fn sys_exit_raises_system_exit() {
+        let exits = |args: Vec<Value>| exception::catch(|| call("exit", args))
+            .err().unwrap();
+        let exc = exits(vec![int_value(2)]);
+
+        assert_eq!(exc, Exception::new("SystemExit", "2"));
+        assert_eq!(exit_status(&exc), Some(2));
+        let exc = exits(vec![Value::Str("2".to_string())]);
+        assert_eq!(exc, Exception::new("SystemExit", "2"));
+        assert_eq!(exit_status(&exc), None);
+        let exc = exits(vec![Value::Bool(true)]);
+        assert_eq!(exit_status(&exc), Some(1));
+        let exc = exits(vec![Value::None]);
+        assert_eq!(exit_status(&exc), Some(0));
+        assert_eq!(exits(vec![]), Exception::new("SystemExit", ""));
+        assert_eq!(exc.is("Exception"), false);
+        assert_eq!(exc.is("BaseException"), true);
+    }
+}
@@ -0,0 +1,1064 @@
+use super::Value;
+use super::ModuleType;
+use super::NumericType;
+use super::TupleType;
+use super::{int_value, Function};
+use std::env;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+thread_local! {
+    // Reference point of `monotonic` and `perf_counter`, taken on import
+    static START: Instant = Instant::now();
+    // The local time zone and the value of TZ it was loaded for
+    static LOCAL_ZONE: RefCell<Option<(Option<String>, Rc<TimeZone>)>> =
+        const { RefCell::new(None) };
+}
+
+pub const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday",
+    "Friday", "Saturday", "Sunday"];
+pub const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May",
+    "June", "July", "August", "September", "October", "November", "December"];
+
+pub fn import_module() -> Value {
+    START.with(|_| ());
+    let mut tbl = HashMap::new();
+    let functions: Vec<(&str, Function)> = vec![
+        ("time", time), ("monotonic", monotonic),
+        ("perf_counter", monotonic), ("process_time", process_time),
+        ("sleep", sleep), ("gmtime", gmtime), ("localtime", localtime),
+        ("mktime", mktime), ("strftime", strftime), ("strptime", strptime)
+    ];
+    for (name, func) in functions {
+        tbl.insert(name.to_string(), Value::Function(Rc::new(func)));
+    }
+    Value::Module(ModuleType::new("time", tbl))
+}
+
+fn float(value: f64) -> Value {
+    Value::Number(NumericType::Float(value as f32))
+}
+
+// Ints are 32-bit, timestamps past 2038 don't fit
+fn checked_int(value: i64, name: &str) -> Value {
+    if value < i32::MIN as i64 || value > i32::MAX as i64 {
+        panic!("OverflowError: {}() result out of range", name)
+    }
+    int_value(value)
+}
+
+/// The current time as seconds and microseconds since the epoch
+pub fn now() -> (i64, i32) {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => (elapsed.as_secs() as i64, elapsed.subsec_micros() as i32),
+        Err(err) => {
+            let before = err.duration();
+            match before.subsec_micros() {
+                0 => (-(before.as_secs() as i64), 0),
+                micros => (-(before.as_secs() as i64) - 1,
+                    1_000_000 - micros as i32)
+            }
+        }
+    }
+}
+
+/// `time()`. Floats are single precision so current timestamps are only
+/// accurate to a couple of minutes, `monotonic` is the one to measure with.
+/// `time_ns` is left out, nanosecond counts don't fit a 32-bit int.
+fn time(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let (secs, micros) = now();
+    float(secs as f64 + micros as f64 / 1e6)
+}
+
+/// `monotonic()` and `perf_counter()`, seconds since the module was first
+/// imported which keeps them precise as single precision floats
+fn monotonic(_params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    float(START.with(|start| start.elapsed().as_secs_f64()))
+}
+
+/// `process_time()`, the CPU time of the process from `/proc/self/schedstat`.
+/// Platforms without it fall back to wall-clock time since import, which
+/// also counts time spent sleeping or waiting on I/O.
+fn process_time(_params: Vec<Value>, _kwargs: HashMap<String, Value>)
+    -> Value {
+    let nanos = fs::read_to_string("/proc/self/schedstat").ok().and_then(|stat| {
+        stat.split_whitespace().next().and_then(|nanos| nanos.parse::<u64>().ok())
+    });
+    match nanos {
+        Some(nanos) => float(nanos as f64 / 1e9),
+        None => monotonic(vec![], HashMap::new())
+    }
+}
+
+fn sleep(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let secs = match params.first() {
+        Some(&Value::Number(NumericType::Float(secs))) => secs as f64,
+        Some(value) => match value.as_index() {
+            Some(secs) => secs as f64,
+            None => panic!("TypeError: an integer is required (got type {})",
+                value.type_name())
+        },
+        None => panic!("TypeError: sleep() takes exactly one argument (0 given)")
+    };
+    if secs.is_nan() {
+        panic!("ValueError: Invalid value NaN (not a number)")
+    }
+    if secs < 0.0 {
+        panic!("ValueError: sleep length must be non-negative")
+    }
+    match Duration::try_from_secs_f64(secs) {
+        Ok(duration) => thread::sleep(duration),
+        Err(_) => panic!("OverflowError: sleep length is too large")
+    }
+    Value::None
+}
+
+// A timestamp argument, the current time when it's missing or None
+fn seconds_arg(value: Option<&Value>) -> i64 {
+    match value {
+        None | Some(&Value::None) => now().0,
+        Some(&Value::Number(NumericType::Float(secs))) => {
+            if secs.is_nan() {
+                panic!("ValueError: Invalid value NaN (not a number)")
+            }
+            if !secs.is_finite() {
+                panic!("OverflowError: timestamp out of range for platform time_t")
+            }
+            secs.floor() as i64
+        },
+        Some(value) => match value.as_index() {
+            Some(secs) => secs as i64,
+            None => panic!("TypeError: an integer is required (got type {})",
+                value.type_name())
+        }
+    }
+}
+
+/// `gmtime(secs=None)`, the UTC `struct_time` fields as a tuple
+fn gmtime(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    Tm::from_timestamp(seconds_arg(params.first()), &Zone::utc()).to_tuple()
+}
+
+/// `localtime(secs=None)`, the local `struct_time` fields as a tuple
+fn localtime(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    local_tm(seconds_arg(params.first())).to_tuple()
+}
+
+/// `mktime(t)`, the inverse of `localtime`. Out of range fields carry over,
+/// the 32nd of January is the 1st of February.
+fn mktime(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let tm = match params.first() {
+        Some(value) => tm_arg(value),
+        None => panic!("TypeError: mktime() takes exactly one argument (0 given)")
+    };
+    checked_int(local_seconds(&tm), "mktime")
+}
+
+/// `strftime(format, t=localtime())`
+fn strftime(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let fmt = match params.first() {
+        Some(&Value::Str(ref fmt)) => fmt.clone(),
+        Some(value) => panic!("TypeError: strftime() argument 1 must be str, \
+            not {}", value.type_name()),
+        None => panic!("TypeError: strftime() takes at least 1 argument (0 given)")
+    };
+    let tm = match params.get(1) {
+        Some(value) => {
+            let mut tm = tm_arg(value);
+            check_ranges(&mut tm);
+            let zone = local_zone(local_seconds(&tm));
+            tm.utcoffset = Some(zone.offset);
+            tm.tzname = Some(zone.name);
+            tm
+        },
+        None => local_tm(now().0)
+    };
+    Value::Str(format(&fmt, &tm))
+}
+
+/// `strptime(string, format='%a %b %d %H:%M:%S %Y')`
+fn strptime(params: Vec<Value>, _kwargs: HashMap<String, Value>) -> Value {
+    let string_arg = |pos: usize| match params.get(pos) {
+        Some(&Value::Str(ref string)) => Some(string.clone()),
+        Some(value) => panic!("TypeError: strptime() argument {} must be str, \
+            not {}", pos + 1, value.type_name()),
+        None => None
+    };
+    let string = match string_arg(0) {
+        Some(string) => string,
+        None => panic!("TypeError: strptime() takes at least 1 argument (0 given)")
+    };
+    let fmt = string_arg(1).unwrap_or_else(|| "%a %b %d %H:%M:%S %Y".to_string());
+    parse(&string, &fmt).to_tuple()
+}
+
+// Reads a `struct_time` tuple
+fn tm_arg(value: &Value) -> Tm {
+    let fields: Vec<i32> = match *value {
+        Value::Tuple(_) | Value::List(_) => value.clone().into_iter().map(|field| {
+            match field.as_index() {
+                Some(field) => field,
+                None => panic!("TypeError: an integer is required (got type {})",
+                    field.type_name())
+            }
+        }).collect(),
+        _ => panic!("TypeError: Tuple or struct_time argument required")
+    };
+    if fields.len() != 9 {
+        panic!("TypeError: function takes exactly 9 arguments ({} given)",
+            fields.len())
+    }
+    Tm {
+        year: fields[0], month: fields[1], day: fields[2], hour: fields[3],
+        minute: fields[4], second: fields[5], microsecond: 0,
+        weekday: fields[6], yday: fields[7], isdst: fields[8],
+        utcoffset: None, tzname: None
+    }
+}
+
+// The checks `strftime` makes, zero months and days stand for the first
+fn check_ranges(tm: &mut Tm) {
+    let checks = [(tm.month, 0, 12, "month"), (tm.day, 0, 31, "day of month"),
+        (tm.hour, 0, 23, "hour"), (tm.minute, 0, 59, "minute"),
+        (tm.second, 0, 61, "seconds"), (tm.yday, 0, 366, "day of year")];
+    for &(value, min, max, name) in &checks {
+        if value < min || value > max {
+            panic!("ValueError: {} out of range", name)
+        }
+    }
+    tm.month = tm.month.max(1);
+    tm.day = tm.day.max(1);
+    tm.yday = tm.yday.max(1);
+    tm.weekday = tm.weekday.rem_euclid(7);
+}
+
+pub fn is_leap(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub fn days_in_month(year: i32, month: i32) -> i32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+/// Days from 1970-01-01 to a date of the proleptic Gregorian calendar,
+/// months and days out of range carry over
+pub fn days_from_civil(year: i32, month: i32, day: i32) -> i64 {
+    let year = year as i64 + (month as i64 - 1).div_euclid(12);
+    let month = (month as i64 - 1).rem_euclid(12) + 1;
+    // Years start in March so the leap day comes last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 +
+        day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The date `days` after 1970-01-01 as `(year, month, day)`
+pub fn civil_from_days(days: i64) -> (i32, i32, i32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 -
+        day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 -
+        year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year as i32, month as i32, day as i32)
+}
+
+/// A broken-down time, the fields of `struct_time` and of the `datetime`
+/// types
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tm {
+    pub year: i32,
+    pub month: i32,
+    pub day: i32,
+    pub hour: i32,
+    pub minute: i32,
+    pub second: i32,
+    pub microsecond: i32,
+    /// 0 is Monday
+    pub weekday: i32,
+    /// 1 is the 1st of January
+    pub yday: i32,
+    /// -1 when it's unknown
+    pub isdst: i32,
+    /// Seconds east of UTC, `None` for naive times
+    pub utcoffset: Option<i32>,
+    pub tzname: Option<String>
+}
+
+impl Tm {
+    /// Midnight of a date
+    pub fn date(year: i32, month: i32, day: i32) -> Tm {
+        let days = days_from_civil(year, month, day);
+        Tm {
+            year, month, day, hour: 0, minute: 0, second: 0, microsecond: 0,
+            weekday: (days + 3).rem_euclid(7) as i32,
+            yday: (days - days_from_civil(year, 1, 1)) as i32 + 1,
+            isdst: -1, utcoffset: None, tzname: None
+        }
+    }
+
+    /// The time `secs` after the epoch in `zone`
+    pub fn from_timestamp(secs: i64, zone: &Zone) -> Tm {
+        let local = secs + zone.offset as i64;
+        let (year, month, day) = civil_from_days(local.div_euclid(86400));
+        let time = local.rem_euclid(86400) as i32;
+        Tm {
+            hour: time / 3600, minute: time / 60 % 60, second: time % 60,
+            isdst: zone.isdst as i32, utcoffset: Some(zone.offset),
+            tzname: Some(zone.name.clone()),
+            ..Tm::date(year, month, day)
+        }
+    }
+
+    /// Seconds since the epoch of the fields read as a UTC time
+    pub fn utc_seconds(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86400 +
+            self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    /// The `struct_time` fields
+    pub fn to_tuple(&self) -> Value {
+        Value::Tuple(TupleType::new(vec![int_value(self.year), int_value(self.month),
+            int_value(self.day), int_value(self.hour), int_value(self.minute),
+            int_value(self.second), int_value(self.weekday), int_value(self.yday),
+            int_value(self.isdst)]))
+    }
+}
+
+/// The local time `secs` after the epoch
+pub fn local_tm(secs: i64) -> Tm {
+    Tm::from_timestamp(secs, &local_zone(secs))
+}
+
+/// Seconds since the epoch of a local time, the fields' `isdst` is ignored
+pub fn local_seconds(tm: &Tm) -> i64 {
+    let local = tm.utc_seconds();
+    // The offset in effect at the first guess is the right one unless a
+    // transition lies in between
+    let guess = local - local_zone(local).offset as i64;
+    local - local_zone(guess).offset as i64
+}
+
+/// `strftime` with the names of the C locale. `%f` is the microseconds,
+/// `%z` and `%Z` are empty for times without a zone.
+pub fn format(fmt: &str, tm: &Tm) -> String {
+    let weekday = WEEKDAYS[tm.weekday.rem_euclid(7) as usize];
+    let month = MONTHS[(tm.month - 1).rem_euclid(12) as usize];
+    // Weeks of the year starting on the first Sunday or Monday
+    let week = |first: i32| (tm.yday - 1 + 7 - (tm.weekday + 7 - first) % 7) / 7;
+
+    let mut out = String::new();
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue
+        }
+        let directive = match chars.next() {
+            Some(directive) => directive,
+            None => {
+                out.push('%');
+                break
+            }
+        };
+        let field = match directive {
+            'a' => weekday[..3].to_string(),
+            'A' => weekday.to_string(),
+            'b' | 'h' => month[..3].to_string(),
+            'B' => month.to_string(),
+            'c' => format("%a %b %e %H:%M:%S %Y", tm),
+            'C' => format!("{:02}", tm.year.div_euclid(100)),
+            'd' => format!("{:02}", tm.day),
+            'D' | 'x' => format("%m/%d/%y", tm),
+            'e' => format!("{:2}", tm.day),
+            'f' => format!("{:06}", tm.microsecond),
+            'F' => format("%Y-%m-%d", tm),
+            'H' => format!("{:02}", tm.hour),
+            'I' => format!("{:02}", (tm.hour + 11) % 12 + 1),
+            'j' => format!("{:03}", tm.yday),
+            'm' => format!("{:02}", tm.month),
+            'M' => format!("{:02}", tm.minute),
+            'n' => "\n".to_string(),
+            'p' => if tm.hour < 12 { "AM" } else { "PM" }.to_string(),
+            'R' => format("%H:%M", tm),
+            'S' => format!("{:02}", tm.second),
+            't' => "\t".to_string(),
+            'T' | 'X' => format("%H:%M:%S", tm),
+            'u' => (tm.weekday + 1).to_string(),
+            'U' => format!("{:02}", week(6)),
+            'w' => ((tm.weekday + 1) % 7).to_string(),
+            'W' => format!("{:02}", week(0)),
+            'y' => format!("{:02}", tm.year.rem_euclid(100)),
+            'Y' => tm.year.to_string(),
+            'z' => tm.utcoffset.map_or(String::new(), format_offset),
+            'Z' => tm.tzname.clone().unwrap_or_default(),
+            '%' => "%".to_string(),
+            other => format!("%{}", other)
+        };
+        out.push_str(&field);
+    }
+    out
+}
+
+/// A UTC offset as `+HHMM`, seconds are appended when there are any
+pub fn format_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    let (hours, minutes, seconds) = (offset / 3600, offset / 60 % 60, offset % 60);
+    if seconds == 0 {
+        format!("{}{:02}{:02}", sign, hours, minutes)
+    } else {
+        format!("{}{:02}{:02}{:02}", sign, hours, minutes, seconds)
+    }
+}
+
+/// Parses `string` like `strptime`, fields the format doesn't mention
+/// default to 1900-01-01 00:00:00. Literal text matches case-insensitively
+/// and whitespace in the format matches one or more whitespace characters.
+pub fn parse(string: &str, fmt: &str) -> Tm {
+    let mut parser = TimeParser {
+        text: string.chars().collect(), pos: 0, string, fmt
+    };
+    let mut tm = Tm { isdst: -1, ..Tm::date(1900, 1, 1) };
+    let (mut yday, mut weekday, mut pm, mut hour12) = (None, None, None, false);
+
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            if parser.whitespace() == 0 {
+                parser.mismatch()
+            }
+            continue
+        }
+        if c != '%' {
+            parser.literal(c);
+            continue
+        }
+        let directive = match chars.next() {
+            Some(directive) => directive,
+            None => panic!("ValueError: stray % in format '{}'", fmt)
+        };
+        match directive {
+            'Y' => tm.year = parser.number(4, 4, 0, 9999),
+            'y' => {
+                let year = parser.number(2, 2, 0, 99);
+                tm.year = if year < 69 { 2000 + year } else { 1900 + year };
+            },
+            'm' => tm.month = parser.number(1, 2, 1, 12),
+            'd' | 'e' => tm.day = parser.number(1, 2, 1, 31),
+            'H' => tm.hour = parser.number(1, 2, 0, 23),
+            'I' => {
+                tm.hour = parser.number(1, 2, 1, 12);
+                hour12 = true;
+            },
+            'M' => tm.minute = parser.number(1, 2, 0, 59),
+            'S' => tm.second = parser.number(1, 2, 0, 61),
+            'f' => {
+                let start = parser.pos;
+                let digits = parser.number(1, 6, 0, 999_999);
+                tm.microsecond = digits * 10_i32.pow(6 - (parser.pos - start) as u32);
+            },
+            'j' => yday = Some(parser.number(1, 3, 1, 366)),
+            'w' => weekday = Some((parser.number(1, 1, 0, 6) + 6) % 7),
+            'u' => weekday = Some(parser.number(1, 1, 1, 7) - 1),
+            'p' => pm = Some(parser.name(&["AM", "PM"]) == 1),
+            'a' | 'A' => {
+                let abbrevs: Vec<&str> = WEEKDAYS.iter().map(|day| &day[..3]).collect();
+                weekday = Some(parser.full_or_abbrev(&WEEKDAYS, &abbrevs) as i32);
+            },
+            'b' | 'B' | 'h' => {
+                let abbrevs: Vec<&str> = MONTHS.iter().map(|month| &month[..3]).collect();
+                tm.month = parser.full_or_abbrev(&MONTHS, &abbrevs) as i32 + 1;
+            },
+            'z' => tm.utcoffset = Some(parser.offset()),
+            'Z' => {
+                let name = parser.word();
+                if ["UTC", "GMT"].contains(&name.to_uppercase().as_str()) {
+                    tm.isdst = 0;
+                }
+                tm.tzname = Some(name);
+            },
+            '%' => parser.literal('%'),
+            'n' | 't' => {
+                parser.whitespace();
+            },
+            other => panic!("ValueError: '{}' is a bad directive in format '{}'",
+                other, fmt)
+        }
+    }
+    if parser.pos < parser.text.len() {
+        let rest: String = parser.text[parser.pos..].iter().collect();
+        panic!("ValueError: unconverted data remains: {}", rest)
+    }
+
+    if hour12 {
+        tm.hour = tm.hour % 12 + if pm == Some(true) { 12 } else { 0 };
+    }
+    if let Some(yday) = yday {
+        if yday > 365 + is_leap(tm.year) as i32 {
+            panic!("ValueError: day of year out of range")
+        }
+        let (_, month, day) = civil_from_days(days_from_civil(tm.year, 1, yday));
+        tm.month = month;
+        tm.day = day;
+    }
+    if tm.day > days_in_month(tm.year, tm.month) {
+        panic!("ValueError: day is out of range for month")
+    }
+    let date = Tm::date(tm.year, tm.month, tm.day);
+    Tm { weekday: weekday.unwrap_or(date.weekday), yday: date.yday, ..tm }
+}
+
+struct TimeParser<'a> {
+    text: Vec<char>,
+    pos: usize,
+    // For the error message
+    string: &'a str,
+    fmt: &'a str
+}
+
+impl<'a> TimeParser<'a> {
+    fn mismatch(&self) -> ! {
+        panic!("ValueError: time data '{}' does not match format '{}'",
+            self.string, self.fmt)
+    }
+
+    fn literal(&mut self, c: char) {
+        match self.text.get(self.pos) {
+            Some(t) if t.to_lowercase().eq(c.to_lowercase()) => self.pos += 1,
+            _ => self.mismatch()
+        }
+    }
+
+    fn whitespace(&mut self) -> usize {
+        let start = self.pos;
+        while self.text.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    // Between `min` and `max` digits whose value lies in `low..=high`
+    fn number(&mut self, min: usize, max: usize, low: i32, high: i32) -> i32 {
+        let start = self.pos;
+        while self.pos - start < max &&
+            self.text.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.text[start..self.pos].iter().collect();
+        match digits.parse() {
+            Ok(value) if self.pos - start >= min && low <= value && value <= high => {
+                value
+            },
+            _ => self.mismatch()
+        }
+    }
+
+    // The index of the name the text continues with
+    fn name(&mut self, names: &[&str]) -> usize {
+        for (idx, name) in names.iter().enumerate() {
+            let len = name.chars().count();
+            let candidate: String = self.text.iter().skip(self.pos).take(len).collect();
+            if candidate.to_lowercase() == name.to_lowercase() {
+                self.pos += len;
+                return idx
+            }
+        }
+        self.mismatch()
+    }
+
+    fn full_or_abbrev(&mut self, full: &[&str], abbrevs: &[&str]) -> usize {
+        let names: Vec<&str> = full.iter().chain(abbrevs).cloned().collect();
+        self.name(&names) % full.len()
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.pos;
+        while self.text.get(self.pos).is_some_and(|c| c.is_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            self.mismatch()
+        }
+        self.text[start..self.pos].iter().collect()
+    }
+
+    // `Z` or `+HH[:]MM[[:]SS]`, in seconds east of UTC
+    fn offset(&mut self) -> i32 {
+        let sign = match self.text.get(self.pos) {
+            Some('Z') | Some('z') => {
+                self.pos += 1;
+                return 0
+            },
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => self.mismatch()
+        };
+        self.pos += 1;
+        let hours = self.number(2, 2, 0, 23);
+        let colon = self.text.get(self.pos) == Some(&':');
+        if colon {
+            self.pos += 1;
+        }
+        let minutes = self.number(2, 2, 0, 59);
+        let has_seconds = match self.text.get(self.pos) {
+            Some(':') if colon => {
+                self.pos += 1;
+                true
+            },
+            Some(c) => !colon && c.is_ascii_digit(),
+            None => false
+        };
+        let seconds = if has_seconds { self.number(2, 2, 0, 59) } else { 0 };
+        sign * (hours * 3600 + minutes * 60 + seconds)
+    }
+}
+
+/// A UTC offset in effect, `offset` is in seconds east of UTC
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub offset: i32,
+    pub isdst: bool,
+    pub name: String
+}
+
+impl Zone {
+    pub fn utc() -> Zone {
+        Zone { offset: 0, isdst: false, name: "UTC".to_string() }
+    }
+}
+
+/// The local zone at `secs` after the epoch. The zone is named by TZ, a file
+/// of the zoneinfo database or a POSIX rule, and is `/etc/localtime` if TZ
+/// isn't set. Anything that can't be read is UTC.
+pub fn local_zone(secs: i64) -> Zone {
+    let tz = env::var("TZ").ok();
+    let zone = LOCAL_ZONE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some((ref loaded_for, ref zone)) = *cache {
+            if *loaded_for == tz {
+                return zone.clone()
+            }
+        }
+        let zone = Rc::new(load_zone(tz.as_deref()));
+        *cache = Some((tz, zone.clone()));
+        zone
+    });
+    zone.lookup(secs)
+}
+
+fn load_zone(tz: Option<&str>) -> TimeZone {
+    let utc = TimeZone::fixed(Zone::utc());
+    let name = match tz {
+        Some(name) => name.trim_start_matches(':'),
+        None => return read_tzif("/etc/localtime").unwrap_or(utc)
+    };
+    if name.is_empty() {
+        return utc
+    }
+    let path = if name.starts_with('/') {
+        name.to_string()
+    } else {
+        format!("/usr/share/zoneinfo/{}", name)
+    };
+    read_tzif(&path).or_else(|| parse_rule(name).map(TimeZone::from_rule))
+        .unwrap_or(utc)
+}
+
+// The zones in effect since each transition, with the rule of the zoneinfo
+// file's footer for the times after the last one
+struct TimeZone {
+    transitions: Vec<(i64, usize)>,
+    types: Vec<Zone>,
+    rule: Option<Rule>
+}
+
+impl TimeZone {
+    fn fixed(zone: Zone) -> TimeZone {
+        TimeZone::from_rule(Rule { std: zone, dst: None })
+    }
+
+    fn from_rule(rule: Rule) -> TimeZone {
+        TimeZone { transitions: vec![], types: vec![], rule: Some(rule) }
+    }
+
+    fn lookup(&self, secs: i64) -> Zone {
+        let count = self.transitions.partition_point(|&(time, _)| time <= secs);
+        match self.rule {
+            Some(ref rule) if count == self.transitions.len() &&
+                (count > 0 || self.types.is_empty()) => rule.lookup(secs),
+            // Before the first transition the first standard time applies
+            _ if count == 0 => self.types.iter().find(|zone| !zone.isdst)
+                .or_else(|| self.types.first()).cloned().unwrap_or_else(Zone::utc),
+            _ => self.types[self.transitions[count - 1].1].clone()
+        }
+    }
+}
+
+// A POSIX TZ rule, standard time and the daylight saving time observed
+// between two local times of each year
+struct Rule {
+    std: Zone,
+    dst: Option<(Zone, RuleDate, RuleDate)>
+}
+
+impl Rule {
+    fn lookup(&self, secs: i64) -> Zone {
+        let (dst, start, end) = match self.dst {
+            Some((ref dst, ref start, ref end)) => (dst, start, end),
+            None => return self.std.clone()
+        };
+        let local = secs + self.std.offset as i64;
+        let year = civil_from_days(local.div_euclid(86400)).0;
+        let start = start.local(year) - self.std.offset as i64;
+        let end = end.local(year) - dst.offset as i64;
+        // Southern hemisphere rules end in the year they start after
+        let in_dst = if start < end {
+            start <= secs && secs < end
+        } else {
+            !(end <= secs && secs < start)
+        };
+        if in_dst { dst.clone() } else { self.std.clone() }
+    }
+}
+
+enum Day {
+    // `Jn`, 1 to 365 never counting the 29th of February
+    Julian(i32),
+    // `n`, 0 to 365
+    Zero(i32),
+    // `Mm.w.d`, day `d` (0 is Sunday) of week `w` of month `m`, week 5 being
+    // the last
+    Month(i32, i32, i32)
+}
+
+struct RuleDate {
+    day: Day,
+    // The local time of day in seconds, may exceed a day or be negative
+    time: i64
+}
+
+impl RuleDate {
+    // The local time in seconds since the epoch in `year`
+    fn local(&self, year: i32) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        let days = match self.day {
+            Day::Julian(day) => {
+                jan1 + day as i64 - 1 + (is_leap(year) && day >= 60) as i64
+            },
+            Day::Zero(day) => jan1 + day as i64,
+            Day::Month(month, week, weekday) => {
+                let first = days_from_civil(year, month, 1);
+                // 1970-01-01 was a Thursday, day 4 counting from Sunday
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (weekday as i64 - first_weekday).rem_euclid(7) +
+                    7 * (week as i64 - 1);
+                while day >= first + days_in_month(year, month) as i64 {
+                    day -= 7;
+                }
+                day
+            }
+        };
+        days * 86400 + self.time
+    }
+}
+
+struct RuleParser<'a> {
+    rest: &'a str
+}
+
+impl<'a> RuleParser<'a> {
+    fn eat(&mut self, c: char) -> bool {
+        if self.rest.starts_with(c) {
+            self.rest = &self.rest[c.len_utf8()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    // Letters, or anything quoted in angle brackets (`<+03>`)
+    fn name(&mut self) -> Option<String> {
+        let name = if self.eat('<') {
+            let end = self.rest.find('>')?;
+            let name = &self.rest[..end];
+            self.rest = &self.rest[end + 1..];
+            name
+        } else {
+            let end = self.rest.find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(self.rest.len());
+            let name = &self.rest[..end];
+            self.rest = &self.rest[end..];
+            name
+        };
+        if name.len() < 3 { None } else { Some(name.to_string()) }
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        let end = self.rest.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let number = self.rest[..end].parse().ok()?;
+        self.rest = &self.rest[end..];
+        Some(number)
+    }
+
+    // `[+-]hh[:mm[:ss]]` in seconds
+    fn time(&mut self) -> Option<i64> {
+        let sign = if self.eat('-') { -1 } else { self.eat('+'); 1 };
+        let mut secs = self.number()? * 3600;
+        if self.eat(':') {
+            secs += self.number()? * 60;
+            if self.eat(':') {
+                secs += self.number()?;
+            }
+        }
+        Some(sign * secs)
+    }
+
+    fn date(&mut self) -> Option<RuleDate> {
+        let day = if self.eat('J') {
+            Day::Julian(self.number()? as i32)
+        } else if self.eat('M') {
+            let month = self.number()? as i32;
+            let week = if self.eat('.') { self.number()? as i32 } else { return None };
+            let weekday = if self.eat('.') { self.number()? as i32 } else { return None };
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                return None
+            }
+            Day::Month(month, week, weekday)
+        } else {
+            Day::Zero(self.number()? as i32)
+        };
+        let time = if self.eat('/') { self.time()? } else { 7200 };
+        Some(RuleDate { day, time })
+    }
+}
+
+// Parses a POSIX TZ rule such as `CET-1CEST,M3.5.0,M10.5.0/3`. Offsets are
+// given west of UTC, daylight saving time defaults to an hour ahead on the
+// US dates.
+fn parse_rule(rule: &str) -> Option<Rule> {
+    let mut parser = RuleParser { rest: rule };
+    let std_name = parser.name()?;
+    let std_offset = -parser.time()?;
+    let std = Zone { offset: std_offset as i32, isdst: false, name: std_name };
+    if parser.rest.is_empty() {
+        return Some(Rule { std, dst: None })
+    }
+
+    let dst_name = parser.name()?;
+    let dst_offset = if parser.rest.is_empty() || parser.rest.starts_with(',') {
+        std_offset + 3600
+    } else {
+        -parser.time()?
+    };
+    let (start, end) = if parser.eat(',') {
+        let start = parser.date()?;
+        if !parser.eat(',') {
+            return None
+        }
+        (start, parser.date()?)
+    } else {
+        (RuleDate { day: Day::Month(3, 2, 0), time: 7200 },
+            RuleDate { day: Day::Month(11, 1, 0), time: 7200 })
+    };
+    if !parser.rest.is_empty() {
+        return None
+    }
+    let dst = Zone { offset: dst_offset as i32, isdst: true, name: dst_name };
+    Some(Rule { std, dst: Some((dst, start, end)) })
+}
+
+fn read_tzif(path: &str) -> Option<TimeZone> {
+    parse_tzif(&fs::read(path).ok()?)
+}
+
+// A big-endian signed integer
+fn be(bytes: &[u8]) -> i64 {
+    let value = bytes.iter().fold(0i64, |value, &byte| value << 8 | byte as i64);
+    if bytes.len() == 4 { value as i32 as i64 } else { value }
+}
+
+// Parses a zoneinfo (TZif) file. Version 2 and later files repeat the data
+// with 64-bit times and end with a POSIX rule for later times.
+fn parse_tzif(data: &[u8]) -> Option<TimeZone> {
+    // The counts of UT/local indicators, standard/wall indicators, leap
+    // seconds, transitions, zone types and abbreviation characters
+    let header = |data: &[u8]| -> Option<[usize; 6]> {
+        if data.get(..4)? != b"TZif" {
+            return None
+        }
+        let mut counts = [0; 6];
+        for (idx, count) in counts.iter_mut().enumerate() {
+            *count = be(data.get(20 + 4 * idx..24 + 4 * idx)?) as usize;
+        }
+        Some(counts)
+    };
+    let [isut, isstd, leap, times, types, chars] = header(data)?;
+    let version = *data.get(4)?;
+    let (data, time_size) = if version >= b'2' {
+        let v1_size = times * 5 + types * 6 + chars + leap * 8 + isstd + isut;
+        (data.get(44 + v1_size..)?, 8)
+    } else {
+        (data, 4)
+    };
+
+    let [isut, isstd, leap, times, types, chars] = header(data)?;
+    let mut pos = 44;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        let bytes = data.get(pos..pos + len)?;
+        pos += len;
+        Some(bytes)
+    };
+    let transition_times = take(times * time_size)?;
+    let transition_types = take(times)?;
+    let type_data = take(types * 6)?;
+    let names = take(chars)?;
+    take(leap * (time_size + 4) + isstd + isut)?;
+    let footer = data.get(pos..);
+
+    let types: Vec<Zone> = type_data.chunks(6).map(|zone| {
+        let name = names.get(zone[5] as usize..).unwrap_or(&[]);
+        let end = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        Zone {
+            offset: be(&zone[..4]) as i32, isdst: zone[4] != 0,
+            name: String::from_utf8_lossy(&name[..end]).into_owned()
+        }
+    }).collect();
+    let transitions: Vec<(i64, usize)> = transition_times.chunks(time_size)
+        .zip(transition_types).map(|(time, &idx)| (be(time), idx as usize))
+        .collect();
+    if transitions.iter().any(|&(_, idx)| idx >= types.len()) {
+        return None
+    }
+
+    let rule = match (version >= b'2', footer) {
+        (true, Some(footer)) => {
+            let footer = String::from_utf8_lossy(footer);
+            footer.trim_matches('\n').lines().next().and_then(parse_rule)
+        },
+        _ => None
+    };
+    Some(TimeZone { transitions, types, rule })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_clocks() {
+        let call = |func: Function| func(vec![], HashMap::new());
+        match call(time) {
+            Value::Number(NumericType::Float(secs)) =>
+                assert_eq!((secs as f64 - now().0 as f64).abs() < 1000.0, true),
+            value => panic!("time() returned {}", value)
+        }
+        match call(process_time) {
+            Value::Number(NumericType::Float(secs)) => assert_eq!(secs >= 0.0, true),
+            value => panic!("process_time() returned {}", value)
+        }
+    }
+
+    #[test]
+    fn time_calendar() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(2023, 13, 1), days_from_civil(2024, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+
+        let tm = Tm::from_timestamp(1_000_000_000, &Zone::utc());
+        assert_eq!(tm.to_tuple(), Value::Tuple(TupleType::new(vec![int_value(2001),
+            int_value(9), int_value(9), int_value(1), int_value(46), int_value(40),
+            int_value(6), int_value(252), int_value(0)])));
+        assert_eq!(tm.utc_seconds(), 1_000_000_000);
+        let local = local_tm(1_000_000_000);
+        assert_eq!(local_seconds(&local), 1_000_000_000);
+    }
+
+    #[test]
+    fn time_format_and_parse() {
+        let tm = Tm {
+            microsecond: 5000, utcoffset: Some(-18000), tzname: Some("EST".into()),
+            ..Tm::from_timestamp(1_000_000_000, &Zone::utc())
+        };
+        assert_eq!(format("%a %d %b %Y %I:%M:%S %p %j %f %z %Z %%", &tm),
+            "Sun 09 Sep 2001 01:46:40 AM 252 005000 -0500 EST %");
+        assert_eq!(format("%c|%U|%W|%w", &tm), "Sun Sep  9 01:46:40 2001|36|36|0");
+
+        let parsed = parse("Sunday  9 september 2001 1:46:40 pm +05:30",
+            "%A %d %B %Y %I:%M:%S %p %z");
+        assert_eq!((parsed.year, parsed.month, parsed.day, parsed.hour),
+            (2001, 9, 9, 13));
+        assert_eq!((parsed.weekday, parsed.yday, parsed.utcoffset),
+            (6, 252, Some(19800)));
+        assert_eq!(parse("12.5", "%S.%f").microsecond, 500_000);
+        assert_eq!(parse("2024 060", "%Y %j").day, 29);
+
+        let error = |string: &str, fmt: &str| {
+            ::exception::catch(|| parse(string, fmt)).err().unwrap().to_string()
+        };
+        assert_eq!(error("2001-13", "%Y-%m"), "ValueError: time data '2001-13' \
+            does not match format '%Y-%m'");
+        assert_eq!(error("2001x", "%Y"), "ValueError: unconverted data remains: x");
+        assert_eq!(error("02-30", "%m-%d"),
+            "ValueError: day is out of range for month");
+    }
+
+    #[test]
+    fn time_zone_rules() {
+        let zone = TimeZone::from_rule(parse_rule("CET-1CEST,M3.5.0,M10.5.0/3")
+            .unwrap());
+        let at = |year, month, day, hour: i64| zone.lookup(
+            days_from_civil(year, month, day) * 86400 + hour * 3600);
+        assert_eq!(at(2024, 1, 15, 12).offset, 3600);
+        // Summer time starts at 01:00 UTC on the 31st of March 2024
+        assert_eq!(at(2024, 3, 31, 0).name, "CET");
+        assert_eq!(at(2024, 3, 31, 1).name, "CEST");
+        assert_eq!(at(2024, 10, 27, 0).offset, 7200);
+        assert_eq!(at(2024, 10, 27, 1).offset, 3600);
+
+        let south = TimeZone::from_rule(parse_rule("<-03>3<-02>,M9.1.6/24,M4.1.6/24")
+            .unwrap());
+        assert_eq!(south.lookup(days_from_civil(2024, 1, 1) * 86400).offset, -7200);
+        assert_eq!(south.lookup(days_from_civil(2024, 6, 1) * 86400).offset, -10800);
+        assert_eq!(parse_rule("EST5EDT").unwrap().std.offset, -18000);
+        assert_eq!(parse_rule("Europe/Paris").is_none(), true);
+    }
+
+    #[test]
+    fn time_sleep_arguments() {
+        let error = |secs: f32| ::exception::catch(|| {
+            sleep(vec![Value::Number(NumericType::Float(secs))], HashMap::new())
+        }).err().map(|exc| exc.to_string());
+
+        assert_eq!(sleep(vec![int_value(0)], HashMap::new()), Value::None);
+        assert_eq!(error(-1.0),
+            Some("ValueError: sleep length must be non-negative".to_string()));
+        assert_eq!(error(f32::INFINITY),
+            Some("OverflowError: sleep length is too large".to_string()));
+        assert_eq!(error(f32::MAX),
+            Some("OverflowError: sleep length is too large".to_string()));
+    }
+}
@@ -0,0 +1,251 @@
+//! Runtime support for `with` statements. The compiled form of
+//!
+//! ```python
+//! with manager as target:
+//!     body
+//! ```
+//!
+//! is `context::with(manager, |target| body)`: `__enter__` is called on the
+//! manager, the body runs and `__exit__` is always called afterwards. An
+//! exception escaping the body is passed to `__exit__` and re-raised unless
+//! `__exit__` returns a truthy value.
+use std::collections::HashMap;
+
+use ::Value;
+use ::exception::{self, Exception};
+
+/// Runs `body` with the result of `__enter__`, returns `None` when the body
+/// raised an exception that `__exit__` suppressed.
+pub fn with<T, F>(manager: Value, body: F) -> Option<T>
+    where F: FnOnce(Value) -> T {
+    let target = enter(&manager);
+
+    match exception::catch(|| body(target)) {
+        Ok(result) => {
+            exit(&manager, None);
+            Some(result)
+        },
+        Err(exc) => {
+            if exit(&manager, Some(&exc)) {
+                None
+            } else {
+                exc.raise()
+            }
+        }
+    }
+}
+
+/// Calls `manager.__enter__()`
+pub fn enter(manager: &Value) -> Value {
+    check_manager(manager);
+    ::call_member(manager.clone(), "__enter__", vec![], HashMap::new())
+}
+
+/// Calls `manager.__exit__(type, value, traceback)` and returns whether the
+/// exception should be suppressed
+pub fn exit(manager: &Value, exc: Option<&Exception>) -> bool {
+    check_manager(manager);
+    ::call_member(manager.clone(), "__exit__", exc_info(exc), HashMap::new())
+        .to_bool()
+}
+
+/// The `(type, value, traceback)` arguments of `__exit__`. Cannoli has no
+/// exception objects, the type is given as the exception name and the value
+/// as the "Name: message" string, `exc_from_info` reverses this.
+pub fn exc_info(exc: Option<&Exception>) -> Vec<Value> {
+    match exc {
+        Some(exc) => vec![Value::Str(exc.name().to_string()),
+            Value::Str(exc.to_string()), Value::None],
+        None => vec![Value::None, Value::None, Value::None]
+    }
+}
+
+/// Rebuilds the exception from `__exit__` arguments, `None` when the block
+/// finished without one
+pub fn exc_from_info(args: &[Value]) -> Option<Exception> {
+    match (args.first(), args.get(1)) {
+        (Some(&Value::None), _) | (None, _) => None,
+        (Some(_), Some(value)) if *value != Value::None => {
+            Some(Exception::parse(&value.to_string()))
+        },
+        (Some(exc_type), _) => Some(Exception::new(&exc_type.to_string(), ""))
+    }
+}
+
+fn check_manager(manager: &Value) {
+    match *manager {
+        Value::Object { .. } | Value::TextIOWrapper(_) => (),
+        _ => panic!("TypeError: '{}' object does not support the context \
+            manager protocol", manager.type_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use ::{NumericType, GeneratorType, GenSignal, GenState};
+    use ::builtin::string;
+
+    // A manager recording the calls it receives in `log`
+    fn manager(log: &Rc<RefCell<Vec<String>>>, suppress: bool) -> Value {
+        let (enter_log, exit_log) = (log.clone(), log.clone());
+        let mut tbl = HashMap::new();
+        tbl.insert("__name__".to_string(), string("Manager"));
+        tbl.insert("__enter__".to_string(), Value::Function(Rc::new(
+            move |_, _| {
+                enter_log.borrow_mut().push("enter".to_string());
+                string("target")
+            })));
+        tbl.insert("__exit__".to_string(), Value::Function(Rc::new(
+            move |args: Vec<Value>, _| {
+                exit_log.borrow_mut().push(format!("exit {}", args[2]));
+                Value::Bool(suppress)
+            })));
+        Value::Object { tbl: Rc::new(RefCell::new(tbl)) }
+    }
+
+    fn contextlib(attr: &str, args: Vec<Value>) -> Value {
+        ::call_member(::builtin::contextlib::import_module(), attr, args,
+            HashMap::new())
+    }
+
+    #[test]
+    fn with_object_manager() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let result = with(manager(&log, false), |target| target.to_string());
+        let suppressed = with(manager(&log, true), |_| -> () {
+            panic!("KeyError: 'a'")
+        });
+        let raised = exception::catch(|| {
+            with(manager(&log, false), |_| -> () { panic!("ValueError: bad") })
+        });
+
+        assert_eq!(result, Some("target".to_string()));
+        assert_eq!(suppressed, None);
+        assert_eq!(raised, Err(Exception::new("ValueError", "bad")));
+        assert_eq!(*log.borrow(), vec!["enter", "exit None", "enter",
+            "exit KeyError: 'a'", "enter", "exit ValueError: bad"]);
+    }
+
+    #[test]
+    fn with_open_file() {
+        let path = ::std::env::temp_dir().join("cannolib_context_file");
+        let path = string(path.to_str().unwrap());
+        let file = ::builtin::open(vec![path.clone(), string("w")],
+            HashMap::new());
+
+        with(file.clone(), |f| {
+            ::call_member(f, "write", vec![string("done")], HashMap::new())
+        });
+        assert_eq!(file.get_attr("closed"), Value::Bool(true));
+        assert_eq!(::std::fs::read_to_string(path.to_string()).unwrap(), "done");
+        let _ = ::std::fs::remove_file(path.to_string());
+    }
+
+    #[test]
+    fn contextlib_suppress_and_closing() {
+        let suppressed = with(contextlib("suppress", vec![string("OSError")]),
+            |_| -> () { panic!("FileNotFoundError: missing") });
+        let raised = exception::catch(|| {
+            with(contextlib("suppress", vec![string("KeyError")]),
+                |_| -> () { panic!("IndexError: out of range") })
+        });
+
+        assert_eq!(suppressed, None);
+        assert_eq!(raised.err().map(|exc| exc.name().to_string()),
+            Some("IndexError".to_string()));
+
+        let log = Rc::new(RefCell::new(vec![]));
+        let close_log = log.clone();
+        let mut tbl = HashMap::new();
+        tbl.insert("__name__".to_string(), string("Resource"));
+        tbl.insert("close".to_string(), Value::Function(Rc::new(move |_, _| {
+            close_log.borrow_mut().push("closed".to_string());
+            Value::None
+        })));
+        let resource = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+        with(contextlib("closing", vec![resource]), |_| ());
+        assert_eq!(*log.borrow(), vec!["closed"]);
+    }
+
+    #[test]
+    fn contextlib_contextmanager() {
+        // @contextmanager
+        // def managed():
+        //     try: yield 1
+        //     except KeyError: pass
+        let func = Value::Function(Rc::new(|_, _| {
+            let mut state = 0;
+            let body = move |signal| {
+                state += 1;
+                match (state, signal) {
+                    (1, _) => GenState::Yield(Value::Number(
+                        NumericType::Integer(1))),
+                    (_, GenSignal::Throw(ref exc)) if exc.is("KeyError") => {
+                        GenState::Return(Value::None)
+                    },
+                    (_, GenSignal::Throw(exc)) => exc.raise(),
+                    _ => GenState::Return(Value::None)
+                }
+            };
+            Value::Generator(GeneratorType::new("managed", Box::new(body)))
+        }));
+        let managed = contextlib("contextmanager", vec![func]);
+
+        let value = with(managed.call(vec![], HashMap::new()), |v| v);
+        let suppressed = with(managed.call(vec![], HashMap::new()),
+            |_| -> () { panic!("KeyError: 'a'") });
+        let raised = exception::catch(|| {
+            with(managed.call(vec![], HashMap::new()),
+                |_| -> () { panic!("ValueError: bad") })
+        });
+
+        assert_eq!(value, Some(Value::Number(NumericType::Integer(1))));
+        assert_eq!(suppressed, None);
+        assert_eq!(raised, Err(Exception::new("ValueError", "bad")));
+    }
+
+    #[test]
+    fn contextlib_exit_stack() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let callback_log = log.clone();
+        let callback = Value::Function(Rc::new(move |args: Vec<Value>, _| {
+            callback_log.borrow_mut().push(format!("callback {}", args[0]));
+            Value::None
+        }));
+
+        let result = with(contextlib("ExitStack", vec![]), |stack| {
+            ::call_member(stack.clone(), "callback",
+                vec![callback, string("first")], HashMap::new());
+            ::call_member(stack.clone(), "enter_context",
+                vec![manager(&log, true)], HashMap::new());
+            panic!("ValueError: bad")
+        });
+
+        assert_eq!(result, None::<()>);
+        assert_eq!(*log.borrow(), vec!["enter", "exit ValueError: bad",
+            "callback first"]);
+    }
+
+    #[test]
+    fn contextlib_redirect_stdout() {
+        let output = Rc::new(RefCell::new(String::new()));
+        let write_output = output.clone();
+        let mut tbl = HashMap::new();
+        tbl.insert("__name__".to_string(), string("Capture"));
+        tbl.insert("write".to_string(), Value::Function(Rc::new(
+            move |args: Vec<Value>, _| {
+                write_output.borrow_mut().push_str(&args[1].to_string());
+                Value::None
+            })));
+        let capture = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+        let print = ::builtin::get_scope()["print"].clone();
+
+        with(contextlib("redirect_stdout", vec![capture]), |_| {
+            print.call(vec![string("captured")], HashMap::new())
+        });
+        assert_eq!(*output.borrow(), "captured\n");
+    }
+}
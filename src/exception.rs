@@ -0,0 +1,240 @@
+//! Cannoli has no native exception object, runtime errors are raised with
+//! `panic!` using Python's "ExceptionName: message" format. This module lets
+//! the runtime (and the compiled output) catch those panics again for the few
+//! protocols that rely on exceptions, e.g. `StopIteration` ending a loop.
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+thread_local! {
+    // Number of nested `catch` calls, the panic hook stays silent while > 0
+    static CATCH_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exception {
+    name: String,
+    msg: String
+}
+
+impl Exception {
+    pub fn new(name: &str, msg: &str) -> Exception {
+        Exception { name: name.to_string(), msg: msg.to_string() }
+    }
+
+    /// Splits a panic message of the form "ValueError: message", panics that
+    /// don't follow the convention are treated as a plain `Exception`.
+    pub fn parse(message: &str) -> Exception {
+        if let Some(idx) = message.find(':') {
+            let (name, msg) = message.split_at(idx);
+            if is_exception_name(name) {
+                return Exception::new(name, msg[1..].trim_start())
+            }
+        } else if is_exception_name(message) {
+            return Exception::new(message, "")
+        }
+
+        Exception::new("Exception", message)
+    }
+
+    /// Maps an I/O error to the matching `OSError` subclass, with CPython's
+    /// "[Errno 2] No such file or directory: 'name'" message format
+    pub fn from_io(err: &io::Error, filename: Option<&str>) -> Exception {
+        let name = match err.kind() {
+            io::ErrorKind::NotFound => "FileNotFoundError",
+            io::ErrorKind::PermissionDenied => "PermissionError",
+            io::ErrorKind::AlreadyExists => "FileExistsError",
+            io::ErrorKind::IsADirectory => "IsADirectoryError",
+            io::ErrorKind::NotADirectory => "NotADirectoryError",
+            io::ErrorKind::Interrupted => "InterruptedError",
+            io::ErrorKind::BrokenPipe => "BrokenPipeError",
+            io::ErrorKind::TimedOut => "TimeoutError",
+            _ => "OSError"
+        };
+        let description = err.to_string();
+        let mut msg = match err.raw_os_error() {
+            Some(code) => {
+                let suffix = format!(" (os error {})", code);
+                format!("[Errno {}] {}", code,
+                    description.trim_end_matches(suffix.as_str()))
+            },
+            None => description
+        };
+        if let Some(filename) = filename {
+            msg.push_str(&format!(": '{}'", filename));
+        }
+
+        Exception::new(name, &msg)
+    }
+
+    fn from_payload(payload: Box<dyn Any + Send>) -> Exception {
+        if let Some(msg) = payload.downcast_ref::<&str>() {
+            Exception::parse(msg)
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            Exception::parse(msg)
+        } else {
+            Exception::new("Exception", "")
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+
+    /// Checks the exception type by name, base classes match their built-in
+    /// subclasses (`OSError` matches `FileNotFoundError`). `BaseException`
+    /// matches everything and `Exception` everything but the exits and
+    /// interrupts deriving directly from `BaseException`.
+    pub fn is(&self, name: &str) -> bool {
+        let mut current = Some(self.name.as_str());
+        while let Some(exc) = current {
+            if exc == name {
+                return true
+            }
+            current = base_exception(exc);
+        }
+        match name {
+            "BaseException" => true,
+            "Exception" => !matches!(self.name.as_str(),
+                "SystemExit" | "KeyboardInterrupt" | "GeneratorExit"),
+            _ => false
+        }
+    }
+
+    /// Re-raises the exception, this never returns
+    pub fn raise(&self) -> ! {
+        panic!("{}", self)
+    }
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.msg.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}: {}", self.name, self.msg)
+        }
+    }
+}
+
+/// Runs `func` and returns any raised exception instead of unwinding further.
+/// The default panic message is suppressed while the closure runs.
+pub fn catch<T, F: FnOnce() -> T>(func: F) -> Result<T, Exception> {
+    install_hook();
+
+    CATCH_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let result = panic::catch_unwind(AssertUnwindSafe(func));
+    CATCH_DEPTH.with(|depth| depth.set(depth.get() - 1));
+
+    result.map_err(Exception::from_payload)
+}
+
+/// Replaces the default panic hook: panics are silent while caught, and an
+/// uncaught `SystemExit` ends the process with its exit status instead of
+/// printing a panic message
+pub fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCH_DEPTH.with(|depth| depth.get()) > 0 {
+                return
+            }
+            let payload = info.payload();
+            let message = payload.downcast_ref::<&str>().map(|msg| msg.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned());
+            match message.map(|msg| Exception::parse(&msg)) {
+                Some(ref exc) if exc.name() == "SystemExit" => {
+                    ::builtin::sys::system_exit(exc)
+                },
+                _ => default_hook(info)
+            }
+        }));
+    });
+}
+
+// Parent class of the built-in exceptions that have one besides `Exception`
+fn base_exception(name: &str) -> Option<&'static str> {
+    match name {
+        "FileNotFoundError" | "FileExistsError" | "PermissionError" |
+        "IsADirectoryError" | "NotADirectoryError" | "InterruptedError" |
+        "BrokenPipeError" | "TimeoutError" | "io.UnsupportedOperation" => {
+            Some("OSError")
+        },
+        "IndexError" | "KeyError" => Some("LookupError"),
+        "ZeroDivisionError" | "OverflowError" => Some("ArithmeticError"),
+        "UnicodeDecodeError" | "UnicodeEncodeError" => Some("UnicodeError"),
+        "UnicodeError" => Some("ValueError"),
+        "ModuleNotFoundError" => Some("ImportError"),
+        "RecursionError" | "NotImplementedError" => Some("RuntimeError"),
+        _ => None
+    }
+}
+
+// Python exception names are CamelCase identifiers, optionally qualified by a
+// module (e.g. `io.UnsupportedOperation`), with one of the suffixes below.
+fn is_exception_name(name: &str) -> bool {
+    let suffixes = ["Error", "Exception", "Exit", "Interrupt", "Iteration",
+        "Operation", "Warning"];
+    let base = name.rsplit('.').next().unwrap();
+
+    !base.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        && base.chars().next().unwrap().is_uppercase()
+        && suffixes.iter().any(|s| base.ends_with(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_named_exception() {
+        let exc = Exception::parse("ValueError: math domain error");
+
+        assert_eq!(exc.name(), "ValueError");
+        assert_eq!(exc.msg(), "math domain error");
+        assert_eq!(exc.is("ValueError"), true);
+        assert_eq!(exc.is("Exception"), true);
+        assert_eq!(exc.is("TypeError"), false);
+        assert_eq!(Exception::new("KeyError", "'a'").is("LookupError"), true);
+    }
+
+    #[test]
+    fn parse_plain_message() {
+        let exc = Exception::parse("list index out of range");
+        let stop = Exception::parse("StopIteration");
+
+        assert_eq!(exc.name(), "Exception");
+        assert_eq!(exc.msg(), "list index out of range");
+        assert_eq!(stop.name(), "StopIteration");
+        assert_eq!(stop.msg(), "");
+    }
+
+    #[test]
+    fn catch_panic() {
+        let ok = catch(|| 5);
+        let err: Result<(), Exception> =
+            catch(|| panic!("KeyError: {}", "'a'"));
+
+        assert_eq!(ok, Ok(5));
+        assert_eq!(err, Err(Exception::new("KeyError", "'a'")));
+    }
+
+    #[test]
+    fn io_error_mapping() {
+        let missing = ::std::io::Error::from_raw_os_error(2);
+        let exc = Exception::from_io(&missing, Some("data.txt"));
+
+        assert_eq!(exc.to_string(), "FileNotFoundError: [Errno 2] No such \
+            file or directory: 'data.txt'");
+    }
+}
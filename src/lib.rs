@@ -1,11 +1,24 @@
 mod value;
 mod types;
 pub use value::Value;
+pub use value::ValueIter;
 pub use types::NumericType;
 pub use types::ListType;
 pub use types::TupleType;
 pub use types::IOWrapper;
+pub use types::FileType;
+pub use types::MemoryFileType;
+pub use types::RangeType;
+pub use types::BytesType;
+pub use types::IteratorType;
+pub use types::{GeneratorType, GenSignal, GenState, GenBody};
+pub use types::ModuleType;
+pub use types::PathType;
+pub use types::DateTimeType;
 pub mod builtin;
+pub mod exception;
+pub mod context;
+pub mod module;
 
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -31,17 +44,22 @@ pub fn attr_assign(dest: Value, attr: &str, src: Value) {
         Value::Object { ref tbl } => {
             tbl.borrow_mut().insert(attr.to_string(), src);
         },
+        Value::Module(ref module) => module.set_attr(attr, src),
         _ => panic!("cannot access attribute on primitives")
     }
 }
 
 /// Takes an object and a list of (names, aliases) and deconstructs the object
 /// into a HashMap will be merged into the local scope list. If None is passed
-/// into the 'members' parameter the entire object is mapped.
+/// into the 'members' parameter the entire object is mapped, for modules see
+/// `module::import_from`.
 pub fn split_object(object: Value, members: Option<Vec<(String, String)>>)
     -> HashMap<String, Value> {
     let mut map: HashMap<String, Value> = HashMap::new();
     let tbl = match object {
+        Value::Module(ref module) => {
+            return module::import_from(module.name(), members)
+        },
         Value::Object { ref tbl } => tbl,
         _ => panic!("Value is not 'object'")
     };
@@ -61,8 +79,9 @@ pub fn split_object(object: Value, members: Option<Vec<(String, String)>>)
     }
 }
 
-// If the attribute belongs to a Value::Class, the `self` value is not passed
-// through to the function call, if it's a Value::Object the value is passed.
+// If the attribute belongs to a Value::Class or Value::Module, or to an object
+// flagged with `__module__`, the `self` value is not passed through to the
+// function call, if it's a Value::Object the value is passed.
 pub fn call_member(mut value: Value, attr: &str, mut args: Vec<Value>,
     kwargs: HashMap<String, Value>) -> Value {
     match value {
@@ -76,11 +95,29 @@ pub fn call_member(mut value: Value, attr: &str, mut args: Vec<Value>,
                         .map(|s| Value::Str(s.to_string())).collect();
                     Value::List(Rc::new(RefCell::new(ListType::new(vec))))
                 },
+                "encode" => {
+                    let mut args_iter = args.into_iter();
+                    let mut arg = |name: &str| {
+                        args_iter.next().or_else(|| kwargs.get(name).cloned())
+                    };
+                    let encoding = arg("encoding")
+                        .map_or("utf-8".to_string(), |v| v.to_string());
+                    let errors = arg("errors")
+                        .map_or("strict".to_string(), |v| v.to_string());
+                    Value::Bytes(BytesType::new(types::bytes::encode(string,
+                        &encoding, &errors)))
+                },
                 _ => panic!(format!("'str' has no attribute '{}'", attr))
             }
         },
         Value::List(ref list) => {
-            list.borrow_mut().call(attr, args, kwargs)
+            ListType::call_list(list, attr, args, kwargs)
+        },
+        Value::Bytes(ref bytes) => {
+            bytes.call(attr, args, kwargs, BytesType::new_bytes)
+        },
+        Value::ByteArray(ref bytes) => {
+            BytesType::call_bytearray(bytes, attr, args, kwargs)
         },
         Value::Class { ref tbl } => {
             if let Some(func) = tbl.get(attr) {
@@ -109,9 +146,31 @@ pub fn call_member(mut value: Value, attr: &str, mut args: Vec<Value>,
             };
             func.call(args, kwargs)
         },
+        Value::Module(ref module) => {
+            module.get_attr(attr).call(args, kwargs)
+        },
         Value::TextIOWrapper(ref mut iow) => {
             iow.call(attr, args, kwargs)
         },
+        Value::Path(ref path) => path.call(attr, args, kwargs),
+        Value::DateTime(ref dt) => dt.call(attr, args, kwargs),
+        Value::Generator(ref gen) => {
+            match attr {
+                "__iter__" => value.clone(),
+                _ => gen.call(attr, args, kwargs)
+            }
+        },
+        Value::Iterator(_) => {
+            match attr {
+                "__iter__" => value.clone(),
+                "__next__" => match value.next() {
+                    Some(value) => value,
+                    None => panic!("StopIteration")
+                },
+                _ => panic!("'{}' object has no attribute '{}'",
+                    value.type_name(), attr)
+            }
+        },
         _ => unimplemented!()
     }
 }
@@ -0,0 +1,274 @@
+//! The module registry. Modules are registered by name with a loader that
+//! runs on the first import, the module it returns is cached (this cache is
+//! `sys.modules`) and shared by every later import. The natively implemented
+//! modules of `builtin` are registered up front, embedding code and compiled
+//! Python modules add theirs through `register`.
+//!
+//! Dotted names import their parent packages first and are bound as an
+//! attribute of the parent, `import os.path` makes `os.path` available.
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use ::{Value, ModuleType};
+
+type Loader = Rc<dyn Fn() -> Value>;
+
+thread_local! {
+    static LOADERS: RefCell<HashMap<String, Loader>> = RefCell::new(
+        ::builtin::native_modules().into_iter().map(|(name, loader)| {
+            (name.to_string(), Rc::new(loader) as Loader)
+        }).collect());
+    static MODULES: RefCell<HashMap<String, Value>> =
+        RefCell::new(HashMap::new());
+    // Modules whose loader is running, see `import_module`
+    static LOADING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+/// Registers the loader of the module `name`, replacing any previous one.
+/// The loader returns the module, or a class or object whose attributes
+/// become the module's, and runs once on the first import.
+pub fn register<F>(name: &str, loader: F) where F: Fn() -> Value + 'static {
+    LOADERS.with(|loaders| {
+        loaders.borrow_mut().insert(name.to_string(), Rc::new(loader))
+    });
+}
+
+/// `import name`, returns the top-level package the statement binds, i.e.
+/// `os` for `import os.path`
+pub fn import(name: &str) -> Value {
+    import_module(name);
+    let top = name.split('.').next().unwrap_or(name);
+    import_module(top)
+}
+
+/// Imports `name` and returns the module itself, `import name as alias`
+pub fn import_module(name: &str) -> Value {
+    if let Some(module) = get(name) {
+        return module
+    }
+    // A module importing itself, directly or through others, would recurse
+    // forever as it's only cached once its loader returns
+    if LOADING.with(|loading| loading.borrow().contains(name)) {
+        panic!("ImportError: cannot import name '{}' (most likely due to a \
+            circular import)", name)
+    }
+    let parent = name.rfind('.').map(|idx| (&name[..idx], &name[idx + 1..]));
+
+    // A package loader may import its own submodules (`os` imports
+    // `os.path`), the package is still loading then and binds the submodule
+    // itself
+    let parent_loading = parent.is_some_and(|(parent, _)| {
+        LOADING.with(|loading| loading.borrow().contains(parent))
+    });
+    if let (Some((parent, _)), false) = (parent, parent_loading) {
+        import_module(parent);
+    }
+
+    let loader = LOADERS.with(|loaders| loaders.borrow().get(name).cloned());
+    let loader = match loader {
+        Some(loader) => loader,
+        None => panic!("ModuleNotFoundError: No module named '{}'", name)
+    };
+    LOADING.with(|loading| loading.borrow_mut().insert(name.to_string()));
+    let result = ::exception::catch(|| into_module(name, loader()));
+    LOADING.with(|loading| loading.borrow_mut().remove(name));
+    let module = match result {
+        Ok(module) => module,
+        Err(exc) => exc.raise()
+    };
+
+    insert(name, module.clone());
+    if let (Some((parent, attr)), false) = (parent, parent_loading) {
+        ::attr_assign(import_module(parent), attr, module.clone());
+    }
+    module
+}
+
+/// `from name import a as b, ...`, maps each alias to its value. Names that
+/// aren't attributes are imported as submodules. Without `members` this is
+/// `from name import *`, which binds the names in the module's `__all__`
+/// or else every name without a leading underscore.
+pub fn import_from(name: &str, members: Option<Vec<(String, String)>>)
+    -> HashMap<String, Value> {
+    // `sys.modules` can hold anything, only modules have members to import
+    let module = match import_module(name) {
+        Value::Module(module) => module,
+        value => panic!("ImportError: cannot import from '{}', it is a '{}' \
+            object, not a module", name, value.type_name())
+    };
+    let members = members.unwrap_or_else(|| module.public_names().into_iter()
+        .map(|name| (name.clone(), name)).collect());
+
+    members.into_iter().map(|(attr, alias)| {
+        let value = match module.get(&attr) {
+            Some(value) => value,
+            None if is_registered(&format!("{}.{}", name, attr)) => {
+                import_module(&format!("{}.{}", name, attr))
+            },
+            None => panic!("ImportError: cannot import name '{}' from '{}'",
+                attr, name)
+        };
+        (alias, value)
+    }).collect()
+}
+
+/// The module cached as `name`, if it was imported
+pub fn get(name: &str) -> Option<Value> {
+    MODULES.with(|modules| modules.borrow().get(name).cloned())
+}
+
+/// Caches a module as `name`, later imports of the name return it
+pub fn insert(name: &str, module: Value) {
+    MODULES.with(|modules| {
+        modules.borrow_mut().insert(name.to_string(), module)
+    });
+}
+
+/// Drops a module from the cache, the next import loads it again
+pub fn remove(name: &str) -> Option<Value> {
+    MODULES.with(|modules| modules.borrow_mut().remove(name))
+}
+
+/// Names of the imported modules, sorted
+pub fn loaded() -> Vec<String> {
+    let mut names: Vec<String> = MODULES.with(|modules| {
+        modules.borrow().keys().cloned().collect()
+    });
+    names.sort();
+    names
+}
+
+fn is_registered(name: &str) -> bool {
+    LOADERS.with(|loaders| loaders.borrow().contains_key(name))
+}
+
+fn into_module(name: &str, value: Value) -> Value {
+    match value {
+        Value::Module(_) => value,
+        Value::Class { tbl } => Value::Module(ModuleType::new(name, tbl)),
+        Value::Object { tbl } => {
+            Value::Module(ModuleType::new(name, tbl.borrow().clone()))
+        },
+        value => panic!("TypeError: loader of module '{}' returned '{}'",
+            name, value.type_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use ::{TupleType, exception};
+    use ::builtin::{int_value, string};
+
+    fn package(attrs: Vec<(&str, Value)>) -> Value {
+        let mut tbl = HashMap::new();
+        for (name, value) in attrs {
+            tbl.insert(name.to_string(), value);
+        }
+        Value::Class { tbl }
+    }
+
+    #[test]
+    fn module_imports_are_cached() {
+        let loads = Rc::new(Cell::new(0));
+        let counter = loads.clone();
+        register("counted", move || {
+            counter.set(counter.get() + 1);
+            package(vec![("value", int_value(1))])
+        });
+
+        let first = import_module("counted");
+        ::attr_assign(first.clone(), "value", int_value(2));
+        let second = import("counted");
+
+        assert_eq!(loads.get(), 1);
+        assert_eq!(first.is(&second), true);
+        assert_eq!(second.get_attr("value"), int_value(2));
+        assert_eq!(second.type_name(), "module");
+        assert_eq!(loaded().contains(&"counted".to_string()), true);
+
+        remove("counted");
+        import_module("counted");
+        assert_eq!(loads.get(), 2);
+    }
+
+    #[test]
+    fn module_dotted_names() {
+        register("pkg", || package(vec![("top", int_value(0))]));
+        register("pkg.sub", || package(vec![("leaf", int_value(1))]));
+
+        let pkg = import("pkg.sub");
+        assert_eq!(pkg.get_attr("__name__"), string("pkg"));
+        assert_eq!(pkg.get_attr("sub").get_attr("leaf"), int_value(1));
+        assert_eq!(import_module("pkg.sub").is(&pkg.get_attr("sub")), true);
+
+        let error = exception::catch(|| import_module("pkg.missing"));
+        assert_eq!(error.err().map(|exc| exc.to_string()), Some(
+            "ModuleNotFoundError: No module named 'pkg.missing'".to_string()));
+    }
+
+    #[test]
+    fn module_from_import() {
+        let all = Value::Tuple(TupleType::new(vec![string("a")]));
+        register("listed", move || package(vec![("a", int_value(1)),
+            ("b", int_value(2)), ("__all__", all.clone())]));
+        register("unlisted", || package(vec![("a", int_value(1)), ("_b", int_value(2))]));
+        register("unlisted.sub", || package(vec![]));
+
+        let star = import_from("listed", None);
+        assert_eq!(star.len(), 1);
+        assert_eq!(star["a"], int_value(1));
+        let star = ::split_object(import_module("unlisted"), None);
+        assert_eq!(star.keys().collect::<Vec<_>>(), vec!["a"]);
+
+        let named = import_from("unlisted", Some(vec![
+            ("_b".to_string(), "b".to_string()),
+            ("sub".to_string(), "sub".to_string())]));
+        assert_eq!(named["b"], int_value(2));
+        assert_eq!(named["sub"].get_attr("__name__"), string("unlisted.sub"));
+
+        let error = exception::catch(|| {
+            import_from("listed", Some(vec![("c".to_string(), "c".to_string())]))
+        });
+        assert_eq!(error.err().map(|exc| exc.to_string()), Some(
+            "ImportError: cannot import name 'c' from 'listed'".to_string()));
+    }
+
+    #[test]
+    fn module_import_errors() {
+        register("cycle_a", || {
+            import_module("cycle_b");
+            package(vec![])
+        });
+        register("cycle_b", || import_module("cycle_a"));
+        register("self_import", || import_module("self_import"));
+
+        let error = |name: &str| exception::catch(|| import_module(name)).err()
+            .map(|exc| exc.to_string());
+        assert_eq!(error("self_import"), Some("ImportError: cannot import name \
+            'self_import' (most likely due to a circular import)".to_string()));
+        assert_eq!(error("cycle_a"), Some("ImportError: cannot import name \
+            'cycle_a' (most likely due to a circular import)".to_string()));
+        assert_eq!(get("cycle_a").is_none(), true);
+
+        insert("fake", int_value(1));
+        let error = exception::catch(|| import_from("fake", None));
+        assert_eq!(error.err().map(|exc| exc.to_string()), Some("ImportError: \
+            cannot import from 'fake', it is a 'int' object, not a module"
+            .to_string()));
+        remove("fake");
+    }
+
+    #[test]
+    fn module_native_modules_shared() {
+        let sys = import("sys");
+        let modules = sys.get_attr("modules");
+
+        assert_eq!(import_module("math").get_attr("__name__"),
+            string("math"));
+        assert_eq!(string("math").contained_in(&modules), true);
+        assert_eq!(modules.index(string("sys")).is(&sys), true);
+    }
+}
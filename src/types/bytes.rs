@@ -0,0 +1,632 @@
+use std::fmt;
+use std::str;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use ::Value;
+use super::NumericType;
+use super::ListType;
+use super::slice;
+
+/// Storage for both `bytes` (Value::Bytes) and `bytearray`
+/// (Value::ByteArray, shared through an Rc like lists). Compiled `b'...'`
+/// literals construct this directly from the literal's bytes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BytesType {
+    bytes: Vec<u8>
+}
+
+impl BytesType {
+    pub fn new(bytes: Vec<u8>) -> BytesType {
+        BytesType { bytes }
+    }
+
+    /// Wraps `bytes` as an immutable Value::Bytes
+    pub fn new_bytes(bytes: Vec<u8>) -> Value {
+        Value::Bytes(BytesType::new(bytes))
+    }
+
+    /// Wraps `bytes` as a mutable Value::ByteArray
+    pub fn new_bytearray(bytes: Vec<u8>) -> Value {
+        Value::ByteArray(Rc::new(RefCell::new(BytesType::new(bytes))))
+    }
+
+    /// Builds the contents of `bytes(source)`/`bytearray(source)`: a count
+    /// of zero bytes, an encoded string, a bytes-like object or an iterable
+    /// of ints.
+    pub fn from_source(source: Option<&Value>, encoding: Option<&Value>,
+        errors: Option<&Value>) -> Vec<u8> {
+        match (source, encoding) {
+            (None, None) => vec![],
+            (Some(&Value::Str(ref string)), Some(encoding)) => {
+                let errors = match errors {
+                    Some(errors) => errors.to_string(),
+                    None => "strict".to_string()
+                };
+                encode(string, &encoding.to_string(), &errors)
+            },
+            (Some(&Value::Str(_)), None) => {
+                panic!("TypeError: string argument without an encoding")
+            },
+            (_, Some(_)) => {
+                panic!("TypeError: encoding without a string argument")
+            },
+            (Some(source), None) => match source.as_index() {
+                Some(count) if count < 0 => {
+                    panic!("ValueError: negative count")
+                },
+                Some(count) => vec![0; count as usize],
+                None => match as_bytes(source) {
+                    Some(bytes) => bytes,
+                    None => source.clone_seq().iter().map(byte).collect()
+                }
+            }
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn to_bool(&self) -> bool {
+        !self.bytes.is_empty()
+    }
+
+    /// Returns the length of the sequence
+    pub fn len(&self) -> Value {
+        Value::Number(NumericType::Integer(self.bytes.len() as i32))
+    }
+
+    /// Returns the length as a usize for use within the runtime
+    pub fn length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns the byte at `pos` as an int without bounds panics, used by
+    /// iterators
+    pub fn get(&self, pos: usize) -> Option<Value> {
+        self.bytes.get(pos).map(|b| Value::Number(NumericType::Integer(*b as i32)))
+    }
+
+    /// Indexing yields ints, `b'abc'[0] == 97`
+    pub fn index(&self, index: Value) -> Value {
+        let pos = self.position(&index, "index out of range");
+        Value::Number(NumericType::Integer(self.bytes[pos] as i32))
+    }
+
+    /// Returns the selected bytes, the caller wraps them in the same type
+    pub fn slice(&self, lower: Option<Value>, upper: Option<Value>,
+        step: Option<Value>) -> Vec<u8> {
+        let (start, stop, step) = slice::indices(lower, upper, step,
+            self.bytes.len());
+        slice::positions(start, stop, step).into_iter()
+            .map(|pos| self.bytes[pos]).collect()
+    }
+
+    /// `bytearray[index] = int`
+    pub fn set_index(&mut self, index: Value, value: Value) {
+        let pos = self.position(&index, "bytearray index out of range");
+        self.bytes[pos] = byte(&value);
+    }
+
+    /// `int in bytes` tests for a byte, `bytes in bytes` for a subsequence
+    pub fn contains(&self, value: &Value) -> bool {
+        match value.as_index() {
+            Some(_) => self.bytes.contains(&byte(value)),
+            None => find(&self.bytes, &bytes_arg(value), 0).is_some()
+        }
+    }
+
+    /// Appends the contents of `other`, used by `+` and `+=`
+    pub fn extend_bytes(&mut self, other: &[u8]) {
+        self.bytes.extend_from_slice(other);
+    }
+
+    pub fn repeat(&self, count: usize) -> Vec<u8> {
+        self.bytes.repeat(count)
+    }
+
+    /// Lowercase hex digits of each byte, `b'\xde\xad'.hex() == 'dead'`
+    pub fn hex(&self) -> String {
+        self.bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Methods shared by `bytes` and `bytearray`. Results that are byte
+    /// sequences are wrapped with `wrap` so they keep the receiver's type.
+    pub fn call(&self, attr: &str, args: Vec<Value>,
+        kwargs: HashMap<String, Value>, wrap: fn(Vec<u8>) -> Value) -> Value {
+        let arg = |pos: usize, name: &str| -> Option<Value> {
+            match args.get(pos).or_else(|| kwargs.get(name)) {
+                Some(&Value::None) | None => None,
+                Some(value) => Some(value.clone())
+            }
+        };
+        let int = |val: i64| Value::Number(NumericType::Integer(val as i32));
+
+        match attr {
+            "decode" => {
+                let encoding = arg(0, "encoding")
+                    .map_or("utf-8".to_string(), |v| v.to_string());
+                let errors = arg(1, "errors")
+                    .map_or("strict".to_string(), |v| v.to_string());
+                Value::Str(decode(&self.bytes, &encoding, &errors))
+            },
+            "hex" => Value::Str(self.hex()),
+            "split" => {
+                let maxsplit = arg(1, "maxsplit").and_then(|v| v.as_index())
+                    .unwrap_or(-1);
+                let parts = match arg(0, "sep") {
+                    Some(sep) => split(&self.bytes, &bytes_arg(&sep), maxsplit),
+                    None => split_whitespace(&self.bytes, maxsplit)
+                };
+                let parts = parts.into_iter().map(wrap).collect();
+                Value::List(Rc::new(RefCell::new(ListType::new(parts))))
+            },
+            "strip" | "lstrip" | "rstrip" => {
+                let chars = match arg(0, "chars") {
+                    Some(chars) => bytes_arg(&chars),
+                    None => b" \t\n\r\x0b\x0c".to_vec()
+                };
+                let mut bytes = &self.bytes[..];
+                if attr != "rstrip" {
+                    while bytes.first().is_some_and(|b| chars.contains(b)) {
+                        bytes = &bytes[1..];
+                    }
+                }
+                if attr != "lstrip" {
+                    while bytes.last().is_some_and(|b| chars.contains(b)) {
+                        bytes = &bytes[..bytes.len() - 1];
+                    }
+                }
+                wrap(bytes.to_vec())
+            },
+            "startswith" | "endswith" => {
+                let affixes = match arg(0, "prefix") {
+                    Some(Value::Tuple(tup)) => tup.clone_seq(),
+                    Some(affix) => vec![affix],
+                    None => panic!("TypeError: {}() takes at least 1 argument \
+                        (0 given)", attr)
+                };
+                Value::Bool(affixes.iter().any(|affix| {
+                    let affix = bytes_arg(affix);
+                    if attr == "startswith" {
+                        self.bytes.starts_with(&affix)
+                    } else {
+                        self.bytes.ends_with(&affix)
+                    }
+                }))
+            },
+            "find" | "rfind" | "index" | "count" => {
+                let sub = match arg(0, "sub") {
+                    Some(ref sub) if sub.as_index().is_some() => vec![byte(sub)],
+                    Some(sub) => bytes_arg(&sub),
+                    None => panic!("TypeError: {}() takes at least 1 argument \
+                        (0 given)", attr)
+                };
+                let (start, stop, _) = slice::indices(arg(1, "start"),
+                    arg(2, "end"), None, self.bytes.len());
+                let stop = stop.max(start) as usize;
+                let haystack = &self.bytes[..stop];
+                let start = start as usize;
+
+                match attr {
+                    "count" => int(count(haystack, &sub, start) as i64),
+                    "rfind" => int(rfind(haystack, &sub, start)
+                        .map_or(-1, |pos| pos as i64)),
+                    _ => match find(haystack, &sub, start) {
+                        Some(pos) => int(pos as i64),
+                        None if attr == "index" => {
+                            panic!("ValueError: subsection not found")
+                        },
+                        None => int(-1)
+                    }
+                }
+            },
+            "replace" => {
+                let (old, new) = match (arg(0, "old"), arg(1, "new")) {
+                    (Some(old), Some(new)) => (bytes_arg(&old), bytes_arg(&new)),
+                    _ => panic!("TypeError: replace() takes at least 2 \
+                        arguments")
+                };
+                let limit = arg(2, "count").and_then(|v| v.as_index())
+                    .unwrap_or(-1);
+                wrap(replace(&self.bytes, &old, &new, limit))
+            },
+            "join" => {
+                let iterable = match arg(0, "iterable_of_bytes") {
+                    Some(iterable) => iterable,
+                    None => panic!("TypeError: join() takes exactly one \
+                        argument (0 given)")
+                };
+                let mut joined = vec![];
+                for (i, part) in iterable.clone_seq().iter().enumerate() {
+                    if i > 0 {
+                        joined.extend_from_slice(&self.bytes);
+                    }
+                    match as_bytes(part) {
+                        Some(part) => joined.extend(part),
+                        None => panic!("TypeError: sequence item {}: expected \
+                            a bytes-like object, {} found", i, part.type_name())
+                    }
+                }
+                wrap(joined)
+            },
+            _ => panic!("AttributeError: '{}' object has no attribute '{}'",
+                wrap(vec![]).type_name(), attr)
+        }
+    }
+
+    /// `bytearray` methods. Only the mutating ones borrow the bytearray
+    /// mutably, an argument that is the bytearray itself (`ba.extend(ba)`) is
+    /// copied before that borrow.
+    pub fn call_bytearray(array: &Rc<RefCell<BytesType>>, attr: &str,
+        args: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+        match attr {
+            "append" | "extend" | "pop" | "clear" => {
+                let args = args.into_iter().map(|arg| match arg {
+                    Value::ByteArray(ref other) if Rc::ptr_eq(other, array) => {
+                        BytesType::new_bytes(other.borrow().bytes.clone())
+                    },
+                    arg => arg
+                }).collect();
+                array.borrow_mut().call_mut(attr, args, kwargs)
+            },
+            "copy" => BytesType::new_bytearray(array.borrow().bytes.clone()),
+            _ => array.borrow().call(attr, args, kwargs, BytesType::new_bytearray)
+        }
+    }
+
+    // The mutating `bytearray` methods
+    fn call_mut(&mut self, attr: &str, args: Vec<Value>,
+        kwargs: HashMap<String, Value>) -> Value {
+        match attr {
+            "append" => {
+                if args.len() != 1 {
+                    panic!("TypeError: append() takes exactly one argument \
+                        ({} given)", args.len())
+                }
+                self.bytes.push(byte(&args[0]));
+                Value::None
+            },
+            "extend" => {
+                let values = match args.first() {
+                    Some(values) => values,
+                    None => panic!("TypeError: extend() takes exactly one \
+                        argument (0 given)")
+                };
+                let bytes = match as_bytes(values) {
+                    Some(bytes) => bytes,
+                    None => values.clone_seq().iter().map(byte).collect()
+                };
+                self.bytes.extend(bytes);
+                Value::None
+            },
+            "pop" => {
+                if self.bytes.is_empty() {
+                    panic!("IndexError: pop from empty bytearray")
+                }
+                let pos = match args.first() {
+                    Some(index) => self.position(index,
+                        "pop index out of range"),
+                    None => self.bytes.len() - 1
+                };
+                Value::Number(NumericType::Integer(self.bytes.remove(pos) as i32))
+            },
+            "clear" => {
+                self.bytes.clear();
+                Value::None
+            },
+            _ => self.call(attr, args, kwargs, BytesType::new_bytearray)
+        }
+    }
+
+    // Resolves a possibly negative index, raising IndexError with `msg`
+    fn position(&self, index: &Value, msg: &str) -> usize {
+        let pos = match index.as_index() {
+            Some(pos) => pos as i64,
+            None => panic!("TypeError: byte indices must be integers or \
+                slices, not {}", index.type_name())
+        };
+        let len = self.bytes.len() as i64;
+        let pos = if pos < 0 { pos + len } else { pos };
+
+        if pos < 0 || pos >= len {
+            panic!("IndexError: {}", msg)
+        }
+        pos as usize
+    }
+}
+
+// Python's repr of a bytes literal: printable ASCII as is, the common escapes
+// and `\xNN` for everything else, single quoted unless that needs escaping.
+impl fmt::Display for BytesType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let quote = if self.bytes.contains(&b'\'') && !self.bytes.contains(&b'"') {
+            '"'
+        } else {
+            '\''
+        };
+        let mut repr = format!("b{}", quote);
+
+        for &b in self.bytes.iter() {
+            match b {
+                b'\\' => repr.push_str("\\\\"),
+                b'\t' => repr.push_str("\\t"),
+                b'\n' => repr.push_str("\\n"),
+                b'\r' => repr.push_str("\\r"),
+                b if b as char == quote => {
+                    repr.push('\\');
+                    repr.push(quote);
+                },
+                0x20..=0x7e => repr.push(b as char),
+                b => repr.push_str(&format!("\\x{:02x}", b))
+            }
+        }
+        repr.push(quote);
+        write!(f, "{}", repr)
+    }
+}
+
+/// The contents of a bytes-like value (bytes or bytearray)
+pub fn as_bytes(value: &Value) -> Option<Vec<u8>> {
+    match *value {
+        Value::Bytes(ref bytes) => Some(bytes.bytes.clone()),
+        Value::ByteArray(ref bytes) => Some(bytes.borrow().bytes.clone()),
+        _ => None
+    }
+}
+
+/// `bytes.fromhex(string)`, pairs of hex digits optionally separated by
+/// whitespace
+pub fn fromhex(string: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut chars = string.char_indices().filter(|&(_, c)| !c.is_whitespace());
+
+    while let Some((pos, high)) = chars.next() {
+        let (pos, low) = match (high.to_digit(16), chars.next()) {
+            (Some(_), Some((pos, low))) => (pos, low),
+            (Some(_), None) => (pos + 1, ' '),
+            (None, _) => (pos, high)
+        };
+        match (high.to_digit(16), low.to_digit(16)) {
+            (Some(high), Some(low)) => bytes.push((high * 16 + low) as u8),
+            _ => panic!("ValueError: non-hexadecimal number found in fromhex() \
+                arg at position {}", pos)
+        }
+    }
+    bytes
+}
+
+/// `str.encode(encoding, errors)` for the utf-8, ascii and latin-1 codecs
+pub fn encode(string: &str, encoding: &str, errors: &str) -> Vec<u8> {
+    let (codec, limit) = match codec(encoding) {
+        "utf-8" => return string.as_bytes().to_vec(),
+        "ascii" => ("ascii", 128),
+        _ => ("latin-1", 256)
+    };
+    let mut bytes = vec![];
+
+    for (pos, c) in string.chars().enumerate() {
+        if (c as u32) < limit {
+            bytes.push(c as u8);
+            continue
+        }
+        match errors {
+            "strict" => panic!("UnicodeEncodeError: '{}' codec can't encode \
+                character '{}' in position {}: ordinal not in range({})",
+                codec, escape_char(c), pos, limit),
+            "ignore" => (),
+            "replace" => bytes.push(b'?'),
+            _ => panic!("LookupError: unknown error handler name '{}'", errors)
+        }
+    }
+    bytes
+}
+
+/// `bytes.decode(encoding, errors)` for the utf-8, ascii and latin-1 codecs
+pub fn decode(bytes: &[u8], encoding: &str, errors: &str) -> String {
+    let codec = codec(encoding);
+    let mut string = String::new();
+    let mut rest = bytes;
+    let mut offset = 0;
+
+    loop {
+        // Length of the valid prefix and of the invalid sequence after it
+        let (valid, invalid) = match codec {
+            "utf-8" => match str::from_utf8(rest) {
+                Ok(_) => (rest.len(), 0),
+                Err(err) => (err.valid_up_to(),
+                    err.error_len().unwrap_or(rest.len() - err.valid_up_to()))
+            },
+            "ascii" => match rest.iter().position(|b| *b >= 128) {
+                Some(pos) => (pos, 1),
+                None => (rest.len(), 0)
+            },
+            _ => (rest.len(), 0)
+        };
+
+        if codec == "latin-1" {
+            string.extend(rest.iter().map(|b| *b as char));
+        } else {
+            string.push_str(str::from_utf8(&rest[..valid]).unwrap());
+        }
+        if invalid == 0 {
+            return string
+        }
+
+        match errors {
+            "strict" => {
+                let bad = rest[valid];
+                let reason = match (codec, bad) {
+                    ("ascii", _) => "ordinal not in range(128)",
+                    _ if valid + invalid == rest.len()
+                        && (0xc2..=0xf4).contains(&bad) => {
+                        "unexpected end of data"
+                    },
+                    (_, 0x80..=0xc1) | (_, 0xf5..=0xff) => "invalid start byte",
+                    _ => "invalid continuation byte"
+                };
+                panic!("UnicodeDecodeError: '{}' codec can't decode byte \
+                    0x{:02x} in position {}: {}", codec, bad, offset + valid,
+                    reason)
+            },
+            "ignore" => (),
+            "replace" => string.push('\u{fffd}'),
+            _ => panic!("LookupError: unknown error handler name '{}'", errors)
+        }
+        offset += valid + invalid;
+        rest = &rest[valid + invalid..];
+    }
+}
+
+/// Normalizes the accepted spellings of the supported codecs, raises
+/// LookupError for unknown encodings
+pub fn codec(encoding: &str) -> &'static str {
+    match encoding.to_lowercase().replace('_', "-").as_str() {
+        "utf-8" | "utf8" => "utf-8",
+        "ascii" | "us-ascii" => "ascii",
+        "latin-1" | "latin1" | "iso-8859-1" | "iso8859-1" => "latin-1",
+        _ => panic!("LookupError: unknown encoding: {}", encoding)
+    }
+}
+
+// Escaped form of a character as shown in UnicodeEncodeError messages
+fn escape_char(c: char) -> String {
+    match c as u32 {
+        code if code < 0x100 => format!("\\x{:02x}", code),
+        code if code < 0x10000 => format!("\\u{:04x}", code),
+        code => format!("\\U{:08x}", code)
+    }
+}
+
+// Converts an int to a byte, raising ValueError outside of range(0, 256)
+fn byte(value: &Value) -> u8 {
+    match value.as_index() {
+        Some(val) if (0..256).contains(&val) => val as u8,
+        Some(_) => panic!("ValueError: byte must be in range(0, 256)"),
+        None => panic!("TypeError: '{}' object cannot be interpreted as an \
+            integer", value.type_name())
+    }
+}
+
+// Bytes-like method arguments, raising TypeError for anything else
+fn bytes_arg(value: &Value) -> Vec<u8> {
+    match as_bytes(value) {
+        Some(bytes) => bytes,
+        None => panic!("TypeError: a bytes-like object is required, not '{}'",
+            value.type_name())
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], start: usize) -> Option<usize> {
+    if start + needle.len() > haystack.len() {
+        return None
+    }
+    (start..haystack.len() - needle.len() + 1)
+        .find(|&pos| haystack[pos..].starts_with(needle))
+}
+
+fn rfind(haystack: &[u8], needle: &[u8], start: usize) -> Option<usize> {
+    if start + needle.len() > haystack.len() {
+        return None
+    }
+    (start..haystack.len() - needle.len() + 1).rev()
+        .find(|&pos| haystack[pos..].starts_with(needle))
+}
+
+// Non-overlapping occurrences, an empty needle matches between every byte
+fn count(haystack: &[u8], needle: &[u8], start: usize) -> usize {
+    if start > haystack.len() {
+        return 0
+    }
+    if needle.is_empty() {
+        return haystack.len() - start + 1
+    }
+    let mut count = 0;
+    let mut pos = start;
+    while let Some(found) = find(haystack, needle, pos) {
+        count += 1;
+        pos = found + needle.len();
+    }
+    count
+}
+
+fn split(bytes: &[u8], sep: &[u8], maxsplit: i32) -> Vec<Vec<u8>> {
+    if sep.is_empty() {
+        panic!("ValueError: empty separator")
+    }
+    let mut parts = vec![];
+    let mut start = 0;
+
+    while maxsplit < 0 || (parts.len() as i32) < maxsplit {
+        match find(bytes, sep, start) {
+            Some(pos) => {
+                parts.push(bytes[start..pos].to_vec());
+                start = pos + sep.len();
+            },
+            None => break
+        }
+    }
+    parts.push(bytes[start..].to_vec());
+    parts
+}
+
+// Splits on runs of ASCII whitespace, dropping empty parts
+fn split_whitespace(bytes: &[u8], maxsplit: i32) -> Vec<Vec<u8>> {
+    let mut parts = vec![];
+    let mut rest = bytes;
+
+    loop {
+        while rest.first().is_some_and(|b| b.is_ascii_whitespace()) {
+            rest = &rest[1..];
+        }
+        if rest.is_empty() {
+            break
+        }
+        if maxsplit >= 0 && parts.len() as i32 == maxsplit {
+            let end = rest.iter().rposition(|b| !b.is_ascii_whitespace())
+                .unwrap();
+            parts.push(rest[..end + 1].to_vec());
+            break
+        }
+        let end = rest.iter().position(|b| b.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        parts.push(rest[..end].to_vec());
+        rest = &rest[end..];
+    }
+    parts
+}
+
+fn replace(bytes: &[u8], old: &[u8], new: &[u8], limit: i32) -> Vec<u8> {
+    let mut replaced = vec![];
+    let mut pos = 0;
+    let mut count = 0;
+
+    while limit < 0 || count < limit {
+        if old.is_empty() {
+            // An empty pattern matches before every byte and at the end
+            replaced.extend_from_slice(new);
+            count += 1;
+            match bytes.get(pos) {
+                Some(b) => replaced.push(*b),
+                None => return replaced
+            }
+            pos += 1;
+            continue
+        }
+        match find(bytes, old, pos) {
+            Some(found) => {
+                replaced.extend_from_slice(&bytes[pos..found]);
+                replaced.extend_from_slice(new);
+                pos = found + old.len();
+                count += 1;
+            },
+            None => break
+        }
+    }
+    if pos <= bytes.len() {
+        replaced.extend_from_slice(&bytes[pos..]);
+    }
+    replaced
+}
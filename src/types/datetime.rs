@@ -0,0 +1,948 @@
+use std::fmt;
+use std::cmp;
+use std::hash::{Hash, Hasher};
+use std::collections::HashMap;
+
+use ::Value;
+use ::builtin::int_value;
+use ::builtin::time::{self, Tm};
+use super::NumericType;
+use super::TupleType;
+
+pub const MINYEAR: i32 = 1;
+pub const MAXYEAR: i32 = 9999;
+const MICROS_PER_SECOND: i128 = 1_000_000;
+pub const MICROS_PER_DAY: i128 = 86_400 * MICROS_PER_SECOND;
+
+/// The values of the `datetime` module. Dates and times are proleptic
+/// Gregorian, time zones are fixed offsets from UTC.
+#[derive(Clone)]
+pub enum DateTimeType {
+    TimeDelta(TimeDelta),
+    Date(Date),
+    Time(Time),
+    DateTime(DateTime),
+    TimeZone(TimeZone)
+}
+
+/// `timedelta`, a duration in microseconds. Python normalizes it to days,
+/// seconds and microseconds with only the days negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeDelta {
+    micros: i128
+}
+
+impl TimeDelta {
+    pub fn new(micros: i128) -> TimeDelta {
+        let days = micros.div_euclid(MICROS_PER_DAY);
+        if days.abs() > 999_999_999 {
+            panic!("OverflowError: days={}; must have magnitude <= 999999999",
+                days)
+        }
+        TimeDelta { micros }
+    }
+
+    pub fn micros(&self) -> i128 {
+        self.micros
+    }
+
+    pub fn days(&self) -> i64 {
+        self.micros.div_euclid(MICROS_PER_DAY) as i64
+    }
+
+    pub fn seconds(&self) -> i32 {
+        (self.micros.rem_euclid(MICROS_PER_DAY) / MICROS_PER_SECOND) as i32
+    }
+
+    pub fn microseconds(&self) -> i32 {
+        self.micros.rem_euclid(MICROS_PER_SECOND) as i32
+    }
+}
+
+/// `timezone`, a fixed offset from UTC in seconds with an optional name
+#[derive(Debug, Clone)]
+pub struct TimeZone {
+    offset: i32,
+    name: Option<String>
+}
+
+impl TimeZone {
+    pub fn new(offset: i32, name: Option<String>) -> TimeZone {
+        if offset.abs() >= 86400 {
+            panic!("ValueError: offset must be a timedelta strictly between \
+                -timedelta(hours=24) and timedelta(hours=24).")
+        }
+        TimeZone { offset, name }
+    }
+
+    pub fn utc() -> TimeZone {
+        TimeZone { offset: 0, name: None }
+    }
+
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// The given name, otherwise `UTC` followed by the offset
+    pub fn name(&self) -> String {
+        match self.name {
+            Some(ref name) => name.clone(),
+            None if self.offset == 0 => "UTC".to_string(),
+            None => format!("UTC{}", iso_offset(self.offset))
+        }
+    }
+}
+
+// Zones are equal when their offsets are, whatever their names
+impl PartialEq for TimeZone {
+    fn eq(&self, other: &TimeZone) -> bool {
+        self.offset == other.offset
+    }
+}
+
+/// `date`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: i32,
+    day: i32
+}
+
+impl Date {
+    pub fn new(year: i32, month: i32, day: i32) -> Date {
+        if !(MINYEAR..=MAXYEAR).contains(&year) {
+            panic!("ValueError: year {} is out of range", year)
+        }
+        if !(1..=12).contains(&month) {
+            panic!("ValueError: month must be in 1..12")
+        }
+        if day < 1 || day > time::days_in_month(year, month) {
+            panic!("ValueError: day is out of range for month")
+        }
+        Date { year, month, day }
+    }
+
+    /// The date `days` after 1970-01-01
+    pub fn from_days(days: i64) -> Date {
+        let (year, month, day) = time::civil_from_days(days);
+        if !(MINYEAR..=MAXYEAR).contains(&year) {
+            panic!("OverflowError: date value out of range")
+        }
+        Date { year, month, day }
+    }
+
+    /// Days since 1970-01-01
+    pub fn days(&self) -> i64 {
+        time::days_from_civil(self.year, self.month, self.day)
+    }
+
+    /// Days since 0001-01-01, which is day 1
+    pub fn toordinal(&self) -> i64 {
+        self.days() - time::days_from_civil(1, 1, 1) + 1
+    }
+
+    /// 0 is Monday
+    pub fn weekday(&self) -> i32 {
+        (self.days() + 3).rem_euclid(7) as i32
+    }
+
+    /// The ISO year, week and weekday. Week 1 is the one holding the year's
+    /// first Thursday.
+    pub fn isocalendar(&self) -> (i32, i32, i32) {
+        let thursday = self.days() - self.weekday() as i64 + 3;
+        let year = time::civil_from_days(thursday).0;
+        let week = (thursday - time::days_from_civil(year, 1, 1)) / 7 + 1;
+        (year, week as i32, self.weekday() + 1)
+    }
+
+    pub fn isoformat(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// `time`, a time of day
+#[derive(Debug, Clone)]
+pub struct Time {
+    hour: i32,
+    minute: i32,
+    second: i32,
+    microsecond: i32,
+    tzinfo: Option<TimeZone>
+}
+
+impl Time {
+    pub fn new(hour: i32, minute: i32, second: i32, microsecond: i32,
+        tzinfo: Option<TimeZone>) -> Time {
+        let checks = [(hour, 23, "hour"), (minute, 59, "minute"),
+            (second, 59, "second"), (microsecond, 999_999, "microsecond")];
+        for &(value, max, name) in &checks {
+            if !(0..=max).contains(&value) {
+                panic!("ValueError: {} must be in 0..{}", name, max)
+            }
+        }
+        Time { hour, minute, second, microsecond, tzinfo }
+    }
+
+    fn from_micros(micros: i128, tzinfo: Option<TimeZone>) -> Time {
+        let secs = (micros / MICROS_PER_SECOND) as i32;
+        Time {
+            hour: secs / 3600, minute: secs / 60 % 60, second: secs % 60,
+            microsecond: (micros % MICROS_PER_SECOND) as i32, tzinfo
+        }
+    }
+
+    /// Microseconds since midnight
+    fn micros(&self) -> i128 {
+        (self.hour * 3600 + self.minute * 60 + self.second) as i128 *
+            MICROS_PER_SECOND + self.microsecond as i128
+    }
+
+    // Microseconds since midnight UTC, `None` for naive times
+    fn utc_micros(&self) -> Option<i128> {
+        self.tzinfo.as_ref().map(|tz| {
+            self.micros() - tz.offset as i128 * MICROS_PER_SECOND
+        })
+    }
+
+    /// `HH:MM:SS.ffffff+HH:MM` with the parts `timespec` asks for, `auto`
+    /// leaves the microseconds out when there are none
+    pub fn tzinfo(&self) -> Option<TimeZone> {
+        self.tzinfo.clone()
+    }
+
+    pub fn isoformat(&self, timespec: &str) -> String {
+        let (hour, minute, second) = (self.hour, self.minute, self.second);
+        let mut iso = match timespec {
+            "auto" if self.microsecond == 0 => {
+                format!("{:02}:{:02}:{:02}", hour, minute, second)
+            },
+            "auto" | "microseconds" => format!("{:02}:{:02}:{:02}.{:06}", hour,
+                minute, second, self.microsecond),
+            "hours" => format!("{:02}", hour),
+            "minutes" => format!("{:02}:{:02}", hour, minute),
+            "seconds" => format!("{:02}:{:02}:{:02}", hour, minute, second),
+            "milliseconds" => format!("{:02}:{:02}:{:02}.{:03}", hour, minute,
+                second, self.microsecond / 1000),
+            _ => panic!("ValueError: Unknown timespec value")
+        };
+        if let Some(ref tz) = self.tzinfo {
+            iso.push_str(&iso_offset(tz.offset));
+        }
+        iso
+    }
+}
+
+/// `datetime`, a date and a time of day
+#[derive(Debug, Clone)]
+pub struct DateTime {
+    date: Date,
+    time: Time
+}
+
+impl DateTime {
+    pub fn new(date: Date, time: Time) -> DateTime {
+        DateTime { date, time }
+    }
+
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// The same fields in another zone, or naive
+    pub fn with_tzinfo(&self, tzinfo: Option<TimeZone>) -> DateTime {
+        DateTime { date: self.date, time: Time { tzinfo, ..self.time.clone() } }
+    }
+
+    fn from_micros(micros: i128, tzinfo: Option<TimeZone>) -> DateTime {
+        let days = micros.div_euclid(MICROS_PER_DAY);
+        if days.abs() > i64::MAX as i128 {
+            panic!("OverflowError: date value out of range")
+        }
+        DateTime {
+            date: Date::from_days(days as i64),
+            time: Time::from_micros(micros.rem_euclid(MICROS_PER_DAY), tzinfo)
+        }
+    }
+
+    /// The time `secs` and `micros` after the epoch in `tz`, a naive local
+    /// time without it
+    pub fn from_timestamp(secs: i64, micros: i32, tz: Option<TimeZone>)
+        -> DateTime {
+        match tz {
+            Some(tz) => {
+                let local = secs as i128 + tz.offset as i128;
+                DateTime::from_micros(local * MICROS_PER_SECOND + micros as i128,
+                    Some(tz))
+            },
+            None => {
+                let tm = time::local_tm(secs);
+                DateTime {
+                    date: Date::from_days(time::days_from_civil(tm.year, tm.month,
+                        tm.day)),
+                    time: Time::new(tm.hour, tm.minute, tm.second, micros, None)
+                }
+            }
+        }
+    }
+
+    // Microseconds since 1970-01-01 of the fields, ignoring the zone
+    fn local_micros(&self) -> i128 {
+        self.date.days() as i128 * MICROS_PER_DAY + self.time.micros()
+    }
+
+    // Microseconds since the epoch, `None` for naive times
+    fn utc_micros(&self) -> Option<i128> {
+        self.time.tzinfo.as_ref().map(|tz| {
+            self.local_micros() - tz.offset as i128 * MICROS_PER_SECOND
+        })
+    }
+
+    // Microseconds since the epoch, naive times are local
+    fn epoch_micros(&self) -> i128 {
+        self.utc_micros().unwrap_or_else(|| {
+            let secs = time::local_seconds(&tm(&self.date, &self.time));
+            secs as i128 * MICROS_PER_SECOND + self.time.microsecond as i128
+        })
+    }
+
+    pub fn isoformat(&self, sep: &str, timespec: &str) -> String {
+        format!("{}{}{}", self.date.isoformat(), sep, self.time.isoformat(timespec))
+    }
+}
+
+// `+HH:MM`, with seconds when there are any
+fn iso_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    match offset % 60 {
+        0 => format!("{}{:02}:{:02}", sign, offset / 3600, offset / 60 % 60),
+        secs => format!("{}{:02}:{:02}:{:02}", sign, offset / 3600,
+            offset / 60 % 60, secs)
+    }
+}
+
+// The fields `time.strftime` formats
+fn tm(date: &Date, time: &Time) -> Tm {
+    Tm {
+        hour: time.hour, minute: time.minute, second: time.second,
+        microsecond: time.microsecond,
+        utcoffset: time.tzinfo.as_ref().map(|tz| tz.offset),
+        tzname: time.tzinfo.as_ref().map(|tz| tz.name()),
+        ..Tm::date(date.year, date.month, date.day)
+    }
+}
+
+pub fn delta(micros: i128) -> Value {
+    Value::DateTime(DateTimeType::TimeDelta(TimeDelta::new(micros)))
+}
+
+/// Binds the arguments of a call to `names`, the first `required` of which
+/// must be given
+pub fn bind_args(func: &str, params: Vec<Value>, mut kwargs: HashMap<String, Value>,
+    names: &[&str], required: usize) -> Vec<Option<Value>> {
+    if params.len() > names.len() {
+        panic!("TypeError: {}() takes at most {} arguments ({} given)", func,
+            names.len(), params.len())
+    }
+    let given = params.len();
+    let mut args: Vec<Option<Value>> = params.into_iter().map(Some).collect();
+    for (pos, name) in names.iter().enumerate() {
+        match kwargs.remove(*name) {
+            Some(_) if pos < given => {
+                panic!("TypeError: argument for {}() given by name ('{}') and \
+                    position ({})", func, name, pos + 1)
+            },
+            Some(value) => args.push(Some(value)),
+            None if pos < given => (),
+            None if pos < required => {
+                panic!("TypeError: Required argument '{}' (pos {}) not found",
+                    name, pos + 1)
+            },
+            None => args.push(None)
+        }
+    }
+    if let Some(name) = kwargs.keys().next() {
+        panic!("TypeError: '{}' is an invalid keyword argument for {}()", name, func)
+    }
+    args
+}
+
+pub fn int_arg(value: &Value) -> i32 {
+    match value.as_index() {
+        Some(value) => value,
+        None => panic!("TypeError: an integer is required (got type {})",
+            value.type_name())
+    }
+}
+
+/// A `tzinfo` argument, `None` for naive times
+pub fn tzinfo_arg(value: Option<Value>) -> Option<TimeZone> {
+    match value {
+        None | Some(Value::None) => None,
+        Some(Value::DateTime(DateTimeType::TimeZone(tz))) => Some(tz),
+        Some(value) => panic!("TypeError: tzinfo argument must be None or of a \
+            tzinfo subclass, not type '{}'", value.type_name())
+    }
+}
+
+pub fn str_arg(value: Option<Value>, func: &str, default: &str) -> String {
+    match value {
+        Some(Value::Str(string)) => string,
+        None => default.to_string(),
+        Some(value) => panic!("TypeError: {}() argument must be str, not {}",
+            func, value.type_name())
+    }
+}
+
+/// The ISO 8601 date `YYYY-MM-DD`
+pub fn parse_iso_date(string: &str) -> Date {
+    match iso_numbers(string, &[4, 2, 2], '-') {
+        Some(ref fields) if string.len() == 10 => {
+            Date::new(fields[0], fields[1], fields[2])
+        },
+        _ => panic!("ValueError: Invalid isoformat string: '{}'", string)
+    }
+}
+
+/// The ISO 8601 time `HH[:MM[:SS[.fff[fff]]]]` with an optional
+/// `+HH:MM[:SS]` offset
+pub fn parse_iso_time(string: &str) -> Time {
+    let invalid = || -> ! {
+        panic!("ValueError: Invalid isoformat string: '{}'", string)
+    };
+    let (time, offset) = match string.find(['+', '-']) {
+        Some(idx) => (&string[..idx], Some(&string[idx..])),
+        None => (string, None)
+    };
+    let (time, fraction) = match time.find('.') {
+        Some(idx) => (&time[..idx], Some(&time[idx + 1..])),
+        None => (time, None)
+    };
+
+    let fields = match iso_numbers(time, &[2, 2, 2], ':') {
+        Some(fields) => fields,
+        None => invalid()
+    };
+    let microsecond = match fraction {
+        Some(digits) if fields.len() == 3 && (digits.len() == 3 || digits.len() == 6) &&
+            digits.bytes().all(|c| c.is_ascii_digit()) => {
+            digits.parse::<i32>().unwrap() * if digits.len() == 3 { 1000 } else { 1 }
+        },
+        Some(_) => invalid(),
+        None => 0
+    };
+    let tzinfo = offset.map(|offset| {
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        match iso_numbers(&offset[1..], &[2, 2, 2], ':') {
+            Some(ref fields) if fields.len() >= 2 => {
+                let seconds = fields.get(2).cloned().unwrap_or(0);
+                TimeZone::new(sign * (fields[0] * 3600 + fields[1] * 60 + seconds),
+                    None)
+            },
+            _ => invalid()
+        }
+    });
+    let field = |idx: usize| fields.get(idx).cloned().unwrap_or(0);
+    Time::new(field(0), field(1), field(2), microsecond, tzinfo)
+}
+
+/// A date and a time separated by any single character
+pub fn parse_iso_datetime(string: &str) -> DateTime {
+    let date = parse_iso_date(string.get(..10).unwrap_or(string));
+    let mut rest = string[10..].chars();
+    let time = match rest.next() {
+        Some(_) if !rest.as_str().is_empty() => parse_iso_time(rest.as_str()),
+        Some(_) => panic!("ValueError: Invalid isoformat string: '{}'", string),
+        None => Time::new(0, 0, 0, 0, None)
+    };
+    DateTime::new(date, time)
+}
+
+// Up to `widths.len()` fixed-width numbers separated by `sep`
+fn iso_numbers(string: &str, widths: &[usize], sep: char) -> Option<Vec<i32>> {
+    let parts: Vec<&str> = string.split(sep).collect();
+    if parts.len() > widths.len() {
+        return None
+    }
+    parts.iter().zip(widths).map(|(part, &width)| {
+        if part.len() == width && part.bytes().all(|c| c.is_ascii_digit()) {
+            part.parse().ok()
+        } else {
+            None
+        }
+    }).collect()
+}
+
+// A timestamp as whole seconds and microseconds
+pub fn timestamp_arg(value: &Value) -> (i64, i32) {
+    match *value {
+        Value::Number(NumericType::Float(secs)) => {
+            if secs.is_nan() {
+                panic!("ValueError: Invalid value NaN (not a number)")
+            }
+            if !secs.is_finite() {
+                panic!("OverflowError: timestamp out of range for platform time_t")
+            }
+            let micros = (secs as f64 * 1e6).round() as i128;
+            (micros.div_euclid(MICROS_PER_SECOND) as i64,
+                micros.rem_euclid(MICROS_PER_SECOND) as i32)
+        },
+        ref value => (int_arg(value) as i64, 0)
+    }
+}
+
+/// `lhs <op> rhs` for the arithmetic operators where either operand is one
+/// of the `datetime` values
+pub fn binary(op: &str, lhs: &Value, rhs: &Value) -> Value {
+    use self::DateTimeType::*;
+    let value = |value: &Value| match *value {
+        Value::DateTime(ref value) => Some(value.clone()),
+        _ => None
+    };
+    let shift = |dt: &self::DateTime, micros: i128| {
+        Value::DateTime(DateTime(self::DateTime::from_micros(
+            dt.local_micros() + micros, dt.time.tzinfo.clone())))
+    };
+
+    let result = match (op, value(lhs), value(rhs)) {
+        ("+", Some(TimeDelta(a)), Some(TimeDelta(b))) => Some(delta(a.micros + b.micros)),
+        ("-", Some(TimeDelta(a)), Some(TimeDelta(b))) => Some(delta(a.micros - b.micros)),
+        // Only whole days are added to a date
+        ("+", Some(Date(date)), Some(TimeDelta(td))) |
+        ("+", Some(TimeDelta(td)), Some(Date(date))) => {
+            Some(Value::DateTime(Date(self::Date::from_days(date.days() + td.days()))))
+        },
+        ("-", Some(Date(date)), Some(TimeDelta(td))) => {
+            let td = self::TimeDelta::new(-td.micros);
+            Some(Value::DateTime(Date(self::Date::from_days(date.days() + td.days()))))
+        },
+        ("-", Some(Date(a)), Some(Date(b))) => {
+            Some(delta((a.days() - b.days()) as i128 * MICROS_PER_DAY))
+        },
+        ("+", Some(DateTime(dt)), Some(TimeDelta(td))) |
+        ("+", Some(TimeDelta(td)), Some(DateTime(dt))) => Some(shift(&dt, td.micros)),
+        ("-", Some(DateTime(dt)), Some(TimeDelta(td))) => Some(shift(&dt, -td.micros)),
+        ("-", Some(DateTime(a)), Some(DateTime(b))) => {
+            match (a.utc_micros(), b.utc_micros()) {
+                (Some(a), Some(b)) => Some(delta(a - b)),
+                (None, None) => Some(delta(a.local_micros() - b.local_micros())),
+                _ => panic!("TypeError: can't subtract offset-naive and \
+                    offset-aware datetimes")
+            }
+        },
+        ("*", Some(TimeDelta(td)), None) => Some(scale(td, rhs, false)),
+        ("*", None, Some(TimeDelta(td))) => Some(scale(td, lhs, false)),
+        ("/", Some(TimeDelta(a)), Some(TimeDelta(b))) => {
+            if b.micros == 0 {
+                panic!("ZeroDivisionError: division by zero")
+            }
+            Some(Value::Number(NumericType::Float((a.micros as f64 /
+                b.micros as f64) as f32)))
+        },
+        ("/", Some(TimeDelta(td)), None) => Some(scale(td, rhs, true)),
+        ("%", Some(TimeDelta(a)), Some(TimeDelta(b))) => {
+            if b.micros == 0 {
+                panic!("ZeroDivisionError: integer division or modulo by zero")
+            }
+            // The remainder takes the divisor's sign
+            let rem = a.micros % b.micros;
+            Some(delta(if rem != 0 && (rem < 0) != (b.micros < 0) {
+                rem + b.micros
+            } else {
+                rem
+            }))
+        },
+        _ => None
+    };
+    result.unwrap_or_else(|| panic!("TypeError: unsupported operand type(s) for \
+        {}: '{}' and '{}'", op, lhs.type_name(), rhs.type_name()))
+}
+
+// Multiplies or divides a duration by a number, rounding half to even
+fn scale(td: TimeDelta, factor: &Value, divide: bool) -> Value {
+    let factor = match *factor {
+        Value::Number(NumericType::Float(factor)) => factor as f64,
+        ref factor => match factor.as_index() {
+            Some(factor) if !divide => return delta(td.micros * factor as i128),
+            Some(factor) => factor as f64,
+            None => panic!("TypeError: unsupported operand type(s) for {}: \
+                'datetime.timedelta' and '{}'", if divide { "/" } else { "*" },
+                factor.type_name())
+        }
+    };
+    if divide && factor == 0.0 {
+        panic!("ZeroDivisionError: division by zero")
+    }
+    delta(round_half_even(if divide {
+        td.micros as f64 / factor
+    } else {
+        td.micros as f64 * factor
+    }))
+}
+
+/// Rounds fractional microseconds half to even like Python
+pub fn round_half_even(micros: f64) -> i128 {
+    if micros.is_nan() {
+        panic!("ValueError: cannot convert float NaN to integer")
+    }
+    if !micros.is_finite() {
+        panic!("OverflowError: cannot convert float infinity to integer")
+    }
+    if (micros - micros.trunc()).abs() == 0.5 {
+        (2.0 * (micros / 2.0).round()) as i128
+    } else {
+        micros.round() as i128
+    }
+}
+
+impl DateTimeType {
+    pub fn type_name(&self) -> &str {
+        match *self {
+            DateTimeType::TimeDelta(_) => "datetime.timedelta",
+            DateTimeType::Date(_) => "datetime.date",
+            DateTimeType::Time(_) => "datetime.time",
+            DateTimeType::DateTime(_) => "datetime.datetime",
+            DateTimeType::TimeZone(_) => "datetime.timezone"
+        }
+    }
+
+    pub fn to_bool(&self) -> bool {
+        match *self {
+            DateTimeType::TimeDelta(ref td) => td.micros != 0,
+            _ => true
+        }
+    }
+
+    pub fn neg(&self) -> Value {
+        match *self {
+            DateTimeType::TimeDelta(ref td) => delta(-td.micros),
+            _ => panic!("TypeError: bad operand type for unary -: '{}'",
+                self.type_name())
+        }
+    }
+
+    /// Orders two values of the same type. Naive and aware times can't be
+    /// ordered against each other.
+    pub fn compare(&self, other: &DateTimeType, op: &str) -> cmp::Ordering {
+        use self::DateTimeType::*;
+        let aware = |a: Option<i128>, b: Option<i128>, naive: cmp::Ordering, kind| {
+            match (a, b) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (None, None) => naive,
+                _ => panic!("TypeError: can't compare offset-naive and \
+                    offset-aware {}", kind)
+            }
+        };
+        match (self, other) {
+            (&TimeDelta(ref a), &TimeDelta(ref b)) => a.cmp(b),
+            (&Date(ref a), &Date(ref b)) => a.cmp(b),
+            (&Time(ref a), &Time(ref b)) => aware(a.utc_micros(), b.utc_micros(),
+                a.micros().cmp(&b.micros()), "times"),
+            (&DateTime(ref a), &DateTime(ref b)) => aware(a.utc_micros(),
+                b.utc_micros(), a.local_micros().cmp(&b.local_micros()), "datetimes"),
+            _ => panic!("TypeError: '{}' not supported between instances of \
+                '{}' and '{}'", op, self.type_name(), other.type_name())
+        }
+    }
+
+    /// `==`, naive and aware times are never equal
+    pub fn equals(&self, other: &DateTimeType) -> bool {
+        use self::DateTimeType::*;
+        match (self, other) {
+            (&TimeZone(ref a), &TimeZone(ref b)) => a == b,
+            (&Time(ref a), &Time(ref b)) => match (a.utc_micros(), b.utc_micros()) {
+                (Some(a), Some(b)) => a == b,
+                (None, None) => a.micros() == b.micros(),
+                _ => false
+            },
+            (&DateTime(ref a), &DateTime(ref b)) => match (a.utc_micros(), b.utc_micros()) {
+                (Some(a), Some(b)) => a == b,
+                (None, None) => a.local_micros() == b.local_micros(),
+                _ => false
+            },
+            (&TimeDelta(_), &TimeDelta(_)) | (&Date(_), &Date(_)) => {
+                self.compare(other, "==") == cmp::Ordering::Equal
+            },
+            _ => false
+        }
+    }
+
+    pub fn get_attr(&self, attr: &str) -> Value {
+        use self::DateTimeType::*;
+        let date_attr = |date: &self::Date| match attr {
+            "year" => Some(int_value(date.year as i64)),
+            "month" => Some(int_value(date.month as i64)),
+            "day" => Some(int_value(date.day as i64)),
+            _ => None
+        };
+        let time_attr = |time: &self::Time| match attr {
+            "hour" => Some(int_value(time.hour as i64)),
+            "minute" => Some(int_value(time.minute as i64)),
+            "second" => Some(int_value(time.second as i64)),
+            "microsecond" => Some(int_value(time.microsecond as i64)),
+            "tzinfo" => Some(time.tzinfo.clone().map_or(Value::None, |tz| {
+                Value::DateTime(TimeZone(tz))
+            })),
+            _ => None
+        };
+        let value = match *self {
+            TimeDelta(ref td) => match attr {
+                "days" => Some(int_value(td.days())),
+                "seconds" => Some(int_value(td.seconds() as i64)),
+                "microseconds" => Some(int_value(td.microseconds() as i64)),
+                _ => None
+            },
+            Date(ref date) => date_attr(date),
+            Time(ref time) => time_attr(time),
+            DateTime(ref dt) => date_attr(&dt.date).or_else(|| time_attr(&dt.time)),
+            TimeZone(_) => None
+        };
+        value.unwrap_or_else(|| panic!("AttributeError: '{}' object has no \
+            attribute '{}'", self.type_name(), attr))
+    }
+
+    pub fn call(&self, attr: &str, args: Vec<Value>, kwargs: HashMap<String, Value>)
+        -> Value {
+        use self::DateTimeType::*;
+        let no_method = || -> ! {
+            panic!("AttributeError: '{}' object has no attribute '{}'",
+                self.type_name(), attr)
+        };
+        let string = |value: String| Value::Str(value);
+
+        // The methods dates and datetimes share
+        let date_method = |date: &self::Date, time: &self::Time,
+            args: Vec<Value>, kwargs: HashMap<String, Value>| match attr {
+            "weekday" => Some(int_value(date.weekday() as i64)),
+            "isoweekday" => Some(int_value(date.weekday() as i64 + 1)),
+            "isocalendar" => {
+                let (year, week, weekday) = date.isocalendar();
+                Some(Value::Tuple(TupleType::new(vec![int_value(year as i64),
+                    int_value(week as i64), int_value(weekday as i64)])))
+            },
+            "toordinal" => Some(int_value(date.toordinal())),
+            "timetuple" => Some(tm(date, time).to_tuple()),
+            "ctime" => Some(string(time::format("%c", &tm(date, time)))),
+            "strftime" => {
+                let args = bind_args("strftime", args, kwargs, &["format"], 1);
+                let fmt = str_arg(args[0].clone(), "strftime", "");
+                Some(string(time::format(&fmt, &tm(date, time))))
+            },
+            _ => None
+        };
+        // The methods times and datetimes share
+        let zone_method = |time: &self::Time| match attr {
+            "utcoffset" => Some(time.tzinfo.as_ref().map_or(Value::None, |tz| {
+                delta(tz.offset as i128 * MICROS_PER_SECOND)
+            })),
+            "tzname" => Some(time.tzinfo.as_ref().map_or(Value::None, |tz| {
+                string(tz.name())
+            })),
+            "dst" => Some(Value::None),
+            _ => None
+        };
+        let replace_time = |time: &self::Time, args: &[Option<Value>]| {
+            let field = |idx: usize, current: i32| {
+                args[idx].as_ref().map_or(current, int_arg)
+            };
+            let tzinfo = match args[4] {
+                Some(ref tzinfo) => tzinfo_arg(Some(tzinfo.clone())),
+                None => time.tzinfo.clone()
+            };
+            self::Time::new(field(0, time.hour), field(1, time.minute),
+                field(2, time.second), field(3, time.microsecond), tzinfo)
+        };
+        let midnight = self::Time::new(0, 0, 0, 0, None);
+
+        match *self {
+            TimeDelta(ref td) => match attr {
+                "total_seconds" => Value::Number(NumericType::Float(
+                    (td.micros as f64 / 1e6) as f32)),
+                _ => no_method()
+            },
+            TimeZone(ref tz) => match attr {
+                "utcoffset" => delta(tz.offset as i128 * MICROS_PER_SECOND),
+                "tzname" => string(tz.name()),
+                "dst" => Value::None,
+                _ => no_method()
+            },
+            Date(ref date) => match attr {
+                "isoformat" => string(date.isoformat()),
+                "replace" => {
+                    let args = bind_args("replace", args, kwargs,
+                        &["year", "month", "day"], 0);
+                    let field = |idx: usize, current: i32| {
+                        args[idx].as_ref().map_or(current, int_arg)
+                    };
+                    Value::DateTime(Date(self::Date::new(field(0, date.year),
+                        field(1, date.month), field(2, date.day))))
+                },
+                _ => date_method(date, &midnight, args, kwargs)
+                    .unwrap_or_else(|| no_method())
+            },
+            Time(ref time) => match attr {
+                "isoformat" => {
+                    let args = bind_args("isoformat", args, kwargs, &["timespec"], 0);
+                    string(time.isoformat(&str_arg(args[0].clone(), "isoformat",
+                        "auto")))
+                },
+                "strftime" => {
+                    let args = bind_args("strftime", args, kwargs, &["format"], 1);
+                    let fmt = str_arg(args[0].clone(), "strftime", "");
+                    let date = self::Date::new(1900, 1, 1);
+                    string(time::format(&fmt, &tm(&date, time)))
+                },
+                "replace" => {
+                    let args = bind_args("replace", args, kwargs, &["hour",
+                        "minute", "second", "microsecond", "tzinfo"], 0);
+                    Value::DateTime(Time(replace_time(time, &args)))
+                },
+                _ => zone_method(time).unwrap_or_else(|| no_method())
+            },
+            DateTime(ref dt) => match attr {
+                "date" => Value::DateTime(Date(dt.date)),
+                "time" => Value::DateTime(Time(self::Time { tzinfo: None, ..dt.time.clone() })),
+                "timetz" => Value::DateTime(Time(dt.time.clone())),
+                "isoformat" => {
+                    let args = bind_args("isoformat", args, kwargs,
+                        &["sep", "timespec"], 0);
+                    let sep = str_arg(args[0].clone(), "isoformat", "T");
+                    if sep.chars().count() != 1 {
+                        panic!("TypeError: isoformat() argument 1 must be a unicode \
+                            character, not str")
+                    }
+                    string(dt.isoformat(&sep, &str_arg(args[1].clone(), "isoformat",
+                        "auto")))
+                },
+                "replace" => {
+                    let args = bind_args("replace", args, kwargs, &["year", "month",
+                        "day", "hour", "minute", "second", "microsecond", "tzinfo"], 0);
+                    let field = |idx: usize, current: i32| {
+                        args[idx].as_ref().map_or(current, int_arg)
+                    };
+                    let date = self::Date::new(field(0, dt.date.year),
+                        field(1, dt.date.month), field(2, dt.date.day));
+                    let time = replace_time(&dt.time, &args[3..]);
+                    Value::DateTime(DateTime(self::DateTime::new(date, time)))
+                },
+                "timestamp" => {
+                    let secs = dt.epoch_micros().div_euclid(MICROS_PER_SECOND);
+                    if secs < i32::MIN as i128 || secs > i32::MAX as i128 {
+                        panic!("OverflowError: timestamp() result out of range")
+                    }
+                    int_value(secs as i64)
+                },
+                "astimezone" => {
+                    let args = bind_args("astimezone", args, kwargs, &["tz"], 0);
+                    let utc = dt.epoch_micros();
+                    let tz = tzinfo_arg(args[0].clone()).unwrap_or_else(|| {
+                        let secs = utc.div_euclid(MICROS_PER_SECOND) as i64;
+                        let zone = time::local_zone(secs);
+                        self::TimeZone::new(zone.offset, Some(zone.name))
+                    });
+                    let local = utc + tz.offset as i128 * MICROS_PER_SECOND;
+                    Value::DateTime(DateTime(self::DateTime::from_micros(local,
+                        Some(tz))))
+                },
+                _ => date_method(&dt.date, &dt.time, args, kwargs)
+                    .or_else(|| zone_method(&dt.time))
+                    .unwrap_or_else(|| no_method())
+            }
+        }
+    }
+}
+
+impl Hash for DateTimeType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            DateTimeType::TimeDelta(ref td) => td.micros.hash(state),
+            DateTimeType::Date(ref date) => date.hash(state),
+            DateTimeType::Time(ref time) => {
+                time.utc_micros().unwrap_or_else(|| time.micros()).hash(state)
+            },
+            DateTimeType::DateTime(ref dt) => {
+                dt.utc_micros().unwrap_or_else(|| dt.local_micros()).hash(state)
+            },
+            DateTimeType::TimeZone(ref tz) => tz.offset.hash(state)
+        }
+    }
+}
+
+impl fmt::Display for DateTimeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DateTimeType::TimeDelta(ref td) => {
+                let days = td.days();
+                if days != 0 {
+                    write!(f, "{} day{}, ", days, if days.abs() == 1 { "" } else { "s" })?;
+                }
+                let secs = td.seconds();
+                write!(f, "{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)?;
+                match td.microseconds() {
+                    0 => Ok(()),
+                    micros => write!(f, ".{:06}", micros)
+                }
+            },
+            DateTimeType::Date(ref date) => write!(f, "{}", date.isoformat()),
+            DateTimeType::Time(ref time) => write!(f, "{}", time.isoformat("auto")),
+            DateTimeType::DateTime(ref dt) => write!(f, "{}", dt.isoformat(" ", "auto")),
+            DateTimeType::TimeZone(ref tz) => write!(f, "{}", tz.name())
+        }
+    }
+}
+
+// The `repr()` of each type, trailing zero fields are left out like Python
+// 3.6 does
+impl fmt::Debug for DateTimeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time_fields = |time: &Time| {
+            let mut fields = format!("{}, {}", time.hour, time.minute);
+            if time.second != 0 || time.microsecond != 0 {
+                fields.push_str(&format!(", {}", time.second));
+            }
+            if time.microsecond != 0 {
+                fields.push_str(&format!(", {}", time.microsecond));
+            }
+            if let Some(ref tz) = time.tzinfo {
+                let tz = DateTimeType::TimeZone(tz.clone());
+                fields.push_str(&format!(", tzinfo={:?}", tz));
+            }
+            fields
+        };
+
+        match *self {
+            DateTimeType::TimeDelta(ref td) => {
+                write!(f, "datetime.timedelta({}", td.days())?;
+                if td.seconds() != 0 || td.microseconds() != 0 {
+                    write!(f, ", {}", td.seconds())?;
+                }
+                if td.microseconds() != 0 {
+                    write!(f, ", {}", td.microseconds())?;
+                }
+                write!(f, ")")
+            },
+            DateTimeType::Date(ref date) => write!(f, "datetime.date({}, {}, {})",
+                date.year, date.month, date.day),
+            DateTimeType::Time(ref time) => {
+                write!(f, "datetime.time({})", time_fields(time))
+            },
+            DateTimeType::DateTime(ref dt) => {
+                write!(f, "datetime.datetime({}, {}, {}, {})", dt.date.year,
+                    dt.date.month, dt.date.day, time_fields(&dt.time))
+            },
+            DateTimeType::TimeZone(ref tz) => {
+                if tz.offset == 0 && tz.name.is_none() {
+                    return write!(f, "datetime.timezone.utc")
+                }
+                let offset = DateTimeType::TimeDelta(TimeDelta::new(
+                    tz.offset as i128 * MICROS_PER_SECOND));
+                match tz.name {
+                    Some(ref name) => {
+                        write!(f, "datetime.timezone({:?}, '{}')", offset, name)
+                    },
+                    None => write!(f, "datetime.timezone({:?})", offset)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,594 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write, Seek, SeekFrom};
+
+use ::Value;
+use ::exception::Exception;
+use super::BytesType;
+use super::bytes;
+
+/// A file returned by `open()` together with the options parsed from its
+/// mode string and keyword arguments
+#[derive(Debug)]
+pub struct FileType {
+    // Taken out by `close()`, every alias of the file sees it closed
+    file: Option<File>,
+    name: String,
+    mode: String,
+    readable: bool,
+    writable: bool,
+    binary: bool,
+    encoding: String,
+    errors: String,
+    // `None` translates universal newlines, other values are written in
+    // place of '\n', see the `newline` argument of `open()`
+    newline: Option<String>,
+    buffering: i32,
+    // Bytes read ahead of the logical position, `read_pos` is the next one
+    // to hand out. Writes are held in `write_buf` until it's full or flushed.
+    read_buf: Vec<u8>,
+    read_pos: usize,
+    write_buf: Vec<u8>
+}
+
+// Size of the read and write buffers unless `buffering` asks for another
+pub const DEFAULT_BUFFER_SIZE: usize = 8192;
+
+impl FileType {
+    /// Opens `name` following Python's mode grammar: exactly one of `r`, `w`,
+    /// `x` or `a`, optionally `+` for updating and `b` or `t` for binary or
+    /// text mode. Binary mode takes no encoding, errors or newline.
+    pub fn open(name: &str, mode: &str, buffering: i32, encoding: Option<String>,
+        errors: Option<String>, newline: Option<String>) -> FileType {
+        let mut flags = String::new();
+        for c in mode.chars() {
+            if !"rwxa+bt".contains(c) || flags.contains(c) {
+                panic!("ValueError: invalid mode: '{}'", mode)
+            }
+            flags.push(c);
+        }
+        let (create, read, write, append) = (flags.contains('x'),
+            flags.contains('r'), flags.contains('w'), flags.contains('a'));
+        let (update, binary) = (flags.contains('+'), flags.contains('b'));
+
+        if binary && flags.contains('t') {
+            panic!("ValueError: can't have text and binary mode at once")
+        }
+        if [create, read, write, append].iter().filter(|flag| **flag).count() != 1 {
+            panic!("ValueError: must have exactly one of create/read/write/\
+                append mode")
+        }
+        if binary {
+            if encoding.is_some() {
+                panic!("ValueError: binary mode doesn't take an encoding \
+                    argument")
+            }
+            if errors.is_some() {
+                panic!("ValueError: binary mode doesn't take an errors \
+                    argument")
+            }
+            if newline.is_some() {
+                panic!("ValueError: binary mode doesn't take a newline \
+                    argument")
+            }
+        } else if buffering == 0 {
+            panic!("ValueError: can't have unbuffered text I/O")
+        }
+        match newline.as_deref() {
+            None | Some("") | Some("\n") | Some("\r") | Some("\r\n") => (),
+            Some(newline) => {
+                panic!("ValueError: illegal newline value: {:?}", newline)
+            }
+        }
+        let encoding = match encoding {
+            Some(encoding) => bytes::codec(&encoding).to_string(),
+            None => "utf-8".to_string()
+        };
+
+        let result = OpenOptions::new()
+            .read(read || update)
+            .write(write || create || update)
+            .append(append)
+            .truncate(write)
+            .create(write || append)
+            .create_new(create)
+            .open(name);
+        let file = match result {
+            Ok(file) => file,
+            Err(err) => Exception::from_io(&err, Some(name)).raise()
+        };
+
+        FileType {
+            file: Some(file),
+            name: name.to_string(),
+            mode: mode.to_string(),
+            readable: read || update,
+            writable: !read || update,
+            binary,
+            encoding,
+            errors: errors.unwrap_or_else(|| "strict".to_string()),
+            newline,
+            buffering,
+            read_buf: vec![],
+            read_pos: 0,
+            write_buf: vec![]
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    pub fn readable(&self) -> bool {
+        self.readable
+    }
+
+    pub fn writable(&self) -> bool {
+        self.writable
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.file.is_none()
+    }
+
+    /// Flushes buffered writes and releases the OS file. Closing an already
+    /// closed file does nothing.
+    pub fn close(&mut self) {
+        if self.file.is_none() {
+            return
+        }
+        let result = open_file(&mut self.file).write_all(&self.write_buf);
+        self.write_buf.clear();
+        self.read_buf.clear();
+        self.read_pos = 0;
+        self.file = None;
+
+        if let Err(err) = result {
+            Exception::from_io(&err, None).raise()
+        }
+    }
+
+    /// `read(size=-1)`, reads `size` characters (bytes in binary mode) or
+    /// everything up to the end of the file
+    pub fn read(&mut self, size: Option<usize>) -> Value {
+        self.check_open();
+        let mut data = vec![];
+
+        if self.binary {
+            while size.is_none_or(|size| data.len() < size) {
+                match self.next_byte() {
+                    Some(byte) => data.push(byte),
+                    None => break
+                }
+            }
+        } else {
+            let mut count = 0;
+            while size.is_none_or(|size| count < size) {
+                if self.next_char(&mut data).is_none() {
+                    break
+                }
+                count += 1;
+            }
+        }
+        self.wrap(data)
+    }
+
+    /// `readline(size=-1)`, the line keeps its terminator. In text mode the
+    /// terminators recognized depend on `newline`: any of '\n', '\r' and
+    /// '\r\n' when it's `None` or empty, otherwise only the given one.
+    /// Returns `None` at the end of the file.
+    pub fn readline(&mut self, size: Option<usize>) -> Option<Value> {
+        self.check_open();
+        let mut line = vec![];
+        let mut count = 0;
+
+        while size.is_none_or(|size| count < size) {
+            count += 1;
+            if self.binary {
+                match self.next_byte() {
+                    Some(byte) => {
+                        line.push(byte);
+                        if byte == b'\n' {
+                            break
+                        }
+                    },
+                    None => break
+                }
+                continue
+            }
+
+            let end = match (self.next_char(&mut line), self.newline.as_deref()) {
+                (None, _) => break,
+                (Some(b'\n'), None) | (Some(b'\r'), None) => true,
+                (Some(b'\n'), Some("")) | (Some(b'\n'), Some("\n")) => true,
+                (Some(b'\r'), Some("")) => {
+                    if self.peek_byte() == Some(b'\n') {
+                        self.next_byte();
+                        line.push(b'\n');
+                    }
+                    true
+                },
+                (Some(b'\r'), Some("\r")) => true,
+                (Some(b'\n'), Some("\r\n")) => line.ends_with(b"\r\n"),
+                _ => false
+            };
+            if end {
+                break
+            }
+        }
+
+        if line.is_empty() {
+            None
+        } else {
+            Some(self.wrap(line))
+        }
+    }
+
+    /// `write(value)`, text files take a str which is encoded and has its
+    /// newlines translated, binary files take a bytes-like object. Returns
+    /// the number of characters or bytes written.
+    pub fn write_value(&mut self, value: &Value) -> usize {
+        self.check_open();
+        if !self.writable {
+            panic!("io.UnsupportedOperation: not writable")
+        }
+        let (data, count) = match (self.binary, value) {
+            (true, value) => match bytes::as_bytes(value) {
+                Some(data) => {
+                    let len = data.len();
+                    (data, len)
+                },
+                None => panic!("TypeError: a bytes-like object is required, \
+                    not '{}'", value.type_name())
+            },
+            (false, &Value::Str(ref text)) => {
+                let text = match self.newline.as_deref() {
+                    Some("\r") | Some("\r\n") => {
+                        text.replace('\n', self.newline.as_ref().unwrap())
+                    },
+                    _ => text.clone()
+                };
+                let data = bytes::encode(&text, &self.encoding, &self.errors);
+                (data, text.chars().count())
+            },
+            (false, value) => panic!("TypeError: write() argument must be \
+                str, not {}", value.type_name())
+        };
+
+        // Data read ahead is dropped so the write lands at the position the
+        // caller sees from `tell()`
+        self.discard_read_buf();
+        self.write_buf.extend_from_slice(&data);
+
+        let line_buffered = self.buffering == 1 && !self.binary
+            && data.contains(&b'\n');
+        if line_buffered || self.write_buf.len() >= self.buffer_size() {
+            self.flush();
+        }
+        count
+    }
+
+    /// Writes any buffered data to the file
+    pub fn flush(&mut self) {
+        self.check_open();
+        if self.write_buf.is_empty() {
+            return
+        }
+        let result = open_file(&mut self.file).write_all(&self.write_buf);
+        self.write_buf.clear();
+        if let Err(err) = result {
+            Exception::from_io(&err, None).raise()
+        }
+    }
+
+    /// Writes out buffered data ignoring errors, for when the file goes away
+    /// or the process exits without a chance to report them
+    pub fn flush_at_exit(&mut self) {
+        if let Some(ref mut file) = self.file {
+            let _ = file.write_all(&self.write_buf);
+        }
+        self.write_buf.clear();
+    }
+
+    /// `seek(offset, whence=0)`, returns the new absolute position. Text
+    /// files only support seeking relative to the start, or to the current
+    /// position or the end with a zero offset.
+    pub fn seek(&mut self, offset: i64, whence: i32) -> u64 {
+        self.check_open();
+        let pos = match whence {
+            0 if offset < 0 => {
+                panic!("ValueError: negative seek position {}", offset)
+            },
+            0 => SeekFrom::Start(offset as u64),
+            1 | 2 if !self.binary && offset != 0 => {
+                panic!("io.UnsupportedOperation: can't do nonzero {}-relative \
+                    seeks", if whence == 1 { "cur" } else { "end" })
+            },
+            1 => SeekFrom::Start((self.tell() as i64 + offset).max(0) as u64),
+            2 => SeekFrom::End(offset),
+            _ => panic!("ValueError: invalid whence ({}, should be 0, 1 or 2)",
+                whence)
+        };
+
+        self.flush();
+        self.discard_read_buf();
+        match open_file(&mut self.file).seek(pos) {
+            Ok(pos) => pos,
+            Err(err) => Exception::from_io(&err, None).raise()
+        }
+    }
+
+    /// Current logical position, accounting for buffered reads and writes
+    pub fn tell(&mut self) -> u64 {
+        let pos = match open_file(&mut self.file).stream_position() {
+            Ok(pos) => pos,
+            Err(err) => Exception::from_io(&err, None).raise()
+        };
+        let unread = (self.read_buf.len() - self.read_pos) as u64;
+
+        pos - unread + self.write_buf.len() as u64
+    }
+
+    /// `truncate(size=None)`, resizes the file to `size` bytes or the current
+    /// position, the position itself is unchanged
+    pub fn truncate(&mut self, size: Option<u64>) -> u64 {
+        self.check_open();
+        if !self.writable {
+            panic!("io.UnsupportedOperation: File not open for writing")
+        }
+        let size = match size {
+            Some(size) => size,
+            None => self.tell()
+        };
+        let pos = self.tell();
+
+        self.flush();
+        self.discard_read_buf();
+        if let Err(err) = open_file(&mut self.file).set_len(size)
+            .and_then(|_| open_file(&mut self.file).seek(SeekFrom::Start(pos))) {
+            Exception::from_io(&err, None).raise()
+        }
+        size
+    }
+
+    /// The underlying OS file descriptor
+    #[cfg(unix)]
+    pub fn fileno(&self) -> i32 {
+        use std::os::unix::io::AsRawFd;
+        match self.file {
+            Some(ref file) => file.as_raw_fd(),
+            None => panic!("ValueError: I/O operation on closed file")
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn fileno(&self) -> i32 {
+        panic!("io.UnsupportedOperation: fileno")
+    }
+
+    // Converts data read from the file into bytes or, in text mode, a str
+    // decoded with the file's encoding and newline handling
+    fn wrap(&self, data: Vec<u8>) -> Value {
+        if self.binary {
+            return Value::Bytes(BytesType::new(data))
+        }
+        let text = bytes::decode(&data, &self.encoding, &self.errors);
+
+        match self.newline {
+            None => Value::Str(text.replace("\r\n", "\n").replace('\r', "\n")),
+            Some(_) => Value::Str(text)
+        }
+    }
+
+    fn check_open(&self) {
+        if self.file.is_none() {
+            panic!("ValueError: I/O operation on closed file")
+        }
+    }
+
+    fn buffer_size(&self) -> usize {
+        if self.buffering > 1 {
+            self.buffering as usize
+        } else if self.buffering == 0 {
+            0
+        } else {
+            DEFAULT_BUFFER_SIZE
+        }
+    }
+
+    fn peek_byte(&mut self) -> Option<u8> {
+        if self.read_pos == self.read_buf.len() {
+            if !self.readable {
+                panic!("io.UnsupportedOperation: not readable")
+            }
+            // Pending writes must reach the file before reading past them
+            self.flush();
+            self.read_buf.resize(self.buffer_size().max(1), 0);
+            self.read_pos = 0;
+            match open_file(&mut self.file).read(&mut self.read_buf) {
+                Ok(len) => self.read_buf.truncate(len),
+                Err(err) => {
+                    self.read_buf.clear();
+                    Exception::from_io(&err, None).raise()
+                }
+            }
+        }
+        self.read_buf.get(self.read_pos).cloned()
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        let byte = self.peek_byte();
+        if byte.is_some() {
+            self.read_pos += 1;
+        }
+        byte
+    }
+
+    // Appends the encoded bytes of the next character to `data` and returns
+    // its first byte. With universal newlines a '\r\n' pair is one character.
+    fn next_char(&mut self, data: &mut Vec<u8>) -> Option<u8> {
+        let first = self.next_byte()?;
+        let len = match (self.encoding.as_str(), first) {
+            ("utf-8", 0xf0..=0xff) => 4,
+            ("utf-8", 0xe0..=0xef) => 3,
+            ("utf-8", 0xc0..=0xdf) => 2,
+            _ => 1
+        };
+
+        data.push(first);
+        for _ in 1..len {
+            match self.peek_byte() {
+                Some(byte) if byte & 0xc0 == 0x80 => {
+                    self.next_byte();
+                    data.push(byte);
+                },
+                _ => break
+            }
+        }
+        if first == b'\r' && self.newline.is_none()
+            && self.peek_byte() == Some(b'\n') {
+            self.next_byte();
+            data.push(b'\n');
+        }
+        Some(first)
+    }
+
+    // Moves the file position back over data that was read ahead but not
+    // consumed
+    fn discard_read_buf(&mut self) {
+        let unread = (self.read_buf.len() - self.read_pos) as i64;
+        self.read_buf.clear();
+        self.read_pos = 0;
+
+        if unread > 0 {
+            if let Err(err) = open_file(&mut self.file).seek(SeekFrom::Current(-unread)) {
+                Exception::from_io(&err, None).raise()
+            }
+        }
+    }
+}
+
+// Buffered writes are flushed when the last reference to the file goes away
+impl Drop for FileType {
+    fn drop(&mut self) {
+        self.flush_at_exit();
+    }
+}
+
+// The file of an open FileType, operations on a closed file are a ValueError
+fn open_file(file: &mut Option<File>) -> &mut File {
+    match *file {
+        Some(ref mut file) => file,
+        None => panic!("ValueError: I/O operation on closed file")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use ::exception;
+
+    fn temp_path(name: &str) -> String {
+        let path = env::temp_dir().join(format!("cannolib_file_{}", name));
+        let _ = fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
+
+    fn open_err(name: &str, mode: &str, encoding: Option<&str>) -> String {
+        let encoding = encoding.map(|s| s.to_string());
+        exception::catch(|| {
+            FileType::open(name, mode, -1, encoding, None, None)
+        }).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn file_mode_validation() {
+        let path = temp_path("modes");
+
+        assert_eq!(open_err(&path, "rw", None), "ValueError: must have \
+            exactly one of create/read/write/append mode");
+        assert_eq!(open_err(&path, "rr", None), "ValueError: invalid mode: 'rr'");
+        assert_eq!(open_err(&path, "rbt", None), "ValueError: can't have text \
+            and binary mode at once");
+        assert_eq!(open_err(&path, "wb", Some("utf-8")), "ValueError: binary \
+            mode doesn't take an encoding argument");
+        assert_eq!(open_err(&path, "r", None).starts_with("FileNotFoundError: \
+            [Errno 2]"), true);
+    }
+
+    #[test]
+    fn file_append_and_exclusive() {
+        let path = temp_path("append");
+        let text = |value: &str| Value::Str(value.to_string());
+
+        let mut file = FileType::open(&path, "x", -1, None, None, None);
+        file.write_value(&text("one\n"));
+        drop(file);
+        assert_eq!(open_err(&path, "x", None).starts_with("FileExistsError: \
+            [Errno 17] File exists"), true);
+
+        let mut file = FileType::open(&path, "a", -1, None, None, None);
+        assert_eq!(file.readable(), false);
+        file.write_value(&text("two\n"));
+        file.flush();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+
+        let mut file = FileType::open(&path, "rb+", -1, None, None, None);
+        assert_eq!(file.read(None), Value::Bytes(BytesType::new(
+            b"one\ntwo\n".to_vec())));
+        assert_eq!(file.writable(), true);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn file_universal_newlines() {
+        let path = temp_path("newlines");
+        fs::write(&path, "a\r\nb\rc\nd").unwrap();
+        let text = |value: &str| Some(Value::Str(value.to_string()));
+
+        let mut file = FileType::open(&path, "r", -1, None, None, None);
+        assert_eq!(file.readline(None), text("a\n"));
+        assert_eq!(file.readline(None), text("b\n"));
+        assert_eq!(file.read(Some(2)), Value::Str("c\n".to_string()));
+        assert_eq!(file.readline(None), text("d"));
+        assert_eq!(file.readline(None), None);
+
+        let mut file = FileType::open(&path, "r", -1, None, None,
+            Some("".to_string()));
+        assert_eq!(file.readline(None), text("a\r\n"));
+        assert_eq!(file.readline(None), text("b\r"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn file_buffered_seek_and_tell() {
+        let path = temp_path("seek");
+        let text = |value: &str| Value::Str(value.to_string());
+
+        let mut file = FileType::open(&path, "w+", -1, None, None, None);
+        file.write_value(&text("hello world\n"));
+        assert_eq!(file.tell(), 12);
+        assert_eq!(file.seek(6, 0), 6);
+        assert_eq!(file.read(Some(5)), text("world"));
+        assert_eq!(file.tell(), 11);
+
+        file.seek(0, 0);
+        assert_eq!(file.read(Some(1)), text("h"));
+        file.write_value(&text("E"));
+        file.seek(0, 0);
+        assert_eq!(file.read(None), text("hEllo world\n"));
+        assert_eq!(file.truncate(Some(5)), 5);
+        file.seek(0, 0);
+        assert_eq!(file.read(None), text("hEllo"));
+        let _ = fs::remove_file(&path);
+    }
+}
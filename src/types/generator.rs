@@ -0,0 +1,362 @@
+use std::fmt;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use ::Value;
+use ::exception::{self, Exception};
+
+/// How a suspended generator body is resumed. `Send` is used for both
+/// `__next__` (sending `None`) and `send(value)`, `Throw` raises the exception
+/// at the `yield` the body is suspended on, `close()` throws `GeneratorExit`.
+pub enum GenSignal {
+    Send(Value),
+    Throw(Exception)
+}
+
+/// What a generator body hands back when it suspends or finishes
+pub enum GenState {
+    Yield(Value),
+    // `yield from iterable`, values are pulled from the iterable until it is
+    // exhausted, then the body is resumed with the iterable's return value
+    YieldFrom(Value),
+    Return(Value)
+}
+
+/// The compiler lowers a function containing `yield` into a state machine
+/// closure, each call runs the body up to the next `yield` and returns the
+/// state. This type drives that closure with Python's generator protocol.
+pub type GenBody = Box<dyn FnMut(GenSignal) -> GenState>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GenStatus {
+    Created,
+    Suspended,
+    Running,
+    Closed
+}
+
+struct GenInner {
+    name: String,
+    // Taken out while the body runs so re-entrant calls can be detected
+    body: Option<GenBody>,
+    status: GenStatus,
+    delegate: Option<Value>,
+    return_value: Value
+}
+
+#[derive(Clone)]
+pub struct GeneratorType {
+    inner: Rc<RefCell<GenInner>>
+}
+
+impl GeneratorType {
+    pub fn new(name: &str, body: GenBody) -> GeneratorType {
+        GeneratorType {
+            inner: Rc::new(RefCell::new(GenInner {
+                name: name.to_string(),
+                body: Some(body),
+                status: GenStatus::Created,
+                delegate: None,
+                return_value: Value::None
+            }))
+        }
+    }
+
+    pub fn call(&self, attr: &str, args: Vec<Value>,
+        _kwargs: HashMap<String, Value>) -> Value {
+        let mut args_iter = args.into_iter();
+
+        match attr {
+            "__next__" => self.send(Value::None).unwrap_or_else(|| {
+                panic!("StopIteration")
+            }),
+            "send" => {
+                let value = match args_iter.next() {
+                    Some(value) => value,
+                    None => panic!("TypeError: send() takes exactly one \
+                        argument (0 given)")
+                };
+                self.send(value).unwrap_or_else(|| panic!("StopIteration"))
+            },
+            "throw" => {
+                let exc = match (args_iter.next(), args_iter.next()) {
+                    (Some(Value::Str(name)), Some(Value::Str(msg))) => {
+                        Exception::new(&name, &msg)
+                    },
+                    (Some(Value::Str(exc)), _) => Exception::parse(&exc),
+                    _ => panic!("TypeError: exceptions must be given as \
+                        'name' or 'name: message' strings")
+                };
+                self.throw(exc).unwrap_or_else(|| panic!("StopIteration"))
+            },
+            "close" => {
+                self.close();
+                Value::None
+            },
+            _ => panic!("'generator' object has no attribute '{}'", attr)
+        }
+    }
+
+    /// Resumes the generator with `value` as the result of the current
+    /// `yield`, returns `None` once the generator is exhausted.
+    pub fn send(&self, value: Value) -> Option<Value> {
+        self.resume(GenSignal::Send(value))
+    }
+
+    /// Raises `exc` inside the generator at the suspended `yield`
+    pub fn throw(&self, exc: Exception) -> Option<Value> {
+        self.resume(GenSignal::Throw(exc))
+    }
+
+    /// Raises `GeneratorExit` inside the generator, the body is expected to
+    /// finish (or re-raise) rather than yield another value.
+    pub fn close(&self) {
+        let status = self.inner.borrow().status;
+        match status {
+            GenStatus::Created | GenStatus::Closed => {
+                self.finish(Value::None);
+                return
+            },
+            _ => ()
+        }
+
+        let result = exception::catch(|| {
+            self.throw(Exception::new("GeneratorExit", ""))
+        });
+        match result {
+            Ok(Some(_)) => {
+                panic!("RuntimeError: generator ignored GeneratorExit")
+            },
+            Ok(None) => (),
+            Err(ref exc) if exc.is("GeneratorExit")
+                || exc.is("StopIteration") => (),
+            Err(exc) => exc.raise()
+        }
+    }
+
+    /// Value given to the generator's `return`, `yield from` evaluates to it
+    pub fn return_value(&self) -> Value {
+        self.inner.borrow().return_value.clone()
+    }
+
+    pub fn name(&self) -> String {
+        self.inner.borrow().name.clone()
+    }
+
+    pub fn ptr_eq(&self, other: &GeneratorType) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Address of the shared generator state, identifies the generator
+    pub fn as_ptr(&self) -> *const u8 {
+        Rc::as_ptr(&self.inner) as *const u8
+    }
+
+    fn resume(&self, mut signal: GenSignal) -> Option<Value> {
+        loop {
+            let delegate = self.inner.borrow().delegate.clone();
+            if let Some(delegate) = delegate {
+                match resume_delegate(&delegate, signal) {
+                    Ok(Some(value)) => return Some(value),
+                    Ok(None) => {
+                        let value = match delegate {
+                            Value::Generator(ref gen) => gen.return_value(),
+                            _ => Value::None
+                        };
+                        self.inner.borrow_mut().delegate = None;
+                        signal = GenSignal::Send(value);
+                    },
+                    Err(exc) => {
+                        self.inner.borrow_mut().delegate = None;
+                        signal = GenSignal::Throw(exc);
+                    }
+                }
+            }
+
+            let status = self.inner.borrow().status;
+            match (status, &signal) {
+                (GenStatus::Running, _) => {
+                    panic!("ValueError: generator already executing")
+                },
+                (GenStatus::Closed, &GenSignal::Send(_)) => return None,
+                (GenStatus::Closed, &GenSignal::Throw(ref exc)) |
+                (GenStatus::Created, &GenSignal::Throw(ref exc)) => {
+                    self.finish(Value::None);
+                    exc.raise()
+                },
+                (GenStatus::Created, &GenSignal::Send(ref value)) => {
+                    if *value != Value::None {
+                        panic!("TypeError: can't send non-None value to a \
+                            just-started generator")
+                    }
+                },
+                (GenStatus::Suspended, _) => ()
+            }
+
+            let mut body = {
+                let mut inner = self.inner.borrow_mut();
+                inner.status = GenStatus::Running;
+                inner.body.take().unwrap()
+            };
+            let result = exception::catch(|| body(signal));
+            {
+                let mut inner = self.inner.borrow_mut();
+                inner.body = Some(body);
+                inner.status = GenStatus::Suspended;
+            }
+
+            match result {
+                Ok(GenState::Yield(value)) => return Some(value),
+                Ok(GenState::YieldFrom(iterable)) => {
+                    self.inner.borrow_mut().delegate = Some(iterable.iter());
+                    signal = GenSignal::Send(Value::None);
+                },
+                Ok(GenState::Return(value)) => {
+                    self.finish(value);
+                    return None
+                },
+                Err(exc) => {
+                    self.finish(Value::None);
+                    // PEP 479, a StopIteration escaping the body would
+                    // otherwise silently end the caller's loop
+                    if exc.is("StopIteration") {
+                        panic!("RuntimeError: generator raised StopIteration")
+                    }
+                    exc.raise()
+                }
+            }
+        }
+    }
+
+    fn finish(&self, return_value: Value) {
+        let mut inner = self.inner.borrow_mut();
+        inner.status = GenStatus::Closed;
+        inner.body = None;
+        inner.delegate = None;
+        inner.return_value = return_value;
+    }
+}
+
+impl fmt::Display for GeneratorType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<generator object {} at {:p}>", self.name(), self.inner)
+    }
+}
+
+// Forwards a resumption to the iterable of a `yield from`. Generators receive
+// sent values and thrown exceptions, plain iterators can only be advanced so
+// exceptions are raised back in the delegating generator instead.
+fn resume_delegate(delegate: &Value, signal: GenSignal)
+    -> Result<Option<Value>, Exception> {
+    match (delegate, signal) {
+        (&Value::Generator(ref gen), GenSignal::Send(value)) => {
+            exception::catch(|| gen.send(value))
+        },
+        (&Value::Generator(ref gen), GenSignal::Throw(exc)) => {
+            if exc.is("GeneratorExit") {
+                exception::catch(|| gen.close())?;
+                Err(exc)
+            } else {
+                exception::catch(|| gen.throw(exc))
+            }
+        },
+        (_, GenSignal::Send(_)) => exception::catch(|| delegate.next()),
+        (_, GenSignal::Throw(exc)) => Err(exc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::builtin::int_value;
+
+    // def counter(n):
+    //     for i in range(n): yield i
+    //     return 'done'
+    fn counter(n: i32) -> Value {
+        let mut i = 0;
+        let body = move |signal| {
+            if let GenSignal::Throw(exc) = signal {
+                exc.raise()
+            }
+            if i < n {
+                i += 1;
+                GenState::Yield(int_value(i - 1))
+            } else {
+                GenState::Return(Value::Str("done".to_string()))
+            }
+        };
+        Value::Generator(GeneratorType::new("counter", Box::new(body)))
+    }
+
+    #[test]
+    fn generator_iteration() {
+        let gen = counter(3);
+
+        assert_eq!(gen.iter() == gen, true);
+        assert_eq!(gen.clone_seq(), vec![int_value(0), int_value(1), int_value(2)]);
+        assert_eq!(gen.next(), None);
+    }
+
+    #[test]
+    fn generator_send() {
+        // def echo():
+        //     value = yield 'ready'
+        //     while True: value = yield value
+        let mut started = false;
+        let body = move |signal| {
+            match signal {
+                GenSignal::Send(value) => {
+                    if started {
+                        GenState::Yield(value)
+                    } else {
+                        started = true;
+                        GenState::Yield(Value::Str("ready".to_string()))
+                    }
+                },
+                GenSignal::Throw(exc) => exc.raise()
+            }
+        };
+        let gen = GeneratorType::new("echo", Box::new(body));
+
+        assert_eq!(gen.send(Value::None), Some(Value::Str("ready".to_string())));
+        assert_eq!(gen.send(int_value(4)), Some(int_value(4)));
+        gen.close();
+        assert_eq!(gen.send(int_value(5)), None);
+    }
+
+    #[test]
+    fn generator_yield_from() {
+        // def outer():
+        //     result = yield from counter(2)
+        //     yield result
+        let mut state = 0;
+        let body = move |signal| {
+            state += 1;
+            match (state, signal) {
+                (_, GenSignal::Throw(exc)) => exc.raise(),
+                (1, _) => GenState::YieldFrom(counter(2)),
+                (2, GenSignal::Send(result)) => GenState::Yield(result),
+                _ => GenState::Return(Value::None)
+            }
+        };
+        let gen = Value::Generator(GeneratorType::new("outer", Box::new(body)));
+
+        assert_eq!(gen.clone_seq(), vec![int_value(0), int_value(1),
+            Value::Str("done".to_string())]);
+    }
+
+    #[test]
+    fn generator_throw() {
+        let gen = counter(3);
+        let result = exception::catch(|| {
+            ::call_member(gen.clone(), "throw",
+                vec![Value::Str("ValueError: bad".to_string())],
+                HashMap::new())
+        });
+
+        assert_eq!(gen.next(), None);
+        assert_eq!(result.err(), Some(Exception::new("ValueError", "bad")));
+    }
+}
@@ -1,13 +1,50 @@
-use std::fs::File;
+use std::io::{self, Read, Write, BufRead, BufWriter, IsTerminal};
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 
 use ::Value;
+use ::exception::Exception;
+use super::NumericType;
+use super::ListType;
+use super::FileType;
+use super::MemoryFileType;
+use super::BytesType;
+use super::bytes;
+use super::DEFAULT_BUFFER_SIZE;
+
+thread_local! {
+    // Output written to the process' stdout. It is block buffered unless
+    // stdout is a terminal, dropping it at exit flushes what is left.
+    static STDOUT: RefCell<StdoutBuffer> = RefCell::new(StdoutBuffer {
+        writer: BufWriter::with_capacity(DEFAULT_BUFFER_SIZE, io::stdout()),
+        line_buffered: io::stdout().is_terminal()
+    });
+    // Files opened by `open()`, flushed when `sys.exit` ends the process
+    static OPEN_FILES: RefCell<Vec<Weak<RefCell<FileType>>>> =
+        const { RefCell::new(vec![]) };
+}
+
+struct StdoutBuffer {
+    writer: BufWriter<io::Stdout>,
+    line_buffered: bool
+}
+
+impl StdoutBuffer {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data)?;
+        if self.line_buffered && data.contains(&b'\n') {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum IOWrapper {
-    File(Rc<RefCell<File>>),
+    File(Rc<RefCell<FileType>>),
+    // `io.StringIO` and `io.BytesIO`
+    Memory(Rc<RefCell<MemoryFileType>>),
     Stdin,
     Stderr,
     Stdout,
@@ -15,21 +52,505 @@ pub enum IOWrapper {
 }
 
 impl IOWrapper {
-    pub fn call(&mut self, attr: &str, _args: Vec<Value>,
-        _kwargs: HashMap<String, Value>) -> Value {
+    /// Wraps a newly opened file and keeps track of it until it goes away
+    pub fn new_file(file: FileType) -> IOWrapper {
+        let file = Rc::new(RefCell::new(file));
+        OPEN_FILES.with(|files| {
+            let mut files = files.borrow_mut();
+            files.retain(|file| file.strong_count() > 0);
+            files.push(Rc::downgrade(&file));
+        });
+        IOWrapper::File(file)
+    }
+
+    pub fn call(&mut self, attr: &str, args: Vec<Value>,
+        kwargs: HashMap<String, Value>) -> Value {
+        let arg = |pos: usize, name: &str| -> Option<Value> {
+            match args.get(pos).or_else(|| kwargs.get(name)) {
+                Some(&Value::None) | None => None,
+                Some(value) => Some(value.clone())
+            }
+        };
+        // Offsets past 2 GiB don't fit the runtime's integers
+        let int = |val: i64| {
+            if val < i32::MIN as i64 || val > i32::MAX as i64 {
+                panic!("OverflowError: Python int too large to convert to C long")
+            }
+            Value::Number(NumericType::Integer(val as i32))
+        };
+
         match attr {
             "close" => self.close(),
-            _ => panic!(format!("'list' has no attribute '{}'", attr))
+            "read" => self.read(size_arg(arg(0, "size"))),
+            "readline" => {
+                let size = size_arg(arg(0, "size"));
+                self.readline_limit(size).unwrap_or_else(|| if self.is_binary() {
+                    BytesType::new_bytes(vec![])
+                } else {
+                    Value::Str(String::new())
+                })
+            },
+            "readlines" => {
+                // Lines are read until their total size exceeds the hint
+                let hint = size_arg(arg(0, "hint"));
+                let mut lines = vec![];
+                let mut total = 0;
+                while hint.is_none_or(|hint| total < hint || hint == 0) {
+                    match self.readline_limit(None) {
+                        Some(line) => {
+                            total += match line {
+                                Value::Str(ref string) => string.chars().count(),
+                                ref line => bytes::as_bytes(line)
+                                    .map_or(0, |data| data.len())
+                            };
+                            lines.push(line);
+                        },
+                        None => break
+                    }
+                }
+                Value::List(Rc::new(RefCell::new(ListType::new(lines))))
+            },
+            "write" => match arg(0, "s") {
+                Some(value) => int(self.write(&value) as i64),
+                None => panic!("TypeError: write() takes exactly one argument \
+                    (0 given)")
+            },
+            "writelines" => {
+                let lines = match arg(0, "lines") {
+                    Some(lines) => lines,
+                    None => panic!("TypeError: writelines() takes exactly one \
+                        argument (0 given)")
+                };
+                for line in lines {
+                    self.write(&line);
+                }
+                Value::None
+            },
+            "flush" => {
+                self.flush();
+                Value::None
+            },
+            "seek" => {
+                let offset = match arg(0, "offset").and_then(|v| v.as_index()) {
+                    Some(offset) => offset as i64,
+                    None => panic!("TypeError: seek() missing required \
+                        argument 'offset'")
+                };
+                let whence = arg(1, "whence").and_then(|v| v.as_index())
+                    .unwrap_or(0);
+                int(match *self {
+                    IOWrapper::Memory(ref buf) => {
+                        buf.borrow_mut().seek(offset, whence)
+                    },
+                    _ => self.file("seek").borrow_mut().seek(offset, whence)
+                } as i64)
+            },
+            "tell" => int(match *self {
+                IOWrapper::Memory(ref buf) => buf.borrow().tell(),
+                _ => self.file("tell").borrow_mut().tell()
+            } as i64),
+            "truncate" => {
+                let size = size_arg(arg(0, "size")).map(|size| size as u64);
+                int(match *self {
+                    IOWrapper::Memory(ref buf) => buf.borrow_mut().truncate(size),
+                    _ => self.file("truncate").borrow_mut().truncate(size)
+                } as i64)
+            },
+            "getvalue" => match *self {
+                IOWrapper::Memory(ref buf) => buf.borrow().getvalue(),
+                IOWrapper::Closed => {
+                    panic!("ValueError: I/O operation on closed file.")
+                },
+                _ => panic!("AttributeError: '{}' object has no attribute \
+                    'getvalue'", self.type_name())
+            },
+            "fileno" => match *self {
+                IOWrapper::File(ref file) => int(file.borrow().fileno() as i64),
+                IOWrapper::Memory(_) => {
+                    panic!("io.UnsupportedOperation: fileno")
+                },
+                IOWrapper::Stdin => int(0),
+                IOWrapper::Stdout => int(1),
+                IOWrapper::Stderr => int(2),
+                IOWrapper::Closed => {
+                    panic!("ValueError: I/O operation on closed file")
+                }
+            },
+            "readable" | "writable" | "seekable" if self.is_closed() => {
+                panic!("ValueError: I/O operation on closed file")
+            },
+            "readable" => Value::Bool(match *self {
+                IOWrapper::File(ref file) => file.borrow().readable(),
+                IOWrapper::Stdin | IOWrapper::Memory(_) => true,
+                IOWrapper::Closed => {
+                    panic!("ValueError: I/O operation on closed file")
+                },
+                _ => false
+            }),
+            "writable" => Value::Bool(match *self {
+                IOWrapper::File(ref file) => file.borrow().writable(),
+                IOWrapper::Stdout | IOWrapper::Stderr |
+                IOWrapper::Memory(_) => true,
+                IOWrapper::Closed => {
+                    panic!("ValueError: I/O operation on closed file")
+                },
+                IOWrapper::Stdin => false
+            }),
+            "seekable" => Value::Bool(matches!(*self,
+                IOWrapper::File(_) | IOWrapper::Memory(_))),
+            "__enter__" => {
+                if self.is_closed() {
+                    panic!("ValueError: I/O operation on closed file")
+                }
+                Value::TextIOWrapper(self.clone())
+            },
+            "__exit__" => {
+                self.close();
+                Value::Bool(false)
+            },
+            "__iter__" => Value::TextIOWrapper(self.clone()).iter(),
+            "__next__" => match self.readline() {
+                Some(line) => line,
+                None => panic!("StopIteration")
+            },
+            _ => panic!("AttributeError: '{}' object has no attribute '{}'",
+                self.type_name(), attr)
         }
     }
 
-    fn close(&mut self) -> Value {
+    /// The `name`, `mode` and `closed` attributes
+    pub fn get_attr(&self, attr: &str) -> Value {
+        let string = |value: &str| Value::Str(value.to_string());
+
+        match (attr, self) {
+            ("name", &IOWrapper::File(ref file)) => string(file.borrow().name()),
+            ("name", &IOWrapper::Stdin) => string("<stdin>"),
+            ("name", &IOWrapper::Stdout) => string("<stdout>"),
+            ("name", &IOWrapper::Stderr) => string("<stderr>"),
+            ("mode", &IOWrapper::File(ref file)) => string(file.borrow().mode()),
+            ("mode", &IOWrapper::Stdin) => string("r"),
+            ("mode", &IOWrapper::Stdout) | ("mode", &IOWrapper::Stderr) => {
+                string("w")
+            },
+            ("closed", _) => Value::Bool(self.is_closed()),
+            _ => panic!("AttributeError: '{}' object has no attribute '{}'",
+                self.type_name(), attr)
+        }
+    }
+
+    /// Python type of the stream, files opened in binary mode are buffered
+    /// byte streams rather than text wrappers
+    pub fn type_name(&self) -> &str {
+        match *self {
+            IOWrapper::File(_) if self.is_binary() => "_io.BufferedRandom",
+            IOWrapper::Memory(_) if self.is_binary() => "_io.BytesIO",
+            IOWrapper::Memory(_) => "_io.StringIO",
+            _ => "_io.TextIOWrapper"
+        }
+    }
+
+    // Whether reads produce bytes rather than str
+    fn is_binary(&self) -> bool {
+        match *self {
+            IOWrapper::File(ref file) => file.borrow().is_binary(),
+            IOWrapper::Memory(ref buf) => buf.borrow().is_binary(),
+            _ => false
+        }
+    }
+
+    /// Reads the next line, including the trailing newline, returns `None`
+    /// once the end of the stream is reached. Binary files produce bytes,
+    /// everything else a str.
+    pub fn readline(&self) -> Option<Value> {
+        self.readline_limit(None)
+    }
+
+    fn readline_limit(&self, size: Option<usize>) -> Option<Value> {
         match *self {
+            IOWrapper::File(ref file) => file.borrow_mut().readline(size),
+            IOWrapper::Memory(ref buf) => buf.borrow_mut().readline(size),
+            IOWrapper::Stdin => read_line(&mut io::stdin().lock()).map(Value::Str),
+            IOWrapper::Closed => {
+                panic!("ValueError: I/O operation on closed file")
+            },
+            _ => panic!("io.UnsupportedOperation: not readable")
+        }
+    }
+
+    // Reads `size` characters or the rest of the stream
+    fn read(&self, size: Option<usize>) -> Value {
+        match *self {
+            IOWrapper::File(ref file) => file.borrow_mut().read(size),
+            IOWrapper::Memory(ref buf) => buf.borrow_mut().read(size),
+            IOWrapper::Stdin => {
+                let mut contents = Vec::new();
+                let stdin = io::stdin();
+                let result = match size {
+                    Some(size) => read_chars(&mut stdin.lock(), size,
+                        &mut contents),
+                    None => stdin.lock().read_to_end(&mut contents).map(|_| ())
+                };
+                if let Err(err) = result {
+                    Exception::from_io(&err, None).raise()
+                }
+                Value::Str(wrap(&contents))
+            },
+            IOWrapper::Closed => {
+                panic!("ValueError: I/O operation on closed file")
+            },
+            _ => panic!("io.UnsupportedOperation: not readable")
+        }
+    }
+
+    // Writes a str to text streams or a bytes-like value to binary files,
+    // returning the number of characters or bytes written
+    fn write(&self, value: &Value) -> usize {
+        match (self, value) {
+            (&IOWrapper::File(ref file), value) => {
+                file.borrow_mut().write_value(value)
+            },
+            (&IOWrapper::Memory(ref buf), value) => {
+                buf.borrow_mut().write_value(value)
+            },
+            (&IOWrapper::Closed, _) => {
+                panic!("ValueError: I/O operation on closed file")
+            },
+            (&IOWrapper::Stdin, _) => {
+                panic!("io.UnsupportedOperation: not writable")
+            },
+            (iow, &Value::Str(ref string)) => {
+                let result = match *iow {
+                    IOWrapper::Stderr => io::stderr().write_all(string.as_bytes()),
+                    _ => STDOUT.with(|stdout| {
+                        stdout.borrow_mut().write(string.as_bytes())
+                    })
+                };
+                if let Err(err) = result {
+                    Exception::from_io(&err, None).raise()
+                }
+                string.chars().count()
+            },
+            (_, value) => panic!("TypeError: write() argument must be str, \
+                not {}", value.type_name())
+        }
+    }
+
+    fn flush(&self) {
+        let result = match *self {
             IOWrapper::File(ref file) => {
-                drop(file)
+                file.borrow_mut().flush();
+                Ok(())
+            },
+            IOWrapper::Memory(ref buf) => {
+                buf.borrow().flush();
+                Ok(())
+            },
+            IOWrapper::Stdout => flush_stdout(),
+            IOWrapper::Stderr => io::stderr().flush(),
+            IOWrapper::Stdin => Ok(()),
+            IOWrapper::Closed => {
+                panic!("ValueError: I/O operation on closed file")
+            }
+        };
+        if let Err(err) = result {
+            Exception::from_io(&err, None).raise()
+        }
+    }
+
+    // The open file for methods only supported on real files
+    fn file(&self, method: &str) -> &Rc<RefCell<FileType>> {
+        match *self {
+            IOWrapper::File(ref file) => file,
+            IOWrapper::Closed => {
+                panic!("ValueError: I/O operation on closed file")
             },
+            _ => panic!("io.UnsupportedOperation: {}", method)
+        }
+    }
+
+    // Closes the shared file so every alias of it is closed as well, this
+    // wrapper itself becomes `Closed`
+    fn close(&mut self) -> Value {
+        match *self {
+            IOWrapper::File(ref file) => file.borrow_mut().close(),
+            IOWrapper::Memory(ref buf) => buf.borrow_mut().close(),
             _ => ()
         }
+        *self = IOWrapper::Closed;
         Value::None
     }
+
+    /// Whether the stream was closed through this or any other alias
+    pub fn is_closed(&self) -> bool {
+        match *self {
+            IOWrapper::File(ref file) => file.borrow().is_closed(),
+            IOWrapper::Memory(ref buf) => buf.borrow().is_closed(),
+            IOWrapper::Closed => true,
+            _ => false
+        }
+    }
+}
+
+/// Writes out the buffers of stdout and every open file without raising,
+/// used when the process exits without unwinding
+pub fn flush_all() {
+    OPEN_FILES.with(|files| {
+        for file in files.borrow().iter().filter_map(|file| file.upgrade()) {
+            if let Ok(mut file) = file.try_borrow_mut() {
+                file.flush_at_exit();
+            }
+        }
+    });
+    let _ = flush_stdout();
+}
+
+/// Writes out everything buffered for the process' stdout
+pub fn flush_stdout() -> io::Result<()> {
+    STDOUT.with(|stdout| stdout.borrow_mut().writer.flush())
+}
+
+/// Reads a line for `input()`, without its trailing newline. Returns `None`
+/// at the end of the stream.
+pub fn input_line<R: BufRead>(reader: &mut R) -> Option<String> {
+    read_line(reader).map(|mut line| {
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        line
+    })
+}
+
+// Reads up to and including the next line ending of a standard stream
+fn read_line<R: BufRead>(reader: &mut R) -> Option<String> {
+    let mut line = Vec::new();
+    if let Err(err) = read_until_newline(reader, &mut line) {
+        Exception::from_io(&err, None).raise()
+    }
+    if line.is_empty() {
+        None
+    } else {
+        Some(wrap(&line))
+    }
+}
+
+// Universal newlines, a line ends at '\n', '\r' or '\r\n'. The byte after a
+// '\r' is peeked to tell the last two apart.
+fn read_until_newline<R: BufRead>(reader: &mut R, line: &mut Vec<u8>)
+    -> io::Result<()> {
+    loop {
+        let (found, used) = {
+            let buf = reader.fill_buf()?;
+            match buf.iter().position(|byte| *byte == b'\n' || *byte == b'\r') {
+                Some(pos) => {
+                    line.extend_from_slice(&buf[..=pos]);
+                    (true, pos + 1)
+                },
+                None => {
+                    line.extend_from_slice(buf);
+                    (buf.is_empty(), buf.len())
+                }
+            }
+        };
+        reader.consume(used);
+        if found {
+            break
+        }
+    }
+    if line.last() == Some(&b'\r')
+        && reader.fill_buf()?.first() == Some(&b'\n') {
+        line.push(b'\n');
+        reader.consume(1);
+    }
+    Ok(())
+}
+
+// Standard streams are utf-8 text with universal newlines
+fn wrap(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+// Reads `size` utf-8 characters, stopping early at the end of the stream
+fn read_chars<R: BufRead>(reader: &mut R, size: usize, data: &mut Vec<u8>)
+    -> io::Result<()> {
+    for _ in 0..size {
+        let mut byte = [0u8; 1];
+        if reader.read(&mut byte)? == 0 {
+            break
+        }
+        let len = match byte[0] {
+            0xf0..=0xff => 4,
+            0xe0..=0xef => 3,
+            0xc0..=0xdf => 2,
+            _ => 1
+        };
+        data.push(byte[0]);
+        for _ in 1..len {
+            match reader.fill_buf()?.first() {
+                Some(&byte) if byte & 0xc0 == 0x80 => {
+                    data.push(byte);
+                    reader.consume(1);
+                },
+                _ => break
+            }
+        }
+    }
+    Ok(())
+}
+
+// Negative and missing sizes both mean "everything"
+fn size_arg(size: Option<Value>) -> Option<usize> {
+    match size {
+        Some(value) => match value.as_index() {
+            Some(size) if size >= 0 => Some(size as usize),
+            Some(_) => None,
+            None => panic!("TypeError: argument should be integer or None, \
+                not '{}'", value.type_name())
+        },
+        None => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(data: &[u8], size: usize) -> Vec<u8> {
+        let mut read = Vec::new();
+        read_chars(&mut io::Cursor::new(data), size, &mut read).unwrap();
+        read
+    }
+
+    #[test]
+    fn stdin_read_chars() {
+        assert_eq!(chars("héllo".as_bytes(), 2), "hé".as_bytes());
+        assert_eq!(chars("€😀x".as_bytes(), 2), "€😀".as_bytes());
+        assert_eq!(chars(b"ab", 5), b"ab");
+        assert_eq!(chars(b"", 3), b"");
+        // A truncated sequence is a single character, the stray byte after
+        // it starts the next one
+        assert_eq!(chars(b"\xe2\x82x", 2), b"\xe2\x82x");
+        assert_eq!(chars(b"\xe2\x82", 5), b"\xe2\x82");
+        assert_eq!(wrap(b"\xe2\x82"), "\u{fffd}");
+    }
+
+    #[test]
+    fn stdin_input_line() {
+        let mut reader = io::Cursor::new(&b"first\r\nsecond\rthird\nlast"[..]);
+        assert_eq!(input_line(&mut reader), Some("first".to_string()));
+        assert_eq!(input_line(&mut reader), Some("second".to_string()));
+        assert_eq!(input_line(&mut reader), Some("third".to_string()));
+        assert_eq!(input_line(&mut reader), Some("last".to_string()));
+        assert_eq!(input_line(&mut reader), None);
+        assert_eq!(input_line(&mut io::Cursor::new(&b"\n"[..])),
+            Some("".to_string()));
+
+        // A '\r\n' split between two reads of the buffer is one line ending
+        let mut reader = io::BufReader::with_capacity(2,
+            &b"a\r\nb\r\r\n"[..]);
+        assert_eq!(read_line(&mut reader), Some("a\n".to_string()));
+        assert_eq!(read_line(&mut reader), Some("b\n".to_string()));
+        assert_eq!(read_line(&mut reader), Some("\n".to_string()));
+        assert_eq!(read_line(&mut reader), None);
+    }
 }
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use ::Value;
+use super::ListType;
+use super::TupleType;
+use super::NumericType;
+use super::IOWrapper;
+use super::RangeType;
+use super::BytesType;
+
+/// Lazy iterators over the built-in iterables, these are what `iter()`
+/// returns and what for-loops advance. Each variant keeps a reference to the
+/// underlying value and a position instead of copying the sequence.
+pub enum IteratorType {
+    List { list: Rc<RefCell<ListType>>, pos: usize },
+    Tuple { tup: TupleType, pos: usize },
+    Str { string: String, pos: usize },
+    Range { range: RangeType, pos: usize },
+    Bytes { bytes: BytesType, pos: usize },
+    ByteArray { bytes: Rc<RefCell<BytesType>>, pos: usize },
+    File(IOWrapper),
+    // Two argument form of `iter(callable, sentinel)`
+    Callable { func: Value, sentinel: Value },
+    // The builtins below wrap other iterators and pull from them on demand
+    Enumerate { iter: Value, count: i32 },
+    Zip { iters: Vec<Value>, strict: bool },
+    Map { func: Value, iters: Vec<Value> },
+    // A `None` function filters on the truthiness of the elements
+    Filter { func: Value, iter: Value },
+    // Walks a sequence backwards by index, `pos` is one past the next index
+    Reversed { seq: Value, pos: usize },
+    Exhausted
+}
+
+impl IteratorType {
+    pub fn new_list(list: Rc<RefCell<ListType>>) -> IteratorType {
+        IteratorType::List { list, pos: 0 }
+    }
+
+    pub fn new_tuple(tup: TupleType) -> IteratorType {
+        IteratorType::Tuple { tup, pos: 0 }
+    }
+
+    pub fn new_str(string: String) -> IteratorType {
+        IteratorType::Str { string, pos: 0 }
+    }
+
+    pub fn new_range(range: RangeType) -> IteratorType {
+        IteratorType::Range { range, pos: 0 }
+    }
+
+    /// Name used when displaying the iterator, mirrors CPython's type names
+    pub fn type_name(&self) -> &str {
+        match *self {
+            IteratorType::List { .. } => "list_iterator",
+            IteratorType::Tuple { .. } => "tuple_iterator",
+            IteratorType::Str { .. } => "str_iterator",
+            IteratorType::Range { .. } => "range_iterator",
+            IteratorType::Bytes { .. } => "bytes_iterator",
+            IteratorType::ByteArray { .. } => "bytearray_iterator",
+            IteratorType::File(_) => "TextIOWrapper",
+            IteratorType::Callable { .. } => "callable_iterator",
+            IteratorType::Enumerate { .. } => "enumerate",
+            IteratorType::Zip { .. } => "zip",
+            IteratorType::Map { .. } => "map",
+            IteratorType::Filter { .. } => "filter",
+            IteratorType::Reversed { .. } => "reversed",
+            IteratorType::Exhausted => "iterator"
+        }
+    }
+}
+
+impl Iterator for IteratorType {
+    type Item = Value;
+
+    /// Advances the iterator, `None` is the equivalent of `StopIteration`.
+    /// Once an iterator is exhausted it stays exhausted, even if the list it
+    /// was created from grows afterwards.
+    fn next(&mut self) -> Option<Value> {
+        let value = match *self {
+            IteratorType::List { ref list, ref mut pos } => {
+                let value = list.borrow().get(*pos);
+                *pos += 1;
+                value
+            },
+            IteratorType::Tuple { ref tup, ref mut pos } => {
+                let value = tup.get(*pos);
+                *pos += 1;
+                value
+            },
+            IteratorType::Str { ref string, ref mut pos } => {
+                string[*pos..].chars().next().map(|c| {
+                    *pos += c.len_utf8();
+                    Value::Str(c.to_string())
+                })
+            },
+            IteratorType::Range { ref range, ref mut pos } => {
+                let value = range.get(*pos);
+                *pos += 1;
+                value
+            },
+            IteratorType::Bytes { ref bytes, ref mut pos } => {
+                let value = bytes.get(*pos);
+                *pos += 1;
+                value
+            },
+            IteratorType::ByteArray { ref bytes, ref mut pos } => {
+                let value = bytes.borrow().get(*pos);
+                *pos += 1;
+                value
+            },
+            IteratorType::File(ref iow) => {
+                iow.readline()
+            },
+            IteratorType::Callable { ref func, ref sentinel } => {
+                let value = func.call(vec![], HashMap::new());
+                if value == *sentinel {
+                    None
+                } else {
+                    Some(value)
+                }
+            },
+            IteratorType::Enumerate { ref iter, ref mut count } => {
+                iter.next().map(|value| {
+                    let index = Value::Number(NumericType::Integer(*count));
+                    *count += 1;
+                    Value::Tuple(TupleType::new(vec![index, value]))
+                })
+            },
+            IteratorType::Zip { ref iters, strict } => next_zip(iters, strict),
+            IteratorType::Map { ref func, ref iters } => {
+                let mut args = Vec::with_capacity(iters.len());
+                for iter in iters.iter() {
+                    match iter.next() {
+                        Some(value) => args.push(value),
+                        None => break
+                    }
+                }
+
+                if args.len() == iters.len() {
+                    Some(func.call(args, HashMap::new()))
+                } else {
+                    None
+                }
+            },
+            IteratorType::Filter { ref func, ref iter } => {
+                let mut result = None;
+                while let Some(value) = iter.next() {
+                    let keep = match *func {
+                        Value::None => value.to_bool(),
+                        _ => func.call(vec![value.clone()], HashMap::new())
+                            .to_bool()
+                    };
+
+                    if keep {
+                        result = Some(value);
+                        break
+                    }
+                }
+                result
+            },
+            IteratorType::Reversed { ref seq, ref mut pos } => {
+                if *pos == 0 {
+                    None
+                } else {
+                    *pos -= 1;
+                    match *seq {
+                        Value::List(ref list) => list.borrow().get(*pos),
+                        Value::Tuple(ref tup) => tup.get(*pos),
+                        Value::Bytes(ref bytes) => bytes.get(*pos),
+                        Value::ByteArray(ref bytes) => bytes.borrow().get(*pos),
+                        _ => {
+                            let index = Value::Number(
+                                NumericType::Integer(*pos as i32));
+                            Some(::call_member(seq.clone(), "__getitem__",
+                                vec![index], HashMap::new()))
+                        }
+                    }
+                }
+            },
+            IteratorType::Exhausted => None
+        };
+
+        if value.is_none() {
+            *self = IteratorType::Exhausted;
+        }
+        value
+    }
+}
+
+// Advances every iterator once, stopping at the shortest. With `strict` the
+// remaining iterators must be exhausted at the same time.
+fn next_zip(iters: &[Value], strict: bool) -> Option<Value> {
+    let mut values = Vec::with_capacity(iters.len());
+
+    for (idx, iter) in iters.iter().enumerate() {
+        match iter.next() {
+            Some(value) => values.push(value),
+            None => {
+                if strict && idx > 0 {
+                    panic!("ValueError: zip() argument {} is shorter than {}",
+                        idx + 1, zip_arguments(idx))
+                } else if strict {
+                    for (idx, iter) in iters.iter().enumerate().skip(1) {
+                        if iter.next().is_some() {
+                            panic!("ValueError: zip() argument {} is longer \
+                                than {}", idx + 1, zip_arguments(idx))
+                        }
+                    }
+                }
+                return None
+            }
+        }
+    }
+
+    if values.is_empty() {
+        None
+    } else {
+        Some(Value::Tuple(TupleType::new(values)))
+    }
+}
+
+fn zip_arguments(count: usize) -> String {
+    if count == 1 {
+        "argument 1".to_string()
+    } else {
+        format!("arguments 1-{}", count)
+    }
+}
@@ -6,6 +6,7 @@ use std::cell::RefCell;
 
 use ::Value;
 use super::NumericType;
+use super::slice;
 
 #[derive(Debug, Clone)]
 pub struct ListType {
@@ -22,21 +23,7 @@ impl ListType {
     }
 
     pub fn index(&self, index: Value) -> Value {
-        let pos = match index {
-            Value::Number(NumericType::Integer(pos)) => pos,
-            _ => panic!("list indices must be integers or slices")
-        };
-
-        if pos.abs() as usize >= self.list.len() {
-            panic!("list index out of range")
-        }
-
-        let pos: usize = if pos < 0 {
-            self.list.len() - pos.abs() as usize
-        } else {
-            pos as usize
-        };
-
+        let pos = self.position(&index, "list index out of range");
         self.list[pos].clone()
     }
 
@@ -49,22 +36,9 @@ impl ListType {
         Value::Number(NumericType::Integer(self.list.len() as i32))
     }
 
-    /// Returns minimum element in sequence, will fail if elements in sequence
-    /// vary in type.
-    pub fn min(&self) -> Value {
-        if self.list.is_empty() {
-            panic!("min() arg is an empty sequence")
-        }
-        let mut iter = self.list.iter();
-        let mut min_val = iter.next().unwrap();
-
-        for value in iter {
-            if value < min_val {
-                min_val = value
-            }
-        }
-
-        min_val.clone()
+    /// Returns the length as a usize for use within the runtime
+    pub fn length(&self) -> usize {
+        self.list.len()
     }
 
     /// Replicates Python3's slicing feature
@@ -81,89 +55,187 @@ impl ListType {
     /// reference: https://stackoverflow.com/a/509295
     pub fn slice(&self, lower: Option<Value>, upper: Option<Value>,
         step: Option<Value>) -> Value {
-        let step = match step {
-            Some(Value::Number(NumericType::Integer(step))) => {
-                if step == 0 {
-                    panic!("slice step cannot be zero")
-                } else {
-                    step
-                }
-            },
-            Some(Value::None) => 1,
-            None => 1,
-            _ => panic!("slice indices must be integers or None")
-        };
-        let list = if step < 0 {
-            let lower = match lower {
-                Some(Value::Number(NumericType::Integer(lower))) =>
-                    calculate_slice(lower, 0, self.list.len() as i32),
-                None => self.list.len() as i32,
-                _ => panic!("slice indices must be integers or None")
-            };
-            let upper = match upper {
-                Some(Value::Number(NumericType::Integer(upper))) =>
-                    calculate_slice(upper, -1, self.list.len() as i32),
-                None => -1,
-                _ => panic!("slice indices must be integers or None")
-            };
+        let (start, stop, step) = slice::indices(lower, upper, step,
+            self.list.len());
+        let list: Vec<Value> = slice::positions(start, stop, step).into_iter()
+            .map(|pos| self.list[pos].clone()).collect();
 
-            if lower > upper {
-                let temp = lower;
-                let lower = (upper + 1) as usize;
-                let upper = if temp == self.list.len() as i32 { temp as usize }
-                    else { (temp + 1) as usize };
-
-                let list: Vec<Value> = (&self.list[lower..upper])
-                    .iter().map(|val| val.clone()).collect();
+        Value::List(Rc::new(RefCell::new(ListType::new(list))))
+    }
 
-                list.iter().rev().enumerate()
-                    .filter(|elem| elem.0 % (step.abs() as usize) == 0)
-                    .map(|elem| elem.1.clone())
-                    .collect()
-            } else {
-                vec![]
-            }
-        } else {
-            let lower = match lower {
-                Some(Value::Number(NumericType::Integer(lower))) =>
-                    calculate_slice(lower, 0, self.list.len() as i32) as usize,
-                None => 0,
-                _ => panic!("slice indices must be integers or None")
-            };
-            let upper = match upper {
-                Some(Value::Number(NumericType::Integer(upper))) =>
-                    calculate_slice(upper, 0, self.list.len() as i32) as usize,
-                None => self.list.len(),
-                _ => panic!("slice indices must be integers or None")
+    /// Stable in-place sort, `key` is called once per element. A reversed
+    /// sort keeps equal elements in their original order, as Python does.
+    pub fn sort(&mut self, key: Option<Value>, reverse: bool) {
+        let mut keyed: Vec<(Value, Value)> = self.list.iter().cloned().map(|value| {
+            let sort_key = match key {
+                Some(ref func) => func.call(vec![value.clone()], HashMap::new()),
+                None => value.clone()
             };
+            (sort_key, value)
+        }).collect();
 
-            if lower >= upper {
-                vec![]
+        keyed.sort_by(|a, b| {
+            if reverse {
+                compare(&b.0, &a.0)
             } else {
-                (&self.list[lower..upper]).iter()
-                    .enumerate()
-                    .filter(|elem| elem.0 % (step as usize) == 0)
-                    .map(|elem| elem.1.clone())
-                    .collect()
+                compare(&a.0, &b.0)
             }
-        };
+        });
+        self.list = keyed.into_iter().map(|(_, value)| value).collect();
+    }
 
-        Value::List(Rc::new(RefCell::new(ListType::new(list))))
+    /// Appends every value, used by in-place concatenation
+    pub fn extend_values(&mut self, values: Vec<Value>) {
+        self.list.extend(values);
+    }
+
+    /// Repeats the contents `count` times in place, used by `*` and `*=`
+    pub fn repeat(&mut self, count: usize) {
+        let len = self.list.len();
+        let list = self.list.iter().cloned().cycle().take(len * count).collect();
+        self.list = list;
     }
 
     pub fn clone_seq(&self) -> Vec<Value> {
         self.list.clone()
     }
 
-    pub fn call(&mut self, attr: &str, args: Vec<Value>,
+    /// Returns the element at `pos` without bounds panics, used by iterators
+    pub fn get(&self, pos: usize) -> Option<Value> {
+        self.list.get(pos).cloned()
+    }
+
+    /// List methods. Only the mutating ones borrow the list mutably, the
+    /// others, and the comparisons and key calls of `remove` and `sort`, run
+    /// on a copy of the elements so they may use the list itself.
+    pub fn call_list(list: &Rc<RefCell<ListType>>, attr: &str,
+        args: Vec<Value>, kwargs: HashMap<String, Value>) -> Value {
+        match attr {
+            "extend" => {
+                // Collected first, `l.extend(l)` reads the list it extends
+                let values = match args.first() {
+                    Some(iterable) if args.len() == 1 => iterable.clone_seq(),
+                    _ => panic!("TypeError: extend() takes exactly one \
+                        argument ({} given)", args.len())
+                };
+                list.borrow_mut().list.extend(values);
+                Value::None
+            },
+            "append" | "pop" | "insert" | "reverse" | "clear" => {
+                list.borrow_mut().call_mut(attr, args, kwargs)
+            },
+            "remove" => {
+                let pos = list.borrow().clone().position_of(args);
+                let mut list = list.borrow_mut();
+                // The comparisons may have shrunk the list
+                if pos < list.list.len() {
+                    list.list.remove(pos);
+                }
+                Value::None
+            },
+            "sort" => {
+                let mut sorted = list.borrow().clone();
+                let result = sorted.sort_method(args, kwargs);
+                list.borrow_mut().list = sorted.list;
+                result
+            },
+            _ => {
+                let snapshot = list.borrow().clone();
+                snapshot.call(attr, args, kwargs)
+            }
+        }
+    }
+
+    // The methods that leave the list unchanged
+    fn call(&self, attr: &str, args: Vec<Value>,
         kwargs: HashMap<String, Value>) -> Value {
         match attr {
-            "append" => self.append(args, kwargs),
-            "pop"    => self.pop(args, kwargs),
+            "index"   => self.find(args, kwargs),
+            "count"   => self.count(args, kwargs),
+            "copy"    => {
+                let list = ListType::new(self.list.clone());
+                Value::List(Rc::new(RefCell::new(list)))
+            },
             _ => panic!(format!("'list' has no attribute '{}'", attr))
         }
     }
 
+    // The mutating methods, none of them compares elements or calls back
+    // into Python code
+    fn call_mut(&mut self, attr: &str, args: Vec<Value>,
+        kwargs: HashMap<String, Value>) -> Value {
+        match attr {
+            "append"  => self.append(args, kwargs),
+            "pop"     => self.pop(args, kwargs),
+            "insert"  => self.insert(args, kwargs),
+            "reverse" => {
+                self.list.reverse();
+                Value::None
+            },
+            "clear"   => {
+                self.list.clear();
+                Value::None
+            },
+            _ => self.call(attr, args, kwargs)
+        }
+    }
+
+    /// `l[index] = value`
+    pub fn set_index(&mut self, index: Value, value: Value) {
+        let pos = self.position(&index, "list assignment index out of range");
+        self.list[pos] = value;
+    }
+
+    /// `del l[index]`
+    pub fn del_index(&mut self, index: Value) {
+        let pos = self.position(&index, "list assignment index out of range");
+        self.list.remove(pos);
+    }
+
+    /// `l[lower:upper:step] = values`, a simple slice may change the length
+    /// of the list while an extended slice must match the number of values.
+    pub fn set_slice(&mut self, lower: Option<Value>, upper: Option<Value>,
+        step: Option<Value>, values: Vec<Value>) {
+        let (start, stop, step) = slice::indices(lower, upper, step,
+            self.list.len());
+
+        if step == 1 {
+            let start = start as usize;
+            let stop = if stop < start as i64 { start } else { stop as usize };
+            self.list.splice(start..stop, values);
+        } else {
+            let positions = slice::positions(start, stop, step);
+            if positions.len() != values.len() {
+                panic!("ValueError: attempt to assign sequence of size {} to \
+                    extended slice of size {}", values.len(), positions.len())
+            }
+
+            for (pos, value) in positions.into_iter().zip(values) {
+                self.list[pos] = value;
+            }
+        }
+    }
+
+    /// `del l[lower:upper:step]`
+    pub fn del_slice(&mut self, lower: Option<Value>, upper: Option<Value>,
+        step: Option<Value>) {
+        let (start, stop, step) = slice::indices(lower, upper, step,
+            self.list.len());
+
+        if step == 1 {
+            let stop = stop.max(start);
+            self.list.drain(start as usize..stop as usize);
+        } else {
+            // One pass keeping every element that isn't selected
+            let mut selected = vec![false; self.list.len()];
+            for pos in slice::positions(start, stop, step) {
+                selected[pos] = true;
+            }
+            let mut selected = selected.into_iter();
+            self.list.retain(|_| !selected.next().unwrap());
+        }
+    }
+
     fn append(&mut self, mut args: Vec<Value>, _kwargs: HashMap<String, Value>)
         -> Value {
         if args.len() != 1 {
@@ -175,9 +247,123 @@ impl ListType {
         Value::None
     }
 
-    fn pop(&mut self, mut _args: Vec<Value>, _kwargs: HashMap<String, Value>)
+    fn pop(&mut self, args: Vec<Value>, _kwargs: HashMap<String, Value>)
+        -> Value {
+        match args.into_iter().next() {
+            Some(index) => {
+                if self.list.is_empty() {
+                    panic!("IndexError: pop from an empty list")
+                }
+                let pos = self.position(&index, "pop index out of range");
+                self.list.remove(pos)
+            },
+            None => self.list.pop().expect("IndexError: pop from an empty list")
+        }
+    }
+
+    fn insert(&mut self, args: Vec<Value>, _kwargs: HashMap<String, Value>)
+        -> Value {
+        if args.len() != 2 {
+            panic!("TypeError: insert expected 2 arguments, got {}",
+                args.len())
+        }
+        let mut args_iter = args.into_iter();
+        let len = self.list.len() as i64;
+        // Out of range positions clamp to the ends of the list
+        let pos = match args_iter.next().unwrap() {
+            ref value if value.as_index().is_some() => {
+                let pos = value.as_index().unwrap() as i64;
+                if pos < 0 { (pos + len).max(0) } else { pos.min(len) }
+            },
+            value => panic!("TypeError: '{}' object cannot be interpreted as \
+                an integer", value.type_name())
+        };
+
+        self.list.insert(pos as usize, args_iter.next().unwrap());
+        Value::None
+    }
+
+    // The position `l.remove(value)` removes
+    fn position_of(&self, args: Vec<Value>) -> usize {
+        if args.len() != 1 {
+            panic!("TypeError: remove() takes exactly one argument ({} given)",
+                args.len())
+        }
+
+        match self.list.iter().position(|e| matches(e, &args[0])) {
+            Some(pos) => pos,
+            None => panic!("ValueError: list.remove(x): x not in list")
+        }
+    }
+
+    /// `l.index(value[, start[, stop]])`
+    fn find(&self, args: Vec<Value>, _kwargs: HashMap<String, Value>)
+        -> Value {
+        let mut args_iter = args.into_iter();
+        let value = match args_iter.next() {
+            Some(value) => value,
+            None => panic!("TypeError: index expected at least 1 argument, \
+                got 0")
+        };
+        let (start, stop, _) = slice::indices(args_iter.next(),
+            args_iter.next(), None, self.list.len());
+
+        for pos in start..stop {
+            if matches(&self.list[pos as usize], &value) {
+                return Value::Number(NumericType::Integer(pos as i32))
+            }
+        }
+
+        match value {
+            Value::Str(ref s) => panic!("ValueError: '{}' is not in list", s),
+            ref value => panic!("ValueError: {} is not in list", value)
+        }
+    }
+
+    fn count(&self, args: Vec<Value>, _kwargs: HashMap<String, Value>)
+        -> Value {
+        if args.len() != 1 {
+            panic!("TypeError: count() takes exactly one argument ({} given)",
+                args.len())
+        }
+
+        let count = self.list.iter().filter(|e| matches(e, &args[0])).count();
+        Value::Number(NumericType::Integer(count as i32))
+    }
+
+    fn sort_method(&mut self, args: Vec<Value>, kwargs: HashMap<String, Value>)
         -> Value {
-        self.list.pop().expect("IndexError: pop from an empty list")
+        if !args.is_empty() {
+            panic!("TypeError: sort() takes no positional arguments")
+        }
+        let key = match kwargs.get("key") {
+            Some(&Value::None) | None => None,
+            Some(func) => Some(func.clone())
+        };
+        let reverse = match kwargs.get("reverse") {
+            Some(value) => value.to_bool(),
+            None => false
+        };
+
+        self.sort(key, reverse);
+        Value::None
+    }
+
+    // Resolves a possibly negative index, panicking with an IndexError that
+    // carries `msg` when it falls outside the list.
+    fn position(&self, index: &Value, msg: &str) -> usize {
+        let pos = match index.as_index() {
+            Some(pos) => pos as i64,
+            None => panic!("TypeError: list indices must be integers or \
+                slices, not {}", index.type_name())
+        };
+        let len = self.list.len() as i64;
+        let pos = if pos < 0 { pos + len } else { pos };
+
+        if pos < 0 || pos >= len {
+            panic!("IndexError: {}", msg)
+        }
+        pos as usize
     }
 }
 
@@ -228,20 +414,44 @@ impl cmp::PartialEq for ListType {
     }
 }
 
-/// Takes an index and lower/upper bounds and calculates the proper index,
-/// this is necessary since Python allows for negative indexes/slices.
-fn calculate_slice(index: i32, lower: i32, upper: i32) -> i32 {
-    if index < 0 {
-        if index.abs() >= upper {
-            lower
-        } else {
-            upper - index.abs()
-        }
+// Element matching of `count`, `index` and `remove`. Identical values match
+// without comparing them, like in Python, so a list containing itself doesn't
+// recurse.
+fn matches(element: &Value, value: &Value) -> bool {
+    element.is(value) || element == value
+}
+
+// Orders two values using only `<`, which raises a TypeError for values
+// that can't be compared, equal values are left in place by the stable sort.
+fn compare(a: &Value, b: &Value) -> cmp::Ordering {
+    if a < b {
+        cmp::Ordering::Less
+    } else if b < a {
+        cmp::Ordering::Greater
     } else {
-        if index >= upper{
-            upper
-        } else {
-            index
-        }
+        cmp::Ordering::Equal
+    }
+}
+
+impl cmp::PartialOrd for ListType {
+    fn partial_cmp(&self, other: &ListType) -> Option<cmp::Ordering> {
+        slice::compare(&self.list, &other.list, Value::partial_cmp,
+            usize::partial_cmp)
+    }
+
+    fn lt(&self, other: &ListType) -> bool {
+        slice::compare(&self.list, &other.list, Value::lt, usize::lt)
+    }
+
+    fn le(&self, other: &ListType) -> bool {
+        slice::compare(&self.list, &other.list, Value::le, usize::le)
+    }
+
+    fn gt(&self, other: &ListType) -> bool {
+        slice::compare(&self.list, &other.list, Value::gt, usize::gt)
+    }
+
+    fn ge(&self, other: &ListType) -> bool {
+        slice::compare(&self.list, &other.list, Value::ge, usize::ge)
     }
 }
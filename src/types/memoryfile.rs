@@ -0,0 +1,255 @@
+use ::Value;
+use super::BytesType;
+use super::bytes;
+
+/// The buffer behind `io.StringIO` and `io.BytesIO`. Positions count
+/// characters for text and bytes for binary buffers.
+#[derive(Debug)]
+pub struct MemoryFileType {
+    buffer: Buffer,
+    pos: usize,
+    closed: bool
+}
+
+#[derive(Debug)]
+enum Buffer {
+    Text(Vec<char>),
+    Bytes(Vec<u8>)
+}
+
+impl MemoryFileType {
+    pub fn new_text(initial: &str) -> MemoryFileType {
+        MemoryFileType {
+            buffer: Buffer::Text(initial.chars().collect()),
+            pos: 0,
+            closed: false
+        }
+    }
+
+    pub fn new_bytes(initial: Vec<u8>) -> MemoryFileType {
+        MemoryFileType { buffer: Buffer::Bytes(initial), pos: 0, closed: false }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        matches!(self.buffer, Buffer::Bytes(_))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Releases the buffer, every later operation raises `ValueError`
+    pub fn close(&mut self) {
+        self.buffer = match self.buffer {
+            Buffer::Text(_) => Buffer::Text(vec![]),
+            Buffer::Bytes(_) => Buffer::Bytes(vec![])
+        };
+        self.closed = true;
+    }
+
+    /// Nothing to write out, only checks that the buffer is open
+    pub fn flush(&self) {
+        self.check_open();
+    }
+
+    /// Reads `size` characters or bytes, everything left when `None`
+    pub fn read(&mut self, size: Option<usize>) -> Value {
+        self.check_open();
+        let start = self.pos.min(self.len());
+        let end = match size {
+            Some(size) => (start + size).min(self.len()),
+            None => self.len()
+        };
+        self.pos = self.pos.max(end);
+        self.wrap(start, end)
+    }
+
+    /// Reads up to and including the next '\n', at most `size` characters
+    /// or bytes. Returns `None` at the end of the buffer.
+    pub fn readline(&mut self, size: Option<usize>) -> Option<Value> {
+        self.check_open();
+        let start = self.pos.min(self.len());
+        let limit = match size {
+            Some(size) => (start + size).min(self.len()),
+            None => self.len()
+        };
+        let newline = match self.buffer {
+            Buffer::Text(ref text) => text[start..limit].iter()
+                .position(|c| *c == '\n'),
+            Buffer::Bytes(ref data) => data[start..limit].iter()
+                .position(|byte| *byte == b'\n')
+        };
+        let end = newline.map_or(limit, |offset| start + offset + 1);
+
+        if start == end {
+            return None
+        }
+        self.pos = end;
+        Some(self.wrap(start, end))
+    }
+
+    /// `write(value)`, a str for text buffers and a bytes-like object for
+    /// binary ones. Writing past the end pads the gap with zeros. Returns
+    /// the number of characters or bytes written.
+    pub fn write_value(&mut self, value: &Value) -> usize {
+        self.check_open();
+        let pos = self.pos;
+        let count = match (&mut self.buffer, value) {
+            (&mut Buffer::Text(ref mut text), &Value::Str(ref string)) => {
+                let chars: Vec<char> = string.chars().collect();
+                overwrite(text, pos, &chars, '\0')
+            },
+            (&mut Buffer::Text(_), value) => panic!("TypeError: string \
+                argument expected, got '{}'", value.type_name()),
+            (&mut Buffer::Bytes(ref mut data), value) => {
+                match bytes::as_bytes(value) {
+                    Some(new) => overwrite(data, pos, &new, 0),
+                    None => panic!("TypeError: a bytes-like object is \
+                        required, not '{}'", value.type_name())
+                }
+            }
+        };
+        self.pos += count;
+        count
+    }
+
+    /// Moves to `offset` from the start, current position or end. Text
+    /// buffers only support zero offsets relative to the last two.
+    pub fn seek(&mut self, offset: i64, whence: i32) -> u64 {
+        self.check_open();
+        let text = !self.is_binary();
+        let base = match whence {
+            0 if offset < 0 => {
+                panic!("ValueError: negative seek value {}", offset)
+            },
+            0 => 0,
+            1 | 2 if text && offset != 0 => {
+                panic!("OSError: Can't do nonzero {}-relative seeks",
+                    if whence == 1 { "cur" } else { "end" })
+            },
+            1 => self.pos as i64,
+            2 => self.len() as i64,
+            _ => panic!("ValueError: invalid whence ({}, should be 0, 1 or 2)",
+                whence)
+        };
+        self.pos = (base + offset).max(0) as usize;
+        self.pos as u64
+    }
+
+    pub fn tell(&self) -> u64 {
+        self.check_open();
+        self.pos as u64
+    }
+
+    /// `truncate(size=None)`, drops everything after `size` or the current
+    /// position, the position itself is unchanged
+    pub fn truncate(&mut self, size: Option<u64>) -> u64 {
+        self.check_open();
+        let size = size.map_or(self.pos, |size| size as usize);
+        match self.buffer {
+            Buffer::Text(ref mut text) => text.truncate(size),
+            Buffer::Bytes(ref mut data) => data.truncate(size)
+        }
+        size as u64
+    }
+
+    /// The whole buffer regardless of the position
+    pub fn getvalue(&self) -> Value {
+        self.check_open();
+        self.wrap(0, self.len())
+    }
+
+    fn len(&self) -> usize {
+        match self.buffer {
+            Buffer::Text(ref text) => text.len(),
+            Buffer::Bytes(ref data) => data.len()
+        }
+    }
+
+    fn wrap(&self, start: usize, end: usize) -> Value {
+        match self.buffer {
+            Buffer::Text(ref text) => {
+                Value::Str(text[start..end].iter().collect())
+            },
+            Buffer::Bytes(ref data) => {
+                Value::Bytes(BytesType::new(data[start..end].to_vec()))
+            }
+        }
+    }
+
+    fn check_open(&self) {
+        if self.closed {
+            panic!("ValueError: I/O operation on closed file.")
+        }
+    }
+}
+
+// Replaces the items from `pos` on with `new`, growing the buffer as needed
+fn overwrite<T: Clone>(buffer: &mut Vec<T>, pos: usize, new: &[T], fill: T)
+    -> usize {
+    if buffer.len() < pos {
+        buffer.resize(pos, fill);
+    }
+    let end = (pos + new.len()).min(buffer.len());
+    buffer.splice(pos..end, new.iter().cloned());
+    new.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::exception;
+
+    fn text(value: &str) -> Value {
+        Value::Str(value.to_string())
+    }
+
+    #[test]
+    fn memory_text_read_write() {
+        let mut buf = MemoryFileType::new_text("héllo\nworld\n");
+
+        assert_eq!(buf.readline(None), Some(text("héllo\n")));
+        assert_eq!(buf.tell(), 6);
+        assert_eq!(buf.read(Some(3)), text("wor"));
+        buf.seek(1, 0);
+        assert_eq!(buf.write_value(&text("EY")), 2);
+        assert_eq!(buf.getvalue(), text("hEYlo\nworld\n"));
+        buf.seek(0, 2);
+        assert_eq!(buf.readline(None), None);
+        buf.seek(14, 0);
+        buf.write_value(&text("!"));
+        assert_eq!(buf.getvalue(), text("hEYlo\nworld\n\0\0!"));
+        assert_eq!(buf.truncate(Some(5)), 5);
+        assert_eq!(buf.read(None), text(""));
+        assert_eq!(buf.getvalue(), text("hEYlo"));
+
+        let error = exception::catch(|| {
+            MemoryFileType::new_text("").seek(1, 1)
+        });
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("OSError: Can't do nonzero cur-relative seeks".to_string()));
+    }
+
+    #[test]
+    fn memory_bytes_read_write() {
+        let mut buf = MemoryFileType::new_bytes(b"ab\ncd".to_vec());
+        let bytes = |data: &[u8]| Value::Bytes(BytesType::new(data.to_vec()));
+
+        assert_eq!(buf.readline(Some(1)), Some(bytes(b"a")));
+        assert_eq!(buf.readline(None), Some(bytes(b"b\n")));
+        assert_eq!(buf.seek(-1, 2), 4);
+        assert_eq!(buf.write_value(&bytes(b"XY")), 2);
+        assert_eq!(buf.getvalue(), bytes(b"ab\ncXY"));
+        assert_eq!(buf.seek(-10, 1), 0);
+
+        let error = exception::catch(|| {
+            MemoryFileType::new_bytes(vec![]).write_value(&text("str"))
+        });
+        assert_eq!(error.err().map(|exc| exc.to_string()), Some("TypeError: \
+            a bytes-like object is required, not 'str'".to_string()));
+        buf.close();
+        let error = exception::catch(|| buf.getvalue());
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("ValueError: I/O operation on closed file.".to_string()));
+    }
+}
@@ -4,5 +4,24 @@ mod numeric;
 pub use self::numeric::NumericType;
 mod tuple;
 pub use self::tuple::TupleType;
+mod file;
+pub use self::file::{FileType, DEFAULT_BUFFER_SIZE};
+mod memoryfile;
+pub use self::memoryfile::MemoryFileType;
 mod iowrapper;
-pub use self::iowrapper::IOWrapper;
+pub use self::iowrapper::{IOWrapper, flush_all, input_line};
+mod iterator;
+pub use self::iterator::IteratorType;
+mod generator;
+pub use self::generator::{GeneratorType, GenSignal, GenState, GenBody};
+mod range;
+pub use self::range::RangeType;
+pub mod bytes;
+pub use self::bytes::BytesType;
+mod slice;
+mod module;
+pub use self::module::ModuleType;
+mod path;
+pub use self::path::PathType;
+pub mod datetime;
+pub use self::datetime::DateTimeType;
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use ::Value;
+
+/// A module's namespace. Every import of a module shares the instance cached
+/// by `::module`, so attributes assigned through one import are seen by all.
+#[derive(Clone)]
+pub struct ModuleType {
+    name: String,
+    tbl: Rc<RefCell<HashMap<String, Value>>>
+}
+
+impl ModuleType {
+    pub fn new(name: &str, mut tbl: HashMap<String, Value>) -> ModuleType {
+        tbl.insert("__name__".to_string(), Value::Str(name.to_string()));
+        ModuleType { name: name.to_string(), tbl: Rc::new(RefCell::new(tbl)) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get(&self, attr: &str) -> Option<Value> {
+        self.tbl.borrow().get(attr).cloned()
+    }
+
+    pub fn get_attr(&self, attr: &str) -> Value {
+        match self.get(attr) {
+            Some(value) => value,
+            None => panic!("AttributeError: module '{}' has no attribute '{}'",
+                self.name, attr)
+        }
+    }
+
+    pub fn set_attr(&self, attr: &str, value: Value) {
+        self.tbl.borrow_mut().insert(attr.to_string(), value);
+    }
+
+    /// The names bound by `from module import *`: the ones listed in
+    /// `__all__` or else every name not starting with an underscore
+    pub fn public_names(&self) -> Vec<String> {
+        if let Some(all) = self.get("__all__") {
+            return all.into_iter().map(|name| match name {
+                Value::Str(name) => name,
+                name => panic!("TypeError: Item in {}.__all__ must be str, \
+                    not {}", self.name, name.type_name())
+            }).collect()
+        }
+        let mut names: Vec<String> = self.tbl.borrow().keys()
+            .filter(|name| !name.starts_with('_')).cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        Rc::as_ptr(&self.tbl) as *const u8
+    }
+}
+
+impl fmt::Display for ModuleType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<module '{}'>", self.name)
+    }
+}
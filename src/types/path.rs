@@ -0,0 +1,501 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::cmp;
+use std::path::Path;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use ::Value;
+use ::builtin::os;
+use super::ListType;
+use super::TupleType;
+use super::BytesType;
+use super::bytes;
+
+/// `pathlib.Path`, a POSIX path. Like `PurePosixPath` the path is kept in
+/// normal form: repeated slashes and `.` components are dropped (`..` is
+/// kept, it can't be resolved without the file system) and the empty path
+/// is `.`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PathType {
+    path: String
+}
+
+impl PathType {
+    pub fn new(path: &str) -> PathType {
+        // POSIX gives two leading slashes a meaning of their own
+        let root = if path.starts_with("//") && !path.starts_with("///") {
+            "//"
+        } else if path.starts_with('/') {
+            "/"
+        } else {
+            ""
+        };
+        let comps: Vec<&str> = path.split('/')
+            .filter(|comp| !comp.is_empty() && *comp != ".").collect();
+        let path = format!("{}{}", root, comps.join("/"));
+        PathType { path: if path.is_empty() { ".".to_string() } else { path } }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// `self / other`, an absolute `other` replaces the path
+    pub fn join(&self, other: &str) -> PathType {
+        if other.starts_with('/') || self.path == "." {
+            PathType::new(other)
+        } else {
+            PathType::new(&format!("{}/{}", self.path, other))
+        }
+    }
+
+    fn root(&self) -> &str {
+        let len = self.path.len() - self.path.trim_start_matches('/').len();
+        &self.path[..len]
+    }
+
+    // The components after the root, empty for `.` and the root itself
+    fn comps(&self) -> Vec<&str> {
+        self.path.split('/')
+            .filter(|comp| !comp.is_empty() && *comp != ".").collect()
+    }
+
+    /// The root followed by each component, `('/', 'usr', 'bin')`
+    pub fn parts(&self) -> Vec<String> {
+        let root = self.root();
+        let mut parts: Vec<String> = if root.is_empty() {
+            vec![]
+        } else {
+            vec![root.to_string()]
+        };
+        parts.extend(self.comps().into_iter().map(|comp| comp.to_string()));
+        parts
+    }
+
+    /// The final component, empty for the root and `.`
+    pub fn name(&self) -> &str {
+        self.comps().last().cloned().unwrap_or("")
+    }
+
+    /// The final component's extension, a leading dot doesn't start one
+    pub fn suffix(&self) -> &str {
+        let name = self.name();
+        match name.rfind('.') {
+            Some(dot) if dot > 0 && dot < name.len() - 1 => &name[dot..],
+            _ => ""
+        }
+    }
+
+    pub fn stem(&self) -> &str {
+        let name = self.name();
+        &name[..name.len() - self.suffix().len()]
+    }
+
+    /// The logical parent, the root and `.` are their own parents
+    pub fn parent(&self) -> PathType {
+        let mut comps = self.comps();
+        if comps.pop().is_none() {
+            return self.clone()
+        }
+        PathType::new(&format!("{}{}", self.root(), comps.join("/")))
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        !self.root().is_empty()
+    }
+
+    /// Properties, read with the attribute syntax, `path.name`
+    pub fn get_attr(&self, attr: &str) -> Value {
+        let string = |value: &str| Value::Str(value.to_string());
+
+        match attr {
+            "name" => string(self.name()),
+            "stem" => string(self.stem()),
+            "suffix" => string(self.suffix()),
+            "suffixes" => {
+                // Every dotted part of the name except a leading one
+                let name = self.name().trim_start_matches('.');
+                let suffixes = if name.ends_with('.') {
+                    vec![]
+                } else {
+                    name.split('.').skip(1)
+                        .map(|suffix| string(&format!(".{}", suffix))).collect()
+                };
+                list(suffixes)
+            },
+            "parent" => Value::Path(self.parent()),
+            "parents" => {
+                let mut parents = vec![];
+                let mut path = self.clone();
+                while path.parent() != path {
+                    path = path.parent();
+                    parents.push(Value::Path(path.clone()));
+                }
+                Value::Tuple(TupleType::new(parents))
+            },
+            "parts" => Value::Tuple(TupleType::new(self.parts().iter()
+                .map(|part| string(part)).collect())),
+            "root" | "anchor" => string(self.root()),
+            _ => panic!("AttributeError: 'PosixPath' object has no attribute \
+                '{}'", attr)
+        }
+    }
+
+    pub fn call(&self, attr: &str, args: Vec<Value>,
+        kwargs: HashMap<String, Value>) -> Value {
+        let arg = |pos: usize, name: &str| -> Option<Value> {
+            match args.get(pos).or_else(|| kwargs.get(name)) {
+                Some(&Value::None) | None => None,
+                Some(value) => Some(value.clone())
+            }
+        };
+        let flag = |pos: usize, name: &str| {
+            arg(pos, name).is_some_and(|value| value.to_bool())
+        };
+        let path = Path::new(&self.path);
+
+        match attr {
+            "__fspath__" | "as_posix" => Value::Str(self.path.clone()),
+            "joinpath" => {
+                Value::Path(args.iter().fold(self.clone(), |path, other| {
+                    path.join(&os::path_arg(other))
+                }))
+            },
+            "with_name" => {
+                let name = string_arg(arg(0, "name"), "with_name");
+                if self.name().is_empty() {
+                    panic!("ValueError: {:?} has an empty name", self)
+                }
+                if name.is_empty() || name.contains('/') || name == "." {
+                    panic!("ValueError: Invalid name '{}'", name)
+                }
+                Value::Path(self.parent().join(&name))
+            },
+            "with_suffix" => {
+                let suffix = string_arg(arg(0, "suffix"), "with_suffix");
+                if !suffix.is_empty() &&
+                    (!suffix.starts_with('.') || suffix == "." || suffix.contains('/')) {
+                    panic!("ValueError: Invalid suffix '{}'", suffix)
+                }
+                if self.name().is_empty() {
+                    panic!("ValueError: {:?} has an empty name", self)
+                }
+                let name = format!("{}{}", self.stem(), suffix);
+                Value::Path(self.parent().join(&name))
+            },
+            "is_absolute" => Value::Bool(self.is_absolute()),
+            "exists" => Value::Bool(path.exists()),
+            "is_file" => Value::Bool(path.is_file()),
+            "is_dir" => Value::Bool(path.is_dir()),
+            "iterdir" => {
+                let mut names: Vec<String> = os::check(fs::read_dir(path),
+                    &self.path).map(|entry| {
+                    os::check(entry, &self.path).file_name().to_string_lossy()
+                        .into_owned()
+                }).collect();
+                names.sort();
+                list(names.iter().map(|name| Value::Path(self.join(name)))
+                    .collect()).iter()
+            },
+            "glob" | "rglob" => {
+                let pattern = string_arg(arg(0, "pattern"), attr);
+                if pattern.is_empty() {
+                    panic!("ValueError: Unacceptable pattern: ''")
+                }
+                if pattern.starts_with('/') {
+                    panic!("NotImplementedError: Non-relative patterns are \
+                        unsupported")
+                }
+                let pattern = if attr == "rglob" {
+                    format!("**/{}", pattern)
+                } else {
+                    pattern
+                };
+                let segments: Vec<&str> = pattern.split('/')
+                    .filter(|segment| !segment.is_empty() && *segment != ".")
+                    .collect();
+                let mut matches = vec![];
+                glob(self, &segments, &mut matches);
+                list(matches.into_iter().map(Value::Path).collect()).iter()
+            },
+            "stat" => {
+                os::stat(vec![Value::Str(self.path.clone())], HashMap::new())
+            },
+            "open" => {
+                let mut args = args;
+                args.insert(0, Value::Str(self.path.clone()));
+                ::builtin::open(args, kwargs)
+            },
+            "read_text" => {
+                let file = ::builtin::open(vec![Value::Str(self.path.clone()),
+                    Value::Str("r".to_string()), Value::None,
+                    arg(0, "encoding").unwrap_or(Value::None),
+                    arg(1, "errors").unwrap_or(Value::None)], HashMap::new());
+                let text = ::call_member(file.clone(), "read", vec![],
+                    HashMap::new());
+                ::call_member(file, "close", vec![], HashMap::new());
+                text
+            },
+            "write_text" => {
+                let data = match arg(0, "data") {
+                    Some(data @ Value::Str(_)) => data,
+                    data => panic!("TypeError: data must be str, not {}",
+                        data.unwrap_or(Value::None).type_name())
+                };
+                let file = ::builtin::open(vec![Value::Str(self.path.clone()),
+                    Value::Str("w".to_string()), Value::None,
+                    arg(1, "encoding").unwrap_or(Value::None),
+                    arg(2, "errors").unwrap_or(Value::None)], HashMap::new());
+                let written = ::call_member(file.clone(), "write", vec![data],
+                    HashMap::new());
+                ::call_member(file, "close", vec![], HashMap::new());
+                written
+            },
+            "read_bytes" => {
+                BytesType::new_bytes(os::check(fs::read(path), &self.path))
+            },
+            "write_bytes" => {
+                let data = arg(0, "data").unwrap_or(Value::None);
+                let data = match bytes::as_bytes(&data) {
+                    Some(data) => data,
+                    None => panic!("TypeError: a bytes-like object is \
+                        required, not '{}'", data.type_name())
+                };
+                os::check(fs::write(path, &data), &self.path);
+                Value::Number(::NumericType::Integer(data.len() as i32))
+            },
+            "mkdir" => {
+                let mode = args.first().or_else(|| kwargs.get("mode"));
+                self.mkdir(mode, flag(1, "parents"), flag(2, "exist_ok"));
+                Value::None
+            },
+            "unlink" => {
+                os::check(fs::remove_file(path), &self.path);
+                Value::None
+            },
+            "rmdir" => {
+                os::check(fs::remove_dir(path), &self.path);
+                Value::None
+            },
+            "rename" => {
+                let target = os::path_arg(&arg(0, "target").unwrap_or(Value::None));
+                os::check(fs::rename(path, &target), &self.path);
+                Value::Path(PathType::new(&target))
+            },
+            "resolve" => Value::Path(self.resolve(flag(0, "strict"))),
+            _ => panic!("AttributeError: 'PosixPath' object has no attribute \
+                '{}'", attr)
+        }
+    }
+
+    /// `mkdir(mode=0o777, parents=False, exist_ok=False)`, missing parents
+    /// are created with the default mode like in Python
+    pub fn mkdir(&self, mode: Option<&Value>, parents: bool, exist_ok: bool) {
+        let result = match os::create_dir(&self.path, mode, false) {
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound && parents => {
+                self.parent().mkdir(None, true, true);
+                os::create_dir(&self.path, mode, false)
+            },
+            result => result
+        };
+        match result {
+            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists &&
+                exist_ok && Path::new(&self.path).is_dir() => (),
+            result => os::check(result, &self.path)
+        }
+    }
+
+    /// The absolute path with symlinks and `..` resolved. Unless `strict`,
+    /// a missing tail is appended to the resolved part that exists.
+    pub fn resolve(&self, strict: bool) -> PathType {
+        let absolute = os::absolute_path(&self.path);
+        match fs::canonicalize(&absolute) {
+            Ok(path) => PathType::new(&path.display().to_string()),
+            Err(err) => {
+                if strict {
+                    os::check(Err::<(), _>(err), &self.path);
+                }
+                let absolute = PathType::new(&absolute);
+                let parent = absolute.parent();
+                if parent == absolute {
+                    absolute
+                } else {
+                    parent.resolve(false).join(absolute.name())
+                }
+            }
+        }
+    }
+}
+
+// Appends the paths below `dir` matching the pattern's remaining segments,
+// `**` matches the directory itself and every directory below it
+fn glob(dir: &PathType, segments: &[&str], matches: &mut Vec<PathType>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return matches.push(dir.clone())
+    };
+    let mut names: Vec<(String, bool)> = match fs::read_dir(&dir.path) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| {
+            (entry.file_name().to_string_lossy().into_owned(),
+                entry.path().is_dir())
+        }).collect(),
+        Err(_) => return
+    };
+    names.sort();
+
+    if *segment == "**" {
+        glob(dir, rest, matches);
+        for (name, is_dir) in names {
+            if is_dir {
+                glob(&dir.join(&name), segments, matches);
+            }
+        }
+    } else if *segment == ".." {
+        glob(&dir.join(".."), rest, matches);
+    } else {
+        for (name, is_dir) in names {
+            if (rest.is_empty() || is_dir) && fnmatch(&name, segment) {
+                glob(&dir.join(&name), rest, matches);
+            }
+        }
+    }
+}
+
+/// Shell-style matching of a whole name: `*`, `?`, `[seq]` and `[!seq]`
+pub fn fnmatch(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut n, mut p) = (0, 0);
+    // Where to retry from when the characters after a `*` stop matching
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+                continue
+            },
+            Some('?') => Some(1),
+            Some('[') => match_class(&pattern[p..], name[n]),
+            Some(&c) if c == name[n] => Some(1),
+            _ => None
+        };
+        match (step, star) {
+            (Some(step), _) => {
+                p += step;
+                n += 1;
+            },
+            (None, Some((star_p, star_n))) => {
+                star = Some((star_p, star_n + 1));
+                p = star_p + 1;
+                n = star_n + 1;
+            },
+            (None, None) => return false
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// Matches `c` against the class at the start of `pattern`, returning the
+// class' length. An unterminated `[` is a literal.
+fn match_class(pattern: &[char], c: char) -> Option<usize> {
+    let negated = pattern.get(1) == Some(&'!');
+    let start = if negated { 2 } else { 1 };
+    // A `]` right after the opening bracket is part of the class
+    let end = match pattern.iter().skip(start + 1).position(|&c| c == ']') {
+        Some(idx) => start + 1 + idx,
+        None => return if c == '[' { Some(1) } else { None }
+    };
+
+    let class = &pattern[start..end];
+    let mut found = false;
+    let mut idx = 0;
+    while idx < class.len() {
+        if idx + 2 < class.len() && class[idx + 1] == '-' {
+            found |= class[idx] <= c && c <= class[idx + 2];
+            idx += 3;
+        } else {
+            found |= class[idx] == c;
+            idx += 1;
+        }
+    }
+    if found != negated { Some(end + 1) } else { None }
+}
+
+fn string_arg(value: Option<Value>, name: &str) -> String {
+    match value {
+        Some(Value::Str(string)) => string,
+        Some(value) => panic!("TypeError: {}() argument must be str, not {}",
+            name, value.type_name()),
+        None => panic!("TypeError: {}() missing 1 required positional \
+            argument", name)
+    }
+}
+
+fn list(values: Vec<Value>) -> Value {
+    Value::List(Rc::new(RefCell::new(ListType::new(values))))
+}
+
+// Paths order by their components, `a/b` sorts before `a-b`
+impl cmp::PartialOrd for PathType {
+    fn partial_cmp(&self, other: &PathType) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl cmp::Ord for PathType {
+    fn cmp(&self, other: &PathType) -> cmp::Ordering {
+        self.parts().cmp(&other.parts())
+    }
+}
+
+impl fmt::Display for PathType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
+
+impl fmt::Debug for PathType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PosixPath('{}')", self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_pure_components() {
+        let path = PathType::new("/usr//lib/./python3.6/site.tar.gz");
+
+        assert_eq!(path.as_str(), "/usr/lib/python3.6/site.tar.gz");
+        assert_eq!(path.name(), "site.tar.gz");
+        assert_eq!(path.suffix(), ".gz");
+        assert_eq!(path.stem(), "site.tar");
+        assert_eq!(path.parent().as_str(), "/usr/lib/python3.6");
+        assert_eq!(path.parts(), vec!["/", "usr", "lib", "python3.6",
+            "site.tar.gz"]);
+        assert_eq!(PathType::new("").as_str(), ".");
+        assert_eq!(PathType::new("a/../b").as_str(), "a/../b");
+        assert_eq!(PathType::new(".bashrc").suffix(), "");
+        assert_eq!(PathType::new("/").parent().as_str(), "/");
+        assert_eq!(PathType::new("a").join("/b").as_str(), "/b");
+        assert_eq!(PathType::new("a/b") < PathType::new("a-b"), true);
+    }
+
+    #[test]
+    fn path_fnmatch() {
+        assert_eq!(fnmatch("main.rs", "*.rs"), true);
+        assert_eq!(fnmatch("main.rs", "m??n.*"), true);
+        assert_eq!(fnmatch("main.rs", "*.py"), false);
+        assert_eq!(fnmatch("a1", "a[0-9]"), true);
+        assert_eq!(fnmatch("ab", "a[!b]"), false);
+        assert_eq!(fnmatch("a]", "a[]]"), true);
+        assert_eq!(fnmatch("abcbd", "*b*d"), true);
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::cmp;
+
+use ::Value;
+use super::NumericType;
+use super::slice;
+
+/// Python's immutable `range` sequence. Only the bounds are stored, the
+/// length, membership and indexing are all computed arithmetically. The stop
+/// is wider than an int, reversing or slicing a range can move it one step
+/// past `i32::MIN` or `i32::MAX`.
+#[derive(Debug, Clone, Copy)]
+pub struct RangeType {
+    start: i32,
+    stop: i64,
+    step: i32
+}
+
+impl RangeType {
+    pub fn new(start: i32, stop: i32, step: i32) -> RangeType {
+        if step == 0 {
+            panic!("ValueError: range() arg 3 must not be zero")
+        }
+        RangeType { start, stop: stop as i64, step }
+    }
+
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    pub fn stop(&self) -> i64 {
+        self.stop
+    }
+
+    pub fn step(&self) -> i32 {
+        self.step
+    }
+
+    /// Number of elements in the range, computed in O(1)
+    pub fn length(&self) -> usize {
+        let (start, stop, step) =
+            (self.start as i64, self.stop, self.step as i64);
+
+        if step > 0 && start < stop {
+            ((stop - start - 1) / step + 1) as usize
+        } else if step < 0 && start > stop {
+            ((start - stop - 1) / -step + 1) as usize
+        } else {
+            0
+        }
+    }
+
+    pub fn to_bool(&self) -> bool {
+        self.length() != 0
+    }
+
+    /// Returns the length of the range
+    pub fn len(&self) -> Value {
+        Value::Number(NumericType::Integer(int(self.length() as i64)))
+    }
+
+    /// Returns the element at `pos` without bounds panics, used by iterators
+    pub fn get(&self, pos: usize) -> Option<Value> {
+        if pos < self.length() {
+            let value = self.start as i64 + pos as i64 * self.step as i64;
+            Some(Value::Number(NumericType::Integer(value as i32)))
+        } else {
+            None
+        }
+    }
+
+    pub fn index(&self, index: Value) -> Value {
+        let pos = match index.as_index() {
+            Some(pos) => pos as i64,
+            None => panic!("TypeError: range indices must be integers or \
+                slices, not {}", index.type_name())
+        };
+        let len = self.length() as i64;
+        let pos = if pos < 0 { pos + len } else { pos };
+
+        if pos < 0 || pos >= len {
+            panic!("IndexError: range object index out of range")
+        }
+        self.get(pos as usize).unwrap()
+    }
+
+    /// O(1) membership test, floats are members when they equal an element
+    pub fn contains(&self, value: &Value) -> bool {
+        let value = match *value {
+            Value::Number(NumericType::Integer(val)) => val as i64,
+            Value::Bool(val) => val as i64,
+            Value::Number(NumericType::Float(val)) => {
+                if val.fract() != 0.0 || !val.is_finite() {
+                    return false
+                }
+                val as i64
+            },
+            _ => return false
+        };
+        let (start, step) = (self.start as i64, self.step as i64);
+        let in_bounds = if step > 0 {
+            value >= start && value < self.stop
+        } else {
+            value <= start && value > self.stop
+        };
+
+        in_bounds && (value - start) % step == 0
+    }
+
+    /// Slicing a range produces another range covering the selected elements
+    pub fn slice(&self, lower: Option<Value>, upper: Option<Value>,
+        step: Option<Value>) -> Value {
+        let (lower, upper, by) = slice::indices(lower, upper, step,
+            self.length());
+        let (start, own_step) = (self.start as i64, self.step as i64);
+        let (first, stop) = (start + lower * own_step, start + upper * own_step);
+        let step = int(own_step * by);
+
+        // An empty slice may start past the ends of an int
+        if (by > 0 && lower >= upper) || (by < 0 && lower <= upper) {
+            let first = first.clamp(i32::MIN as i64, i32::MAX as i64);
+            return Value::Range(RangeType { start: first as i32, stop: first,
+                step })
+        }
+
+        Value::Range(RangeType { start: first as i32, stop, step })
+    }
+
+    /// The same elements in the opposite order, used by `reversed()`
+    pub fn reversed(&self) -> RangeType {
+        let len = self.length() as i64;
+        if len == 0 {
+            return RangeType { start: self.start, stop: self.start as i64,
+                step: 1 }
+        }
+        let (start, step) = (self.start as i64, self.step as i64);
+        let last = start + (len - 1) * step;
+
+        RangeType { start: last as i32, stop: start - step, step: int(-step) }
+    }
+}
+
+// Bounds are computed in i64, ranges whose start or step don't fit back into
+// an int can't be represented
+fn int(value: i64) -> i32 {
+    if value < i32::MIN as i64 || value > i32::MAX as i64 {
+        panic!("OverflowError: Python int too large to convert to C long")
+    }
+    value as i32
+}
+
+impl fmt::Display for RangeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.step == 1 {
+            write!(f, "range({}, {})", self.start, self.stop)
+        } else {
+            write!(f, "range({}, {}, {})", self.start, self.stop, self.step)
+        }
+    }
+}
+
+// Ranges compare equal when they describe the same sequence of values, e.g.
+// range(0) == range(2, 1) and range(0, 3, 5) == range(0, 1).
+impl cmp::PartialEq for RangeType {
+    fn eq(&self, other: &RangeType) -> bool {
+        let len = self.length();
+
+        if len != other.length() {
+            false
+        } else if len == 0 {
+            true
+        } else if self.start != other.start {
+            false
+        } else {
+            len == 1 || self.step == other.step
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use ::Value;
+
+/// Resolves slice bounds against a sequence of length `len` the same way as
+/// Python's `slice.indices()`: missing bounds default based on the direction
+/// of `step` and out of range bounds are clamped. Returns (start, stop, step).
+pub fn indices(lower: Option<Value>, upper: Option<Value>, step: Option<Value>,
+    len: usize) -> (i64, i64, i64) {
+    let step = match step {
+        Some(Value::None) | None => 1,
+        Some(step) => match step.as_index() {
+            Some(0) => panic!("ValueError: slice step cannot be zero"),
+            Some(step) => step as i64,
+            None => panic!("TypeError: slice indices must be integers or None")
+        }
+    };
+    let len = len as i64;
+    let (lower_bound, upper_bound) = if step < 0 {
+        (-1, len - 1)
+    } else {
+        (0, len)
+    };
+    let clamp = |bound: Option<Value>, default: i64| -> i64 {
+        match bound {
+            Some(Value::None) | None => default,
+            Some(bound) => match bound.as_index() {
+                Some(idx) if idx < 0 => (idx as i64 + len).max(lower_bound),
+                Some(idx) => (idx as i64).min(upper_bound),
+                None => {
+                    panic!("TypeError: slice indices must be integers or None")
+                }
+            }
+        }
+    };
+
+    if step < 0 {
+        (clamp(lower, upper_bound), clamp(upper, lower_bound), step)
+    } else {
+        (clamp(lower, lower_bound), clamp(upper, upper_bound), step)
+    }
+}
+
+/// Expands resolved slice indices into the positions they select, in order
+pub fn positions(start: i64, stop: i64, step: i64) -> Vec<usize> {
+    let mut positions = vec![];
+    let mut pos = start;
+
+    while (step > 0 && pos < stop) || (step < 0 && pos > stop) {
+        positions.push(pos as usize);
+        pos += step;
+    }
+    positions
+}
+
+/// Orders sequences by their first differing elements, the comparison
+/// operator itself is applied to that pair so unorderable elements raise a
+/// TypeError naming the right operator. Without a difference the lengths are
+/// compared, the shorter sequence is the smaller one.
+pub fn compare<T>(seq1: &[Value], seq2: &[Value], elements: fn(&Value, &Value) -> T,
+    lengths: fn(&usize, &usize) -> T) -> T {
+    match seq1.iter().zip(seq2).find(|&(val1, val2)| val1 != val2) {
+        Some((val1, val2)) => elements(val1, val2),
+        None => lengths(&seq1.len(), &seq2.len())
+    }
+}
@@ -1,17 +1,21 @@
 use std::fmt;
 use std::cmp;
+use std::rc::Rc;
 
 use ::Value;
 use super::NumericType;
+use super::slice;
 
+// Tuples are immutable so the elements are shared between clones, this keeps
+// passing tuples around (and iterating them) from copying every element.
 #[derive(Debug, Clone)]
 pub struct TupleType {
-    list: Vec<Value>
+    list: Rc<Vec<Value>>
 }
 
 impl TupleType {
     pub fn new(list: Vec<Value>) -> TupleType {
-        TupleType { list }
+        TupleType { list: Rc::new(list) }
     }
 
     pub fn to_bool(&self) -> bool {
@@ -19,22 +23,18 @@ impl TupleType {
     }
 
     pub fn index(&self, index: Value) -> Value {
-        let pos = match index {
-            Value::Number(NumericType::Integer(pos)) => pos,
-            _ => panic!("list indices must be integers or slices")
+        let pos = match index.as_index() {
+            Some(pos) => pos as i64,
+            None => panic!("TypeError: tuple indices must be integers or \
+                slices, not {}", index.type_name())
         };
+        let len = self.list.len() as i64;
+        let pos = if pos < 0 { pos + len } else { pos };
 
-        if pos.abs() as usize >= self.list.len() {
-            panic!("list index out of range")
+        if pos < 0 || pos >= len {
+            panic!("IndexError: tuple index out of range")
         }
-
-        let pos: usize = if pos < 0 {
-            self.list.len() - pos.abs() as usize
-        } else {
-            pos as usize
-        };
-
-        self.list[pos].clone()
+        self.list[pos as usize].clone()
     }
 
     pub fn contains(&self, value: &Value) -> bool {
@@ -46,22 +46,9 @@ impl TupleType {
         Value::Number(NumericType::Integer(self.list.len() as i32))
     }
 
-    /// Returns minimum element in sequence, will fail if elements in sequence
-    /// vary in type.
-    pub fn min(&self) -> Value {
-        if self.list.is_empty() {
-            panic!("min() arg is an empty sequence")
-        }
-        let mut iter = self.list.iter();
-        let mut min_val = iter.next().unwrap();
-
-        for value in iter {
-            if value < min_val {
-                min_val = value
-            }
-        }
-
-        min_val.clone()
+    /// Returns the length as a usize for use within the runtime
+    pub fn length(&self) -> usize {
+        self.list.len()
     }
 
     /// Replicates Python3's slicing feature
@@ -78,78 +65,26 @@ impl TupleType {
     /// reference: https://stackoverflow.com/a/509295
     pub fn slice(&self, lower: Option<Value>, upper: Option<Value>,
         step: Option<Value>) -> Value {
-        let step = match step {
-            Some(Value::Number(NumericType::Integer(step))) => {
-                if step == 0 {
-                    panic!("slice step cannot be zero")
-                } else {
-                    step
-                }
-            },
-            Some(Value::None) => 1,
-            None => 1,
-            _ => panic!("slice indices must be integers or None")
-        };
-        let list = if step < 0 {
-            let lower = match lower {
-                Some(Value::Number(NumericType::Integer(lower))) =>
-                    calculate_slice(lower, 0, self.list.len() as i32),
-                None => self.list.len() as i32,
-                _ => panic!("slice indices must be integers or None")
-            };
-            let upper = match upper {
-                Some(Value::Number(NumericType::Integer(upper))) =>
-                    calculate_slice(upper, -1, self.list.len() as i32),
-                None => -1,
-                _ => panic!("slice indices must be integers or None")
-            };
-
-            if lower > upper {
-                let temp = lower;
-                let lower = (upper + 1) as usize;
-                let upper = if temp == self.list.len() as i32 { temp as usize }
-                    else { (temp + 1) as usize };
-
-                let list: Vec<Value> = (&self.list[lower..upper])
-                    .iter().map(|val| val.clone()).collect();
-
-                list.iter().rev().enumerate()
-                    .filter(|elem| elem.0 % (step.abs() as usize) == 0)
-                    .map(|elem| elem.1.clone())
-                    .collect()
-            } else {
-                vec![]
-            }
-        } else {
-            let lower = match lower {
-                Some(Value::Number(NumericType::Integer(lower))) =>
-                    calculate_slice(lower, 0, self.list.len() as i32) as usize,
-                None => 0,
-                _ => panic!("slice indices must be integers or None")
-            };
-            let upper = match upper {
-                Some(Value::Number(NumericType::Integer(upper))) =>
-                    calculate_slice(upper, 0, self.list.len() as i32) as usize,
-                None => self.list.len(),
-                _ => panic!("slice indices must be integers or None")
-            };
-
-            if lower >= upper {
-                vec![]
-            } else {
-                (&self.list[lower..upper]).iter()
-                    .enumerate()
-                    .filter(|elem| elem.0 % (step as usize) == 0)
-                    .map(|elem| elem.1.clone())
-                    .collect()
-            }
-        };
+        let (start, stop, step) = slice::indices(lower, upper, step,
+            self.list.len());
+        let list: Vec<Value> = slice::positions(start, stop, step).into_iter()
+            .map(|pos| self.list[pos].clone()).collect();
 
         Value::Tuple(TupleType::new(list))
     }
 
     pub fn clone_seq(&self) -> Vec<Value> {
-        self.list.clone()
+        self.list.to_vec()
+    }
+
+    /// Address of the shared elements, clones of a tuple share it
+    pub fn as_ptr(&self) -> *const u8 {
+        Rc::as_ptr(&self.list) as *const u8
+    }
+
+    /// Returns the element at `pos` without bounds panics, used by iterators
+    pub fn get(&self, pos: usize) -> Option<Value> {
+        self.list.get(pos).cloned()
     }
 }
 
@@ -204,20 +139,25 @@ impl cmp::PartialEq for TupleType {
     }
 }
 
-/// Takes an index and lower/upper bounds and calculates the proper index,
-/// this is necessary since Python allows for negative indexes/slices.
-fn calculate_slice(index: i32, lower: i32, upper: i32) -> i32 {
-    if index < 0 {
-        if index.abs() >= upper {
-            lower
-        } else {
-            upper - index.abs()
-        }
-    } else {
-        if index >= upper{
-            upper
-        } else {
-            index
-        }
+impl cmp::PartialOrd for TupleType {
+    fn partial_cmp(&self, other: &TupleType) -> Option<cmp::Ordering> {
+        slice::compare(&self.list, &other.list, Value::partial_cmp,
+            usize::partial_cmp)
+    }
+
+    fn lt(&self, other: &TupleType) -> bool {
+        slice::compare(&self.list, &other.list, Value::lt, usize::lt)
+    }
+
+    fn le(&self, other: &TupleType) -> bool {
+        slice::compare(&self.list, &other.list, Value::le, usize::le)
+    }
+
+    fn gt(&self, other: &TupleType) -> bool {
+        slice::compare(&self.list, &other.list, Value::gt, usize::gt)
+    }
+
+    fn ge(&self, other: &TupleType) -> bool {
+        slice::compare(&self.list, &other.list, Value::ge, usize::ge)
     }
 }
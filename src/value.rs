@@ -1,14 +1,45 @@
 use std::ops;
 use std::cmp;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 use super::NumericType;
 use super::ListType;
 use super::TupleType;
+use super::RangeType;
+use super::BytesType;
+use super::types::bytes;
 use super::IOWrapper;
+use super::IteratorType;
+use super::GeneratorType;
+use super::ModuleType;
+use super::PathType;
+use super::DateTimeType;
+use super::types::datetime;
+use super::exception;
+
+/// What makes two values the same object for `is` and `id()`. Heap values
+/// are identified by the address of their shared allocation. Immutable values
+/// are copied rather than shared, so they are identified by the value itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Identity {
+    Address(usize),
+    None,
+    Bool(bool),
+    Int(i32),
+    Str(String),
+    // The `repr()` of the remaining immutable values, which includes the type
+    Repr(String),
+    EmptyTuple,
+    Class(String),
+    Stdin,
+    Stdout,
+    Stderr
+}
 
 #[derive(Clone)]
 pub enum Value {
@@ -17,11 +48,19 @@ pub enum Value {
     Bool(bool),
     List(Rc<RefCell<ListType>>),
     Tuple(TupleType),
+    Range(RangeType),
+    Bytes(BytesType),
+    ByteArray(Rc<RefCell<BytesType>>),
     Function(Rc<Fn(Vec<Value>, HashMap<String, Value>) -> Value>),
     // Class definitions are immutable in Cannoli
     Class { tbl: HashMap<String, Value> },
     Object { tbl: Rc<RefCell<HashMap<String, Value>>> },
+    Module(ModuleType),
     TextIOWrapper(IOWrapper),
+    Iterator(Rc<RefCell<IteratorType>>),
+    Generator(GeneratorType),
+    Path(PathType),
+    DateTime(DateTimeType),
     None
 }
 
@@ -33,14 +72,50 @@ impl Value {
             Value::Bool(ref val) => *val,
             Value::List(ref list) => list.borrow().to_bool(),
             Value::Tuple(ref tup) => tup.to_bool(),
+            Value::Range(ref range) => range.to_bool(),
+            Value::Bytes(ref bytes) => bytes.to_bool(),
+            Value::ByteArray(ref bytes) => bytes.borrow().to_bool(),
             Value::Function(_) => true,
             Value::Class { .. } => true,
             Value::Object { .. } => true,
+            Value::Module(_) => true,
+            Value::Path(_) => true,
+            Value::DateTime(ref dt) => dt.to_bool(),
+            Value::Iterator(_) => true,
+            Value::Generator(_) => true,
             Value::None => false,
             _ => unimplemented!()
         }
     }
 
+    /// The integer value of ints and bools, `bool` being a subtype of `int`
+    /// lets `True`/`False` be used as indices, counts and bounds.
+    pub fn as_index(&self) -> Option<i32> {
+        match *self {
+            Value::Number(NumericType::Integer(val)) => Some(val),
+            Value::Bool(val) => Some(val as i32),
+            _ => None
+        }
+    }
+
+    /// Hash used by `hash()`, ints hash to themselves and values that compare
+    /// equal (e.g. `True`, `1` and `1.0`) always hash the same.
+    pub fn hash_value(&self) -> i32 {
+        match *self {
+            Value::Number(NumericType::Integer(val)) => val,
+            Value::Bool(val) => val as i32,
+            Value::Number(NumericType::Float(val))
+                if val.fract() == 0.0 && val.abs() < i32::MAX as f32 => {
+                val as i32
+            },
+            _ => {
+                let mut hasher = DefaultHasher::new();
+                self.hash(&mut hasher);
+                hasher.finish() as i32
+            }
+        }
+    }
+
     // Logical NOT provided as a translation for Python's `not` keyword. Rust
     // provides one overload NOT and cannolib has used that as Bitwise NOT.
     // This function will always return a Value::Bool.
@@ -53,6 +128,13 @@ impl Value {
         match *self {
             Value::List(ref list) => list.borrow().index(index),
             Value::Tuple(ref tup) => tup.index(index),
+            Value::Range(ref range) => range.index(index),
+            Value::Bytes(ref bytes) => bytes.index(index),
+            Value::ByteArray(ref bytes) => bytes.borrow().index(index),
+            Value::Object { .. } => {
+                ::call_member(self.clone(), "__getitem__", vec![index],
+                    HashMap::new())
+            },
             _ => panic!("value not subscriptable")
         }
     }
@@ -63,14 +145,118 @@ impl Value {
         match *self {
             Value::List(ref list) => list.borrow().slice(lower, upper, step),
             Value::Tuple(ref tup) => tup.slice(lower, upper, step),
+            Value::Range(ref range) => range.slice(lower, upper, step),
+            Value::Bytes(ref bytes) => {
+                BytesType::new_bytes(bytes.slice(lower, upper, step))
+            },
+            Value::ByteArray(ref bytes) => {
+                BytesType::new_bytearray(bytes.borrow().slice(lower, upper, step))
+            },
             _ => panic!("value not subscriptable")
         }
     }
 
+    /// Item assignment, `value[index] = item`
+    pub fn set_index(&self, index: Value, item: Value) {
+        match *self {
+            Value::List(ref list) => list.borrow_mut().set_index(index, item),
+            Value::ByteArray(ref bytes) => {
+                bytes.borrow_mut().set_index(index, item)
+            },
+            Value::Object { .. } => {
+                ::call_member(self.clone(), "__setitem__", vec![index, item],
+                    HashMap::new());
+            },
+            _ => panic!("TypeError: '{}' object does not support item \
+                assignment", self.type_name())
+        }
+    }
+
+    /// Slice assignment, `value[lower:upper:step] = iterable`
+    pub fn set_slice(&self, lower: Option<Value>, upper: Option<Value>,
+        step: Option<Value>, iterable: Value) {
+        match *self {
+            Value::List(ref list) => {
+                // Collected first since the iterable may be the list itself
+                let values = iterable.clone_seq();
+                list.borrow_mut().set_slice(lower, upper, step, values)
+            },
+            _ => panic!("TypeError: '{}' object does not support item \
+                assignment", self.type_name())
+        }
+    }
+
+    /// `del value[index]`
+    pub fn del_index(&self, index: Value) {
+        match *self {
+            Value::List(ref list) => list.borrow_mut().del_index(index),
+            Value::Object { .. } => {
+                ::call_member(self.clone(), "__delitem__", vec![index],
+                    HashMap::new());
+            },
+            _ => panic!("TypeError: '{}' object doesn't support item deletion",
+                self.type_name())
+        }
+    }
+
+    /// `del value[lower:upper:step]`
+    pub fn del_slice(&self, lower: Option<Value>, upper: Option<Value>,
+        step: Option<Value>) {
+        match *self {
+            Value::List(ref list) => {
+                list.borrow_mut().del_slice(lower, upper, step)
+            },
+            _ => panic!("TypeError: '{}' object doesn't support item deletion",
+                self.type_name())
+        }
+    }
+
+    /// In-place addition, `self += other`. Lists are extended in place so
+    /// every alias of the list observes the change, immutable values produce
+    /// a new value. The result must be assigned back to the target name.
+    pub fn iadd(&self, other: Value) -> Value {
+        match *self {
+            Value::List(ref list) => {
+                // Collected first since `other` may be the list itself
+                let values = other.clone_seq();
+                list.borrow_mut().extend_values(values);
+                self.clone()
+            },
+            Value::ByteArray(ref array) => {
+                match bytes::as_bytes(&other) {
+                    Some(other) => array.borrow_mut().extend_bytes(&other),
+                    None => panic!("TypeError: can't concat {} to bytearray",
+                        other.type_name())
+                }
+                self.clone()
+            },
+            _ => self.clone() + other
+        }
+    }
+
+    /// In-place repetition, `self *= other`, mutates lists like `iadd`
+    pub fn imul(&self, other: Value) -> Value {
+        match *self {
+            Value::List(ref list) => {
+                let count = repeat_count(&other);
+                list.borrow_mut().repeat(count);
+                self.clone()
+            },
+            Value::ByteArray(ref array) => {
+                let repeated = array.borrow().repeat(repeat_count(&other));
+                *array.borrow_mut() = BytesType::new(repeated);
+                self.clone()
+            },
+            _ => self.clone() * other
+        }
+    }
+
     pub fn pow(&self, value: &Value) -> Value {
         match (self, value) {
             (&Value::Number(ref val1), &Value::Number(ref val2)) =>
                 Value::Number(val1.pow(val2)),
+            (&Value::Bool(_), _) | (_, &Value::Bool(_)) =>
+                promote_bool(self.clone()).pow(&promote_bool(value.clone())),
             _ => panic!("pow() unsupported for specified values")
         }
     }
@@ -80,6 +266,9 @@ impl Value {
         match *iterable {
             Value::List(ref list) => list.borrow().contains(self),
             Value::Tuple(ref tup) => tup.contains(self),
+            Value::Range(ref range) => range.contains(self),
+            Value::Bytes(ref bytes) => bytes.contains(self),
+            Value::ByteArray(ref bytes) => bytes.borrow().contains(self),
             Value::Str(ref s) => {
                 let substr = match *self {
                     Value::Str(ref substr) => substr,
@@ -87,6 +276,13 @@ impl Value {
                 };
                 s.contains(substr)
             },
+            // Objects without `__contains__` are searched by iterating
+            Value::Object { ref tbl } if tbl.borrow().contains_key("__contains__") => {
+                ::call_member(iterable.clone(), "__contains__",
+                    vec![self.clone()], HashMap::new()).to_bool()
+            },
+            Value::Object { .. } => iterable.iter().into_iter()
+                .any(|item| item == *self),
             _ => panic!("value is not iterable")
         }
     }
@@ -94,27 +290,235 @@ impl Value {
     /// Providing this method because the compiled output is a little
     /// complicated and negating 'contained_in' is a bit more complicated
     pub fn not_contained_in(&self, iterable: &Value) -> bool {
-        match *iterable {
-            Value::List(ref list) => !(list.borrow().contains(self)),
-            Value::Tuple(ref tup) => !(tup.contains(self)),
-            Value::Str(ref s) => {
-                let substr = match *self {
-                    Value::Str(ref substr) => substr,
-                    _ => panic!("in '<string>' string required as left operand")
-                };
-                !(s.contains(substr))
-            },
-            _ => panic!("value is not iterable")
-        }
+        !self.contained_in(iterable)
     }
 
-    /// Clones the inner sequence of tuples and lists, this is to iterate
-    /// lists and tuples in a for-loop
+    /// Clones the inner sequence of tuples and lists, any other iterable is
+    /// consumed through the iterator protocol and collected.
     pub fn clone_seq(&self) -> Vec<Value> {
         match *self {
             Value::List(ref list) => list.borrow().clone_seq(),
             Value::Tuple(ref tup) => tup.clone_seq(),
-            _ => panic!("value not iterable")
+            _ => self.iter().into_iter().collect()
+        }
+    }
+
+    /// Python's `iter()`, returns a lazy Value::Iterator over the value. The
+    /// iterator references the original list/tuple instead of copying it.
+    pub fn iter(&self) -> Value {
+        let iter = match *self {
+            Value::List(ref list) => IteratorType::new_list(list.clone()),
+            Value::Tuple(ref tup) => IteratorType::new_tuple(tup.clone()),
+            Value::Range(ref range) => IteratorType::new_range(*range),
+            Value::Str(ref string) => IteratorType::new_str(string.clone()),
+            Value::Bytes(ref bytes) => {
+                IteratorType::Bytes { bytes: bytes.clone(), pos: 0 }
+            },
+            Value::ByteArray(ref bytes) => {
+                IteratorType::ByteArray { bytes: bytes.clone(), pos: 0 }
+            },
+            Value::TextIOWrapper(ref iow) => IteratorType::File(iow.clone()),
+            Value::Iterator(_) | Value::Generator(_) => return self.clone(),
+            Value::Object { ref tbl } => {
+                if !tbl.borrow().contains_key("__iter__") {
+                    panic!("TypeError: '{}' object is not iterable",
+                        self.type_name())
+                }
+                return ::call_member(self.clone(), "__iter__", vec![],
+                    HashMap::new())
+            },
+            _ => panic!("TypeError: '{}' object is not iterable",
+                self.type_name())
+        };
+
+        Value::Iterator(Rc::new(RefCell::new(iter)))
+    }
+
+    /// Advances an iterator (Python's `__next__`), returning `None` once the
+    /// iterator raises `StopIteration`. User objects must define `__next__`.
+    pub fn next(&self) -> Option<Value> {
+        match *self {
+            Value::Iterator(ref iter) => iter.borrow_mut().next(),
+            Value::Generator(ref gen) => gen.send(Value::None),
+            Value::Object { ref tbl } => {
+                if !tbl.borrow().contains_key("__next__") {
+                    panic!("TypeError: '{}' object is not an iterator",
+                        self.type_name())
+                }
+                let result = exception::catch(|| {
+                    ::call_member(self.clone(), "__next__", vec![],
+                        HashMap::new())
+                });
+
+                match result {
+                    Ok(value) => Some(value),
+                    Err(ref exc) if exc.is("StopIteration") => None,
+                    Err(exc) => exc.raise()
+                }
+            },
+            _ => panic!("TypeError: '{}' object is not an iterator",
+                self.type_name())
+        }
+    }
+
+    /// Python's `is` operator, true when both values are the same object
+    pub fn is(&self, other: &Value) -> bool {
+        match (self.identity(), other.identity()) {
+            (Some(id1), Some(id2)) => id1 == id2,
+            _ => false
+        }
+    }
+
+    /// Python's `is not` operator
+    pub fn is_not(&self, other: &Value) -> bool {
+        !self.is(other)
+    }
+
+    // `==` for objects: the left operand's `__eq__`, then the right one's.
+    // Without either they are equal only to themselves.
+    fn object_eq(&self, other: &Value) -> bool {
+        let has_eq = |value: &Value| match *value {
+            Value::Object { ref tbl } => tbl.borrow().contains_key("__eq__"),
+            _ => false
+        };
+        if has_eq(self) {
+            ::call_member(self.clone(), "__eq__", vec![other.clone()],
+                HashMap::new()).to_bool()
+        } else if has_eq(other) {
+            ::call_member(other.clone(), "__eq__", vec![self.clone()],
+                HashMap::new()).to_bool()
+        } else {
+            self.is(other)
+        }
+    }
+
+    /// Python's `id()`, an integer that stays the same for the lifetime of
+    /// the object. It's derived from the identity, ints are 32-bit so distinct
+    /// objects can share an id in the rare case their identities collide.
+    pub fn id(&self) -> i32 {
+        let identity = self.identity()
+            .unwrap_or_else(|| Identity::Repr(format!("{:?}", self)));
+        let id = match identity {
+            // Allocations are at least 8-byte aligned
+            Identity::Address(address) => (address >> 3) as u64,
+            identity => {
+                let mut hasher = DefaultHasher::new();
+                identity.hash(&mut hasher);
+                hasher.finish()
+            }
+        };
+        (id & 0x7fff_ffff) as i32
+    }
+
+    fn identity(&self) -> Option<Identity> {
+        let address = |ptr: *const u8| Some(Identity::Address(ptr as usize));
+
+        match *self {
+            Value::List(ref list) => address(Rc::as_ptr(list) as *const u8),
+            Value::ByteArray(ref bytes) => {
+                address(Rc::as_ptr(bytes) as *const u8)
+            },
+            Value::Object { ref tbl } => address(Rc::as_ptr(tbl) as *const u8),
+            Value::Function(ref func) => address(Rc::as_ptr(func) as *const u8),
+            Value::Iterator(ref iter) => address(Rc::as_ptr(iter) as *const u8),
+            Value::Generator(ref gen) => address(gen.as_ptr()),
+            Value::Module(ref module) => address(module.as_ptr()),
+            Value::Tuple(ref tup) => {
+                if tup.length() == 0 {
+                    Some(Identity::EmptyTuple)
+                } else {
+                    address(tup.as_ptr())
+                }
+            },
+            Value::TextIOWrapper(ref iow) => match *iow {
+                IOWrapper::File(ref file) => {
+                    address(Rc::as_ptr(file) as *const u8)
+                },
+                IOWrapper::Memory(ref buf) => {
+                    address(Rc::as_ptr(buf) as *const u8)
+                },
+                IOWrapper::Stdin => Some(Identity::Stdin),
+                IOWrapper::Stdout => Some(Identity::Stdout),
+                IOWrapper::Stderr => Some(Identity::Stderr),
+                IOWrapper::Closed => None
+            },
+            // Class tables are copied rather than shared, but a class name is
+            // only ever bound to one definition
+            Value::Class { ref tbl } => {
+                tbl.get("__name__").map(|name| Identity::Class(name.to_string()))
+            },
+            Value::None => Some(Identity::None),
+            Value::Bool(val) => Some(Identity::Bool(val)),
+            Value::Number(NumericType::Integer(val)) => Some(Identity::Int(val)),
+            Value::Str(ref string) => Some(Identity::Str(string.clone())),
+            Value::Number(NumericType::Float(_)) | Value::Range(_) |
+            Value::Bytes(_) | Value::Path(_) | Value::DateTime(_) => {
+                Some(Identity::Repr(format!("{:?}", self)))
+            }
+        }
+    }
+
+    /// Orders two scalar values, `None` means they are unordered (NaN). `op`
+    /// is only used for the TypeError raised for values without an ordering.
+    fn compare(&self, other: &Value, op: &str) -> Option<cmp::Ordering> {
+        match (self, other) {
+            (&Value::Number(ref val1), &Value::Number(ref val2)) => {
+                val1.partial_cmp(val2)
+            },
+            (&Value::Number(ref val1), &Value::Bool(val2)) => {
+                val1.partial_cmp(&NumericType::Integer(val2 as i32))
+            },
+            (&Value::Bool(val1), &Value::Number(ref val2)) => {
+                NumericType::Integer(val1 as i32).partial_cmp(val2)
+            },
+            (&Value::Str(ref val1), &Value::Str(ref val2)) => {
+                val1.partial_cmp(val2)
+            },
+            (&Value::Path(ref val1), &Value::Path(ref val2)) => {
+                val1.partial_cmp(val2)
+            },
+            (&Value::DateTime(ref val1), &Value::DateTime(ref val2)) => {
+                Some(val1.compare(val2, op))
+            },
+            (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
+                val1.partial_cmp(val2)
+            },
+            (&Value::Bytes(_), &Value::Bytes(_)) |
+            (&Value::Bytes(_), &Value::ByteArray(_)) |
+            (&Value::ByteArray(_), &Value::Bytes(_)) |
+            (&Value::ByteArray(_), &Value::ByteArray(_)) => {
+                bytes::as_bytes(self).partial_cmp(&bytes::as_bytes(other))
+            },
+            _ => panic!("TypeError: '{}' not supported between instances of \
+                '{}' and '{}'", op, self.type_name(), other.type_name())
+        }
+    }
+
+    /// Returns the Python type name of the value, used in error messages
+    pub fn type_name(&self) -> String {
+        match *self {
+            Value::Number(NumericType::Integer(_)) => "int".to_string(),
+            Value::Number(NumericType::Float(_)) => "float".to_string(),
+            Value::Str(_) => "str".to_string(),
+            Value::Bool(_) => "bool".to_string(),
+            Value::List(_) => "list".to_string(),
+            Value::Tuple(_) => "tuple".to_string(),
+            Value::Range(_) => "range".to_string(),
+            Value::Bytes(_) => "bytes".to_string(),
+            Value::ByteArray(_) => "bytearray".to_string(),
+            Value::Function(_) => "function".to_string(),
+            Value::Class { .. } => "type".to_string(),
+            Value::Object { ref tbl } => match tbl.borrow().get("__name__") {
+                Some(name) => name.to_string(),
+                None => "object".to_string()
+            },
+            Value::TextIOWrapper(ref iow) => iow.type_name().to_string(),
+            Value::Iterator(ref iter) => iter.borrow().type_name().to_string(),
+            Value::Generator(_) => "generator".to_string(),
+            Value::Module(_) => "module".to_string(),
+            Value::Path(_) => "PosixPath".to_string(),
+            Value::DateTime(ref dt) => dt.type_name().to_string(),
+            Value::None => "NoneType".to_string()
         }
     }
 
@@ -123,6 +527,11 @@ impl Value {
         -> Value {
         match *self {
             Value::Function(ref f) => f(args, kwargs),
+            // Native classes such as `bytes` construct their instances
+            // directly from the arguments
+            Value::Class { ref tbl } if tbl.contains_key("__new__") => {
+                tbl["__new__"].call(args, kwargs)
+            },
             Value::Class { ref tbl } => {
                 let obj = Value::Object {
                     tbl: Rc::new(RefCell::new(tbl.clone()))
@@ -157,6 +566,10 @@ impl Value {
                     panic!(format!("class has no attribute '{}'", attr))
                 }
             },
+            Value::Module(ref module) => module.get_attr(attr),
+            Value::TextIOWrapper(ref iow) => iow.get_attr(attr),
+            Value::Path(ref path) => path.get_attr(attr),
+            Value::DateTime(ref dt) => dt.get_attr(attr),
             _ => unreachable!()
         }
     }
@@ -176,6 +589,11 @@ impl fmt::Debug for Value {
             },
             Value::List(ref list) => write!(f, "{}", list.borrow()),
             Value::Tuple(ref tup) => write!(f, "{}", tup),
+            Value::Range(ref range) => write!(f, "{}", range),
+            Value::Bytes(ref bytes) => write!(f, "{}", bytes),
+            Value::ByteArray(ref bytes) => {
+                write!(f, "bytearray({})", bytes.borrow())
+            },
             Value::Function(_) => write!(f, "<cannoli function>"),
             Value::Object { ref tbl } => {
                 if let Some(value) = tbl.borrow().get("__name__") {
@@ -192,6 +610,14 @@ impl fmt::Debug for Value {
                 }
             },
             Value::TextIOWrapper(_) => write!(f, "TextIOWrapper"),
+            Value::Iterator(ref iter) => {
+                write!(f, "<{} object at {:p}>", iter.borrow().type_name(),
+                    iter)
+            },
+            Value::Generator(ref gen) => write!(f, "{}", gen),
+            Value::Module(ref module) => write!(f, "{}", module),
+            Value::Path(ref path) => write!(f, "{:?}", path),
+            Value::DateTime(ref dt) => write!(f, "{:?}", dt),
             Value::None => write!(f, "None"),
         }
     }
@@ -211,6 +637,11 @@ impl fmt::Display for Value {
             },
             Value::List(ref list) => write!(f, "{}", list.borrow()),
             Value::Tuple(ref tup) => write!(f, "{}", tup),
+            Value::Range(ref range) => write!(f, "{}", range),
+            Value::Bytes(ref bytes) => write!(f, "{}", bytes),
+            Value::ByteArray(ref bytes) => {
+                write!(f, "bytearray({})", bytes.borrow())
+            },
             Value::Function(_) => write!(f, "<cannoli function>"),
             Value::Object { ref tbl } => {
                 if let Some(value) = tbl.borrow().get("__name__") {
@@ -227,6 +658,14 @@ impl fmt::Display for Value {
                 }
             },
             Value::TextIOWrapper(_) => write!(f, "TextIOWrapper"),
+            Value::Iterator(ref iter) => {
+                write!(f, "<{} object at {:p}>", iter.borrow().type_name(),
+                    iter)
+            },
+            Value::Generator(ref gen) => write!(f, "{}", gen),
+            Value::Module(ref module) => write!(f, "{}", module),
+            Value::Path(ref path) => write!(f, "{}", path),
+            Value::DateTime(ref dt) => write!(f, "{}", dt),
             Value::None => write!(f, "None"),
         }
     }
@@ -236,17 +675,30 @@ impl fmt::Display for Value {
 impl cmp::PartialEq for Value {
     fn eq(&self, other: &Value) -> bool {
         match (self, other) {
+            (&Value::Object { .. }, _) | (_, &Value::Object { .. }) => {
+                self.object_eq(other)
+            },
             (&Value::Number(ref val1), &Value::Number(ref val2)) => {
                 val1 == val2
             },
+            (&Value::Number(ref val1), &Value::Bool(val2)) => {
+                *val1 == NumericType::Integer(val2 as i32)
+            },
             (&Value::Number(_), _) => false,
             (&Value::Str(ref val1), &Value::Str(ref val2)) => {
                 val1 == val2
             },
             (&Value::Str(_), _) => false,
+            (&Value::Path(ref path1), &Value::Path(ref path2)) => path1 == path2,
+            (&Value::Path(_), _) => false,
+            (&Value::DateTime(ref dt1), &Value::DateTime(ref dt2)) => dt1.equals(dt2),
+            (&Value::DateTime(_), _) => false,
             (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
                 val1 == val2
             },
+            (&Value::Bool(val1), &Value::Number(ref val2)) => {
+                NumericType::Integer(val1 as i32) == *val2
+            },
             (&Value::Bool(_), _) => false,
             (&Value::List(ref lst1), &Value::List(ref lst2)) => {
                 *lst1.borrow() == *lst2.borrow()
@@ -256,25 +708,61 @@ impl cmp::PartialEq for Value {
                 *tup1 == *tup2
             },
             (&Value::Tuple(_), _) => false,
+            (&Value::Range(ref range1), &Value::Range(ref range2)) => {
+                *range1 == *range2
+            },
+            (&Value::Range(_), _) => false,
+            (&Value::Bytes(_), _) | (&Value::ByteArray(_), _) => {
+                match bytes::as_bytes(other) {
+                    Some(other) => bytes::as_bytes(self) == Some(other),
+                    None => false
+                }
+            },
+            (&Value::Iterator(ref iter1), &Value::Iterator(ref iter2)) => {
+                Rc::ptr_eq(iter1, iter2)
+            },
+            (&Value::Iterator(_), _) => false,
+            (&Value::Generator(ref gen1), &Value::Generator(ref gen2)) => {
+                gen1.ptr_eq(gen2)
+            },
+            (&Value::Generator(_), _) => false,
             (&Value::None, &Value::None) => true,
             (&Value::None, _) => false,
-            _ => unimplemented!()
+            // Functions, classes and modules are equal only to themselves
+            _ => self.is(other)
         }
     }
 
     fn ne(&self, other: &Value) -> bool {
         match (self, other) {
+            (&Value::Object { ref tbl }, _) if tbl.borrow().contains_key("__ne__") => {
+                ::call_member(self.clone(), "__ne__", vec![other.clone()],
+                    HashMap::new()).to_bool()
+            },
+            (&Value::Object { .. }, _) | (_, &Value::Object { .. }) => {
+                !self.object_eq(other)
+            },
             (&Value::Number(ref val1), &Value::Number(ref val2)) => {
                 val1 != val2
             },
+            (&Value::Number(ref val1), &Value::Bool(val2)) => {
+                *val1 != NumericType::Integer(val2 as i32)
+            },
             (&Value::Number(_), _) => true,
             (&Value::Str(ref val1), &Value::Str(ref val2)) => {
                 val1 != val2
             },
             (&Value::Str(_), _) => true,
+            (&Value::Path(ref path1), &Value::Path(ref path2)) => path1 != path2,
+            (&Value::Path(_), _) => true,
+            (&Value::DateTime(ref dt1), &Value::DateTime(ref dt2)) => !dt1.equals(dt2),
+            (&Value::DateTime(_), _) => true,
             (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
                 val1 != val2
             },
+            (&Value::Bool(val1), &Value::Number(ref val2)) => {
+                NumericType::Integer(val1 as i32) != *val2
+            },
             (&Value::Bool(_), _) => true,
             (&Value::List(ref lst1), &Value::List(ref lst2)) => {
                 *lst1.borrow() != *lst2.borrow()
@@ -284,90 +772,163 @@ impl cmp::PartialEq for Value {
                 *tup1 != *tup2
             },
             (&Value::Tuple(_), _) => true,
+            (&Value::Range(ref range1), &Value::Range(ref range2)) => {
+                *range1 != *range2
+            },
+            (&Value::Range(_), _) => true,
+            (&Value::Bytes(_), _) | (&Value::ByteArray(_), _) => {
+                match bytes::as_bytes(other) {
+                    Some(other) => bytes::as_bytes(self) != Some(other),
+                    None => true
+                }
+            },
+            (&Value::Iterator(ref iter1), &Value::Iterator(ref iter2)) => {
+                !Rc::ptr_eq(iter1, iter2)
+            },
+            (&Value::Iterator(_), _) => true,
+            (&Value::Generator(ref gen1), &Value::Generator(ref gen2)) => {
+                !gen1.ptr_eq(gen2)
+            },
+            (&Value::Generator(_), _) => true,
             (&Value::None, &Value::None) => false,
             (&Value::None, _) => true,
-            _ => unimplemented!()
+            _ => !self.is(other)
         }
     }
 }
 
+// Consistent with `==`: equal numbers hash the same regardless of whether
+// they're ints, floats or bools. Values without a value comparison hash by
+// identity, mutable lists are unhashable like in Python.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Value::Number(NumericType::Integer(val)) => (val as i64).hash(state),
+            Value::Bool(val) => (val as i64).hash(state),
+            Value::Number(NumericType::Float(val)) => {
+                if val.fract() == 0.0 && val.is_finite() {
+                    (val as i64).hash(state)
+                } else {
+                    val.to_bits().hash(state)
+                }
+            },
+            Value::Str(ref val) => val.hash(state),
+            Value::Path(ref path) => path.hash(state),
+            Value::DateTime(ref dt) => dt.hash(state),
+            Value::Tuple(ref tup) => {
+                for value in tup.clone_seq() {
+                    value.hash(state);
+                }
+            },
+            Value::Range(ref range) => {
+                let len = range.length();
+                len.hash(state);
+                if len > 0 {
+                    range.start().hash(state);
+                }
+                if len > 1 {
+                    range.step().hash(state);
+                }
+            },
+            Value::Bytes(ref bytes) => bytes.hash(state),
+            Value::List(_) | Value::ByteArray(_) => {
+                panic!("TypeError: unhashable type: '{}'", self.type_name())
+            },
+            _ => self.identity().hash(state)
+        }
+    }
+}
+
+// Lists and tuples are compared lexicographically by their own PartialOrd
+// impls, everything else goes through `Value::compare`. Comparing values of
+// types that have no ordering raises a TypeError, just like Python.
 impl cmp::PartialOrd for Value {
     fn partial_cmp(&self, other: &Value) -> Option<cmp::Ordering> {
         match (self, other) {
-            (&Value::Number(ref val1), &Value::Number(ref val2)) => {
-                val1.partial_cmp(val2)
-            },
-            (&Value::Str(ref val1), &Value::Str(ref val2)) => {
-                val1.partial_cmp(val2)
+            (&Value::List(ref lst1), &Value::List(ref lst2)) => {
+                lst1.borrow().partial_cmp(&*lst2.borrow())
             },
-            (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
-                val1.partial_cmp(val2)
+            (&Value::Tuple(ref tup1), &Value::Tuple(ref tup2)) => {
+                tup1.partial_cmp(tup2)
             },
-            _ => unimplemented!()
+            _ => self.compare(other, "<")
         }
     }
 
     fn lt(&self, other: &Value) -> bool {
         match (self, other) {
-            (&Value::Number(ref val1), &Value::Number(ref val2)) => {
-                val1 < val2
-            },
-            (&Value::Str(ref val1), &Value::Str(ref val2)) => {
-                val1 < val2
+            (&Value::List(ref lst1), &Value::List(ref lst2)) => {
+                *lst1.borrow() < *lst2.borrow()
             },
-            (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
-                val1 < val2
+            (&Value::Tuple(ref tup1), &Value::Tuple(ref tup2)) => {
+                *tup1 < *tup2
             },
-            _ => panic!("operation '<' not supported between these values")
+            _ => self.compare(other, "<") == Some(cmp::Ordering::Less)
         }
     }
 
     fn le(&self, other: &Value) -> bool {
         match (self, other) {
-            (&Value::Number(ref val1), &Value::Number(ref val2)) => {
-                val1 <= val2
-            },
-            (&Value::Str(ref val1), &Value::Str(ref val2)) => {
-                val1 <= val2
+            (&Value::List(ref lst1), &Value::List(ref lst2)) => {
+                *lst1.borrow() <= *lst2.borrow()
             },
-            (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
-                val1 <= val2
+            (&Value::Tuple(ref tup1), &Value::Tuple(ref tup2)) => {
+                *tup1 <= *tup2
             },
-            _ => panic!("operation '<=' not supported between these values")
+            _ => matches!(self.compare(other, "<="),
+                Some(cmp::Ordering::Less) | Some(cmp::Ordering::Equal))
         }
     }
 
     fn gt(&self, other: &Value) -> bool {
         match (self, other) {
-            (&Value::Number(ref val1), &Value::Number(ref val2)) => {
-                val1 > val2
-            },
-            (&Value::Str(ref val1), &Value::Str(ref val2)) => {
-                val1 > val2
+            (&Value::List(ref lst1), &Value::List(ref lst2)) => {
+                *lst1.borrow() > *lst2.borrow()
             },
-            (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
-                val1 > val2
+            (&Value::Tuple(ref tup1), &Value::Tuple(ref tup2)) => {
+                *tup1 > *tup2
             },
-            _ => panic!("operation '>' not supported between these values")
+            _ => self.compare(other, ">") == Some(cmp::Ordering::Greater)
         }
     }
 
     fn ge(&self, other: &Value) -> bool {
         match (self, other) {
-            (&Value::Number(ref val1), &Value::Number(ref val2)) => {
-                val1 >= val2
-            },
-            (&Value::Str(ref val1), &Value::Str(ref val2)) => {
-                val1 >= val2
+            (&Value::List(ref lst1), &Value::List(ref lst2)) => {
+                *lst1.borrow() >= *lst2.borrow()
             },
-            (&Value::Bool(ref val1), &Value::Bool(ref val2)) => {
-                val1 >= val2
+            (&Value::Tuple(ref tup1), &Value::Tuple(ref tup2)) => {
+                *tup1 >= *tup2
             },
-            _ => panic!("operation '>=' not supported between these values")
+            _ => matches!(self.compare(other, ">="),
+                Some(cmp::Ordering::Greater) | Some(cmp::Ordering::Equal))
         }
     }
 }
 
+/// Adapts a Python iterator to a Rust iterator so the compiled output can
+/// lower `for x in value:` to `for x in value { ... }`.
+pub struct ValueIter {
+    iter: Value
+}
+
+impl Iterator for ValueIter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        self.iter.next()
+    }
+}
+
+impl IntoIterator for Value {
+    type Item = Value;
+    type IntoIter = ValueIter;
+
+    fn into_iter(self) -> ValueIter {
+        ValueIter { iter: self.iter() }
+    }
+}
+
 impl ops::Add for Value {
     type Output = Value;
 
@@ -376,14 +937,55 @@ impl ops::Add for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs + rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) + promote_bool(rhs)
+            },
             (Value::Str(lhs), Value::Str(rhs)) => {
                 Value::Str(lhs + &rhs)
             },
+            (Value::List(lhs), Value::List(rhs)) => {
+                let mut list = lhs.borrow().clone_seq();
+                list.extend(rhs.borrow().clone_seq());
+                Value::List(Rc::new(RefCell::new(ListType::new(list))))
+            },
+            (Value::Tuple(lhs), Value::Tuple(rhs)) => {
+                let mut list = lhs.clone_seq();
+                list.extend(rhs.clone_seq());
+                Value::Tuple(TupleType::new(list))
+            },
+            (lhs @ Value::Bytes(_), rhs) | (lhs @ Value::ByteArray(_), rhs) => {
+                let mut joined = bytes::as_bytes(&lhs).unwrap();
+                match bytes::as_bytes(&rhs) {
+                    Some(rhs) => joined.extend(rhs),
+                    None => panic!("TypeError: can't concat {} to {}",
+                        rhs.type_name(), lhs.type_name())
+                }
+                match lhs {
+                    Value::Bytes(_) => BytesType::new_bytes(joined),
+                    _ => BytesType::new_bytearray(joined)
+                }
+            },
+            (lhs @ Value::DateTime(_), rhs) | (lhs, rhs @ Value::DateTime(_)) => {
+                datetime::binary("+", &lhs, &rhs)
+            },
+            (lhs @ Value::Str(_), rhs) | (lhs @ Value::List(_), rhs) |
+            (lhs @ Value::Tuple(_), rhs) => {
+                panic!("TypeError: can only concatenate {} (not \"{}\") to {}",
+                    lhs.type_name(), rhs.type_name(), lhs.type_name())
+            },
             _ => unimplemented!()
         }
     }
 }
 
+impl ops::AddAssign for Value {
+    fn add_assign(&mut self, other: Value) {
+        *self = self.iadd(other);
+    }
+}
+
 impl ops::BitAnd for Value {
     type Output = Value;
 
@@ -392,6 +994,11 @@ impl ops::BitAnd for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs & rhs)
             },
+            (Value::Bool(lhs), Value::Bool(rhs)) => Value::Bool(lhs & rhs),
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) & promote_bool(rhs)
+            },
             _ => panic!("Bitwise AND applies to Value::Number")
         }
     }
@@ -405,6 +1012,11 @@ impl ops::BitOr for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs | rhs)
             },
+            (Value::Bool(lhs), Value::Bool(rhs)) => Value::Bool(lhs | rhs),
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) | promote_bool(rhs)
+            },
             _ => panic!("Bitwise OR applies to Value::Number")
         }
     }
@@ -418,6 +1030,11 @@ impl ops::BitXor for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs ^ rhs)
             },
+            (Value::Bool(lhs), Value::Bool(rhs)) => Value::Bool(lhs ^ rhs),
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) ^ promote_bool(rhs)
+            },
             _ => panic!("Bitwise XOR applies to Value::Number")
         }
     }
@@ -431,6 +1048,26 @@ impl ops::Div for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs / rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) / promote_bool(rhs)
+            },
+            // `pathlib` joins paths with `/`
+            (Value::Path(lhs), Value::Str(rhs)) => Value::Path(lhs.join(&rhs)),
+            (Value::Path(lhs), Value::Path(rhs)) => {
+                Value::Path(lhs.join(rhs.as_str()))
+            },
+            (Value::Str(lhs), Value::Path(rhs)) => {
+                Value::Path(PathType::new(&lhs).join(rhs.as_str()))
+            },
+            (lhs @ Value::Path(_), rhs) | (lhs, rhs @ Value::Path(_)) => {
+                panic!("TypeError: unsupported operand type(s) for /: '{}' \
+                    and '{}'", lhs.type_name(), rhs.type_name())
+            },
+            (lhs @ Value::DateTime(_), rhs) | (lhs, rhs @ Value::DateTime(_)) => {
+                datetime::binary("/", &lhs, &rhs)
+            },
             _ => unimplemented!()
         }
     }
@@ -444,11 +1081,72 @@ impl ops::Mul for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs * rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) * promote_bool(rhs)
+            },
+            (lhs @ Value::DateTime(_), rhs) | (lhs, rhs @ Value::DateTime(_)) => {
+                datetime::binary("*", &lhs, &rhs)
+            },
+            (seq @ Value::Str(_), count) | (count, seq @ Value::Str(_)) |
+            (seq @ Value::List(_), count) | (count, seq @ Value::List(_)) |
+            (seq @ Value::Tuple(_), count) | (count, seq @ Value::Tuple(_)) |
+            (seq @ Value::Bytes(_), count) | (count, seq @ Value::Bytes(_)) |
+            (seq @ Value::ByteArray(_), count) |
+            (count, seq @ Value::ByteArray(_)) => {
+                let count = repeat_count(&count);
+                match seq {
+                    Value::Str(string) => Value::Str(string.repeat(count)),
+                    Value::List(list) => {
+                        let mut list = list.borrow().clone();
+                        list.repeat(count);
+                        Value::List(Rc::new(RefCell::new(list)))
+                    },
+                    Value::Tuple(tup) => {
+                        let list = tup.clone_seq();
+                        let len = list.len();
+                        Value::Tuple(TupleType::new(list.into_iter()
+                            .cycle().take(len * count).collect()))
+                    },
+                    Value::Bytes(bytes) => {
+                        BytesType::new_bytes(bytes.repeat(count))
+                    },
+                    Value::ByteArray(bytes) => {
+                        BytesType::new_bytearray(bytes.borrow().repeat(count))
+                    },
+                    _ => unreachable!()
+                }
+            },
             _ => unimplemented!()
         }
     }
 }
 
+impl ops::MulAssign for Value {
+    fn mul_assign(&mut self, other: Value) {
+        *self = self.imul(other);
+    }
+}
+
+// Number of times a sequence is repeated by `*`, negative counts produce an
+// empty sequence like in Python.
+fn repeat_count(count: &Value) -> usize {
+    match count.as_index() {
+        Some(count) => count.max(0) as usize,
+        None => panic!("TypeError: can't multiply sequence by non-int of type \
+            '{}'", count.type_name())
+    }
+}
+
+// `bool` is a subtype of `int`, arithmetic on bools works on their int value
+fn promote_bool(value: Value) -> Value {
+    match value {
+        Value::Bool(val) => Value::Number(NumericType::Integer(val as i32)),
+        value => value
+    }
+}
+
 impl ops::Neg for Value {
     type Output = Value;
 
@@ -457,6 +1155,7 @@ impl ops::Neg for Value {
             Value::Number(val) => Value::Number(-val),
             Value::Bool(val) =>
                 Value::Number(NumericType::Integer(-(val as i32))),
+            Value::DateTime(ref dt) => dt.neg(),
             _ => panic!("bad operand type for unary -"),
         }
     }
@@ -484,6 +1183,14 @@ impl ops::Rem for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs % rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) % promote_bool(rhs)
+            },
+            (lhs @ Value::DateTime(_), rhs) | (lhs, rhs @ Value::DateTime(_)) => {
+                datetime::binary("%", &lhs, &rhs)
+            },
             _ => unimplemented!()
         }
     }
@@ -497,6 +1204,11 @@ impl ops::Shl<Value> for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs << rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) << promote_bool(rhs)
+            },
             _ => unimplemented!()
         }
     }
@@ -510,6 +1222,11 @@ impl ops::Shr<Value> for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs >> rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) >> promote_bool(rhs)
+            },
             _ => unimplemented!()
         }
     }
@@ -523,6 +1240,14 @@ impl ops::Sub for Value {
             (Value::Number(lhs), Value::Number(rhs)) => {
                 Value::Number(lhs - rhs)
             },
+            (lhs @ Value::Bool(_), rhs @ Value::Number(_)) |
+            (lhs @ Value::Number(_), rhs @ Value::Bool(_)) |
+            (lhs @ Value::Bool(_), rhs @ Value::Bool(_)) => {
+                promote_bool(lhs) - promote_bool(rhs)
+            },
+            (lhs @ Value::DateTime(_), rhs) | (lhs, rhs @ Value::DateTime(_)) => {
+                datetime::binary("-", &lhs, &rhs)
+            },
             _ => unimplemented!()
         }
     }
@@ -531,6 +1256,7 @@ impl ops::Sub for Value {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ::{call_member, attr_assign};
 
     #[test]
     fn self_to_bool_number() {
@@ -833,4 +1559,823 @@ mod tests {
         assert_eq!(x.clone() - y, Value::Number(NumericType::Integer(-1)));
         assert_eq!(x.clone() - z, Value::Number(NumericType::Float(3.0)));
     }
+
+    #[test]
+    fn iter_value_list() {
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            Value::Number(NumericType::Integer(1)),
+            Value::Number(NumericType::Integer(2))
+        ]))));
+        let iter = list.iter();
+
+        assert_eq!(iter.next(), Some(Value::Number(NumericType::Integer(1))));
+        call_member(list.clone(), "append",
+            vec![Value::Number(NumericType::Integer(3))], HashMap::new());
+        assert_eq!(iter.next(), Some(Value::Number(NumericType::Integer(2))));
+        assert_eq!(iter.next(), Some(Value::Number(NumericType::Integer(3))));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.iter() == iter, true);
+    }
+
+    #[test]
+    fn iter_value_str_tuple() {
+        let string = Value::Str("aé".to_string());
+        let tup = Value::Tuple(TupleType::new(vec![Value::Bool(true)]));
+
+        assert_eq!(string.clone_seq(), vec![Value::Str("a".to_string()),
+            Value::Str("é".to_string())]);
+        assert_eq!(tup.into_iter().collect::<Vec<Value>>(),
+            vec![Value::Bool(true)]);
+    }
+
+    #[test]
+    fn iter_value_object() {
+        let mut tbl = HashMap::new();
+        tbl.insert("__name__".to_string(), Value::Str("Count".to_string()));
+        tbl.insert("n".to_string(), Value::Number(NumericType::Integer(0)));
+        tbl.insert("__iter__".to_string(), Value::Function(Rc::new(
+            |args: Vec<Value>, _| args[0].clone())));
+        tbl.insert("__next__".to_string(), Value::Function(Rc::new(
+            |args: Vec<Value>, _| {
+                let n = args[0].get_attr("n");
+                if n == Value::Number(NumericType::Integer(2)) {
+                    panic!("StopIteration")
+                }
+                attr_assign(args[0].clone(), "n",
+                    n.clone() + Value::Number(NumericType::Integer(1)));
+                n
+            })));
+        let obj = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+
+        assert_eq!(obj.clone_seq(), vec![
+            Value::Number(NumericType::Integer(0)),
+            Value::Number(NumericType::Integer(1))
+        ]);
+        assert_eq!(obj.next(), None);
+    }
+
+    #[test]
+    fn object_getitem_len() {
+        let mut tbl = HashMap::new();
+        tbl.insert("__getitem__".to_string(), Value::Function(Rc::new(
+            |args: Vec<Value>, _| args[1].clone() * args[1].clone())));
+        tbl.insert("__len__".to_string(), Value::Function(Rc::new(
+            |_, _| Value::Number(NumericType::Integer(3)))));
+        let obj = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+
+        assert_eq!(obj.index(Value::Number(NumericType::Integer(4))),
+            Value::Number(NumericType::Integer(16)));
+        assert_eq!(::builtin::len(vec![obj], HashMap::new()),
+            Value::Number(NumericType::Integer(3)));
+    }
+
+    #[test]
+    fn range_value_sequence() {
+        let range = Value::Range(RangeType::new(10, 0, -3));
+        let int = |val| Value::Number(NumericType::Integer(val));
+
+        assert_eq!(range.clone_seq(), vec![int(10), int(7), int(4), int(1)]);
+        assert_eq!(::builtin::len(vec![range.clone()], HashMap::new()), int(4));
+        assert_eq!(range.index(int(-1)), int(1));
+        assert_eq!(int(4).contained_in(&range), true);
+        assert_eq!(int(5).contained_in(&range), false);
+        assert_eq!(Value::Number(NumericType::Float(7.0)).contained_in(&range),
+            true);
+        assert_eq!(format!("{}", range), "range(10, 0, -3)");
+    }
+
+    #[test]
+    fn range_value_slice_eq() {
+        let range = Value::Range(RangeType::new(0, 10, 1));
+        let int = |val| Value::Number(NumericType::Integer(val));
+
+        assert_eq!(range.slice(Some(int(2)), None, Some(int(3))),
+            Value::Range(RangeType::new(2, 10, 3)));
+        assert_eq!(range.slice(None, None, Some(int(-1))).clone_seq(),
+            Value::Range(RangeType::new(0, 10, 1).reversed()).clone_seq());
+        assert_eq!(Value::Range(RangeType::new(0, 3, 5)),
+            Value::Range(RangeType::new(0, 1, 1)));
+        assert_eq!(Value::Range(RangeType::new(5, 1, 1)),
+            Value::Range(RangeType::new(0, 0, 2)));
+
+        let wide = RangeType::new(i32::MIN, i32::MAX, 1);
+        let name = |exc: exception::Exception| exc.name().to_string();
+        assert_eq!(exception::catch(|| wide.len()).err().map(name),
+            Some("OverflowError".to_string()));
+        assert_eq!(wide.reversed().get(0),
+            Some(Value::Number(NumericType::Integer(i32::MAX - 1))));
+        let negative = RangeType::new(i32::MIN, 0, 1);
+        assert_eq!(negative.reversed().get(negative.length() - 1),
+            Some(Value::Number(NumericType::Integer(i32::MIN))));
+        assert_eq!(negative.reversed().to_string(), "range(-1, -2147483649, -1)");
+        let odd = Value::Range(negative).slice(None, None, Some(int(-2)));
+        assert_eq!(odd.index(int(-1)), int(i32::MIN + 1));
+        assert_eq!(odd.slice(None, None, Some(int(-1))).to_string(),
+            "range(-2147483647, 1, 2)");
+        assert_eq!(Value::Range(RangeType::new(0, i32::MAX, 2))
+            .slice(Some(int(i32::MAX)), None, None).clone_seq(), vec![]);
+        let sliced = exception::catch(|| RangeType::new(0, 2, i32::MIN)
+            .slice(None, None, Some(Value::Number(NumericType::Integer(-1)))));
+        assert_eq!(sliced.err().map(name), Some("OverflowError".to_string()));
+        assert_eq!(RangeType::new(i32::MAX - 2, i32::MAX, 1).reversed().get(0),
+            Some(Value::Number(NumericType::Integer(i32::MAX - 1))));
+    }
+
+    #[test]
+    fn lazy_builtins() {
+        use ::builtin::{enumerate, zip, map, filter, reversed};
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            int(1), int(0), int(3)
+        ]))));
+        let kwargs = HashMap::new();
+
+        let pairs = enumerate(vec![list.clone(), int(5)], kwargs.clone());
+        assert_eq!(pairs.next(),
+            Some(Value::Tuple(TupleType::new(vec![int(5), int(1)]))));
+        let zipped = zip(vec![list.clone(), Value::Str("ab".to_string())],
+            kwargs.clone());
+        assert_eq!(zipped.clone_seq().len(), 2);
+        let summed = map(vec![Value::Function(Rc::new(
+            |args: Vec<Value>, _| args[0].clone() + args[1].clone())),
+            list.clone(), Value::Range(RangeType::new(0, 10, 1))],
+            kwargs.clone());
+        assert_eq!(summed.clone_seq(), vec![int(1), int(1), int(5)]);
+        let truthy = filter(vec![Value::None, list.clone()], kwargs.clone());
+        assert_eq!(truthy.clone_seq(), vec![int(1), int(3)]);
+        let backwards = reversed(vec![list.clone()], kwargs.clone());
+        assert_eq!(backwards.clone_seq(), vec![int(3), int(0), int(1)]);
+        let bytes = reversed(vec![BytesType::new_bytes(b"ab".to_vec())],
+            kwargs.clone());
+        assert_eq!(bytes.clone_seq(), vec![int(98), int(97)]);
+        let array = reversed(vec![BytesType::new_bytearray(b"ab".to_vec())],
+            kwargs.clone());
+        assert_eq!(array.clone_seq(), vec![int(98), int(97)]);
+    }
+
+    #[test]
+    fn zip_strict_length_mismatch() {
+        let mut kwargs = HashMap::new();
+        kwargs.insert("strict".to_string(), Value::Bool(true));
+        let zipped = ::builtin::zip(vec![Value::Str("ab".to_string()),
+            Value::Str("abc".to_string())], kwargs);
+        let result = ::exception::catch(|| zipped.clone_seq());
+
+        assert_eq!(result.err().map(|exc| exc.to_string()),
+            Some("ValueError: zip() argument 2 is longer than argument 1"
+                .to_string()));
+    }
+
+    #[test]
+    fn builtin_min_max_key_default() {
+        use ::builtin::{min, max};
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            int(3), int(-7), int(7)
+        ]))));
+        let abs = Value::Function(Rc::new(::builtin::abs));
+        let mut kwargs = HashMap::new();
+        kwargs.insert("key".to_string(), abs);
+
+        assert_eq!(min(vec![list.clone()], HashMap::new()), int(-7));
+        assert_eq!(max(vec![int(1), int(4), int(2)], HashMap::new()), int(4));
+        assert_eq!(max(vec![list.clone()], kwargs.clone()), int(-7));
+        assert_eq!(min(vec![list.clone()], kwargs.clone()), int(3));
+
+        let empty = Value::List(Rc::new(RefCell::new(ListType::new(vec![]))));
+        kwargs.insert("default".to_string(), Value::None);
+        assert_eq!(max(vec![empty], kwargs), Value::None);
+    }
+
+    #[test]
+    fn builtin_sum_any_all() {
+        use ::builtin::{sum, any, all};
+        let float = |val| Value::Number(NumericType::Float(val));
+        let floats = Value::List(Rc::new(RefCell::new(ListType::new(
+            vec![float(0.1); 10]))));
+        assert_eq!(sum(vec![floats.clone()], HashMap::new()), float(1.0));
+        assert_eq!(sum(vec![floats.clone(), float(-1.0)], HashMap::new()) <
+            float(0.0001), true);
+        assert_eq!(any(vec![floats.clone()], HashMap::new()), Value::Bool(true));
+        assert_eq!(all(vec![Value::Str("".to_string())], HashMap::new()),
+            Value::Bool(true));
+        assert_eq!(all(vec![Value::Range(RangeType::new(0, 3, 1))],
+            HashMap::new()), Value::Bool(false));
+    }
+
+    #[test]
+    fn builtin_round_half_even() {
+        use ::builtin::round;
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let float = |val| Value::Number(NumericType::Float(val));
+
+        assert_eq!(round(vec![float(2.5)], HashMap::new()), int(2));
+        assert_eq!(round(vec![float(3.5)], HashMap::new()), int(4));
+        assert_eq!(round(vec![float(-0.5)], HashMap::new()), int(0));
+        assert_eq!(round(vec![float(0.125), int(2)], HashMap::new()),
+            float(0.12));
+        assert_eq!(round(vec![int(1250), int(-2)], HashMap::new()), int(1200));
+        assert_eq!(round(vec![int(1350), int(-2)], HashMap::new()), int(1400));
+
+        let overflow = |params: Vec<Value>| exception::catch(|| {
+            round(params, HashMap::new())
+        }).err().map(|exc| exc.to_string());
+        assert_eq!(overflow(vec![int(i32::MAX), int(-1)]),
+            Some("OverflowError: round() result out of range".to_string()));
+        assert_eq!(overflow(vec![float(1e20)]),
+            Some("OverflowError: round() result out of range".to_string()));
+    }
+
+    #[test]
+    fn builtin_abs_overflow() {
+        use ::builtin::abs;
+        let int = |val| Value::Number(NumericType::Integer(val));
+
+        assert_eq!(abs(vec![int(-i32::MAX)], HashMap::new()), int(i32::MAX));
+        let error = exception::catch(|| abs(vec![int(i32::MIN)], HashMap::new()));
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("OverflowError: abs() result out of range".to_string()));
+    }
+
+    #[test]
+    fn builtin_sorted_stable() {
+        let pair = |a: i32, b: &str| Value::Tuple(TupleType::new(vec![
+            Value::Number(NumericType::Integer(a)), Value::Str(b.to_string())]));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            pair(2, "a"), pair(1, "b"), pair(2, "c"), pair(1, "d")
+        ]))));
+        let mut kwargs = HashMap::new();
+        kwargs.insert("key".to_string(), Value::Function(Rc::new(
+            |args: Vec<Value>, _| args[0].index(
+                Value::Number(NumericType::Integer(0))))));
+        kwargs.insert("reverse".to_string(), Value::Bool(true));
+
+        assert_eq!(::builtin::sorted(vec![list], kwargs).clone_seq(), vec![
+            pair(2, "a"), pair(2, "c"), pair(1, "b"), pair(1, "d")
+        ]);
+        assert_eq!(::builtin::sorted(vec![Value::Str("cab".to_string())],
+            HashMap::new()).clone_seq(), vec![Value::Str("a".to_string()),
+            Value::Str("b".to_string()), Value::Str("c".to_string())]);
+    }
+
+    #[test]
+    fn list_methods() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            int(3), int(1), int(2)
+        ]))));
+        let call = |attr: &str, args: Vec<Value>| {
+            call_member(list.clone(), attr, args, HashMap::new())
+        };
+
+        call("insert", vec![int(-10), int(0)]);
+        call("extend", vec![list.clone()]);
+        assert_eq!(list.clone_seq(), vec![int(0), int(3), int(1), int(2),
+            int(0), int(3), int(1), int(2)]);
+        assert_eq!(call("index", vec![int(3), int(2)]), int(5));
+        assert_eq!(call("count", vec![int(1)]), int(2));
+        call("remove", vec![int(3)]);
+        assert_eq!(call("pop", vec![int(0)]), int(0));
+        call("sort", vec![]);
+        assert_eq!(list.clone_seq(), vec![int(0), int(1), int(1), int(2),
+            int(2), int(3)]);
+        call("reverse", vec![]);
+        let copy = call("copy", vec![]);
+        call("clear", vec![]);
+        assert_eq!(list.clone_seq(), vec![]);
+        assert_eq!(copy.clone_seq(), vec![int(3), int(2), int(2), int(1),
+            int(1), int(0)]);
+    }
+
+    #[test]
+    fn list_value_self_argument() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            int(2), int(1)
+        ]))));
+        let call = |attr: &str, args: Vec<Value>, kwargs| {
+            call_member(list.clone(), attr, args, kwargs)
+        };
+        call("append", vec![list.clone()], HashMap::new());
+
+        assert_eq!(call("count", vec![list.clone()], HashMap::new()), int(1));
+        assert_eq!(call("index", vec![list.clone()], HashMap::new()), int(2));
+        call("remove", vec![list.clone()], HashMap::new());
+        let inner = list.clone();
+        let mut kwargs = HashMap::new();
+        kwargs.insert("key".to_string(), Value::Function(Rc::new(
+            move |args: Vec<Value>, _| args[0].clone() + ::builtin::len(vec![inner.clone()], HashMap::new()))));
+        call("sort", vec![], kwargs);
+        assert_eq!(list.clone_seq(), vec![int(1), int(2)]);
+    }
+
+    #[test]
+    fn list_del_slice_large() {
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(
+            Value::Range(RangeType::new(0, 200_000, 1)).clone_seq()))));
+        let int = |val| Value::Number(NumericType::Integer(val));
+
+        list.del_slice(None, Some(int(100_000)), None);
+        list.del_slice(None, None, Some(int(2)));
+        assert_eq!(list.clone_seq().len(), 50_000);
+        assert_eq!(list.index(int(0)), int(100_001));
+        list.del_slice(Some(int(-1)), None, Some(int(-3)));
+        assert_eq!(list.index(int(-1)), int(199_997));
+    }
+
+    #[test]
+    fn list_item_slice_assignment() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(
+            Value::Range(RangeType::new(0, 6, 1)).clone_seq()))));
+
+        list.set_index(int(-1), int(50));
+        list.set_slice(Some(int(1)), Some(int(3)), None,
+            Value::Str("ab".to_string()));
+        assert_eq!(list.index(int(2)), Value::Str("b".to_string()));
+        list.set_slice(None, None, Some(int(2)), Value::Tuple(TupleType::new(
+            vec![int(7), int(8), int(9)])));
+        list.del_index(int(1));
+        list.del_slice(None, None, Some(int(-2)));
+        assert_eq!(list.clone_seq(), vec![int(8), int(9)]);
+
+        let result = ::exception::catch(|| {
+            list.set_slice(None, None, Some(int(-1)),
+                Value::Str("a".to_string()))
+        });
+        assert_eq!(result.err().map(|exc| exc.name().to_string()),
+            Some("ValueError".to_string()));
+    }
+
+    #[test]
+    fn op_add_mul_value_sequence() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            int(1)
+        ]))));
+        let tup = Value::Tuple(TupleType::new(vec![int(1), int(2)]));
+
+        assert_eq!(list.clone() + list.clone(),
+            Value::List(Rc::new(RefCell::new(ListType::new(vec![int(1), int(1)])))));
+        assert_eq!((tup.clone() + tup.clone()).clone_seq().len(), 4);
+        assert_eq!(Value::Str("ab".to_string()) * int(2),
+            Value::Str("abab".to_string()));
+        assert_eq!(int(3) * list.clone(),
+            Value::List(Rc::new(RefCell::new(ListType::new(vec![int(1); 3])))));
+        assert_eq!(tup.clone() * int(-1), Value::Tuple(TupleType::new(vec![])));
+    }
+
+    #[test]
+    fn op_inplace_value_sequence() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![
+            int(1)
+        ]))));
+        let mut alias = list.clone();
+        let mut string = Value::Str("a".to_string());
+        let original = string.clone();
+
+        alias += Value::Range(RangeType::new(2, 4, 1));
+        alias *= int(2);
+        alias = alias.iadd(list.clone());
+        string += Value::Str("b".to_string());
+
+        assert_eq!(list.clone_seq(), vec![int(1), int(2), int(3), int(1),
+            int(2), int(3), int(1), int(2), int(3), int(1), int(2), int(3)]);
+        assert_eq!(alias == list, true);
+        assert_eq!(string, Value::Str("ab".to_string()));
+        assert_eq!(original, Value::Str("a".to_string()));
+    }
+
+    #[test]
+    fn partial_ord_value_sequence() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = |vals: Vec<Value>|
+            Value::List(Rc::new(RefCell::new(ListType::new(vals))));
+        let x = list(vec![int(1), int(2)]);
+        let y = list(vec![int(1), int(3)]);
+        let z = list(vec![int(1), int(2), int(0)]);
+        let a = Value::Tuple(TupleType::new(vec![int(1), Value::Str("b".to_string())]));
+        let b = Value::Tuple(TupleType::new(vec![int(2), int(0)]));
+
+        assert_eq!(x < y, true);
+        assert_eq!(x < z, true);
+        assert_eq!(z >= x, true);
+        assert_eq!(y > z, true);
+        assert_eq!(x <= x, true);
+        assert_eq!(a < b, true);
+        assert_eq!(b > a, true);
+    }
+
+    #[test]
+    fn partial_eq_value_bool_number() {
+        let one = Value::Number(NumericType::Integer(1));
+        let zero = Value::Number(NumericType::Float(0.0));
+
+        assert_eq!(Value::Bool(true) == one, true);
+        assert_eq!(one == Value::Bool(true), true);
+        assert_eq!(Value::Bool(false) == zero, true);
+        assert_eq!(Value::Bool(false) != one, true);
+        assert_eq!(Value::Bool(true) > zero, true);
+    }
+
+    #[test]
+    fn partial_ord_value_mixed_type_error() {
+        let x = Value::Str("a".to_string());
+        let y = Value::Number(NumericType::Integer(1));
+        let result = ::exception::catch(|| x < y);
+        let nested = ::exception::catch(|| {
+            Value::Tuple(TupleType::new(vec![y.clone()])) >
+                Value::Tuple(TupleType::new(vec![x.clone()]))
+        });
+
+        assert_eq!(result.err().map(|exc| exc.to_string()),
+            Some("TypeError: '<' not supported between instances of 'str' \
+                and 'int'".to_string()));
+        assert_eq!(nested.err().map(|exc| exc.to_string()),
+            Some("TypeError: '>' not supported between instances of 'int' \
+                and 'str'".to_string()));
+    }
+
+    #[test]
+    fn identity_value_is() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![]))));
+        let other = Value::List(Rc::new(RefCell::new(ListType::new(vec![]))));
+        let mut tbl = HashMap::new();
+        tbl.insert("__name__".to_string(), Value::Str("Point".to_string()));
+        let obj = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+
+        assert_eq!(list.is(&list.clone()), true);
+        assert_eq!(list.is(&other), false);
+        assert_eq!(list == other, true);
+        assert_eq!(obj.is(&obj.clone()), true);
+        assert_eq!(obj == obj.clone(), true);
+        assert_eq!(Value::None.is(&Value::None), true);
+        assert_eq!(Value::Bool(true).is(&Value::Bool(true)), true);
+        assert_eq!(Value::Bool(true).is_not(&int(1)), true);
+        assert_eq!(int(256).is(&int(256)), true);
+        assert_eq!(int(257).is(&int(258)), false);
+    }
+
+    #[test]
+    fn identity_value_id() {
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(vec![]))));
+        let other = Value::List(Rc::new(RefCell::new(ListType::new(vec![]))));
+        let float = Value::Number(NumericType::Float(1.5));
+
+        assert_eq!(list.id(), list.clone().id());
+        assert_eq!(list.id() != other.id(), true);
+        assert_eq!(Value::None.id(), Value::None.id());
+        assert_eq!(float.id(), float.clone().id());
+        assert_eq!(float.is(&float), true);
+        assert_eq!(float.is(&Value::Number(NumericType::Integer(1))), false);
+        let long = Value::Str("a long string".to_string());
+        assert_eq!(long.id(), long.clone().id());
+        assert_eq!(long.is(&long), true);
+    }
+
+    #[test]
+    fn object_dunder_eq() {
+        let mut tbl = HashMap::new();
+        tbl.insert("__eq__".to_string(), Value::Function(Rc::new(
+            |args: Vec<Value>, _| Value::Bool(args[1] == Value::None))));
+        let obj = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+        let plain = Value::Object { tbl: Rc::new(RefCell::new(HashMap::new())) };
+
+        assert_eq!(obj == Value::None, true);
+        assert_eq!(Value::None == obj, true);
+        assert_eq!(obj == Value::Bool(false), false);
+        assert_eq!(plain == obj, false);
+        assert_eq!(obj != plain, true);
+        assert_eq!(plain == plain.clone(), true);
+    }
+
+    #[test]
+    fn object_module_flag() {
+        let argc = Value::Function(Rc::new(|args: Vec<Value>, _| {
+            Value::Number(NumericType::Integer(args.len() as i32))
+        }));
+        let object = |flagged: bool| {
+            let mut tbl = HashMap::new();
+            tbl.insert("argc".to_string(), argc.clone());
+            if flagged {
+                tbl.insert("__module__".to_string(), Value::Bool(true));
+            }
+            Value::Object { tbl: Rc::new(RefCell::new(tbl)) }
+        };
+
+        // Functions of module objects aren't methods and don't take `self`
+        assert_eq!(call_member(object(true), "argc", vec![Value::None],
+            HashMap::new()), Value::Number(NumericType::Integer(1)));
+        assert_eq!(call_member(object(false), "argc", vec![Value::None],
+            HashMap::new()), Value::Number(NumericType::Integer(2)));
+    }
+
+    #[test]
+    fn bool_value_arithmetic() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let float = Value::Number(NumericType::Float(0.5));
+        let t = Value::Bool(true);
+        let f = Value::Bool(false);
+
+        assert_eq!(t.clone() + t.clone(), int(2));
+        assert_eq!(t.clone() - int(3), int(-2));
+        assert_eq!((t.clone() * float).to_string(), "0.5");
+        assert_eq!(int(5) % t.clone(), int(0));
+        assert_eq!(t.clone() << int(3), int(8));
+        assert_eq!((t.clone() & f.clone()).to_string(), "False");
+        assert_eq!((t.clone() | int(2)).to_string(), "3");
+        assert_eq!(t.pow(&int(2)), int(1));
+        assert_eq!(t.clone() > f.clone(), true);
+        assert_eq!(t.to_string(), "True");
+    }
+
+    #[test]
+    fn bool_value_index() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(
+            vec![int(1), int(2), int(3)]))));
+        let tup = Value::Tuple(TupleType::new(vec![int(1), int(2)]));
+        let bools = Value::List(Rc::new(RefCell::new(ListType::new(
+            vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)]))));
+
+        assert_eq!(list.index(Value::Bool(true)), int(2));
+        assert_eq!(tup.index(Value::Bool(false)), int(1));
+        assert_eq!(tup.index(int(-2)), int(1));
+        assert_eq!(list.slice(Some(Value::Bool(true)), None, None).to_string(),
+            "[2, 3]");
+        assert_eq!((list * Value::Bool(false)).to_string(), "[]");
+        assert_eq!(::builtin::sum(vec![bools], HashMap::new()), int(2));
+    }
+
+    #[test]
+    fn bool_value_hash() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let float = Value::Number(NumericType::Float(1.0));
+
+        assert_eq!(Value::Bool(true).hash_value(), int(1).hash_value());
+        assert_eq!(float.hash_value(), int(1).hash_value());
+        assert_eq!(Value::Tuple(TupleType::new(vec![Value::Bool(false)]))
+            .hash_value(), Value::Tuple(TupleType::new(vec![int(0)]))
+            .hash_value());
+    }
+
+    #[test]
+    fn bytes_value_sequence() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let bytes = BytesType::new_bytes(b"abc".to_vec());
+        let array = BytesType::new_bytearray(b"xy".to_vec());
+
+        assert_eq!(bytes.index(int(0)), int(97));
+        assert_eq!(bytes.index(int(-1)), int(99));
+        assert_eq!(bytes.slice(Some(int(1)), None, None),
+            BytesType::new_bytes(b"bc".to_vec()));
+        assert_eq!(bytes.clone_seq(), vec![int(97), int(98), int(99)]);
+        assert_eq!(int(98).contained_in(&bytes), true);
+        assert_eq!(BytesType::new_bytes(b"bc".to_vec()).contained_in(&bytes),
+            true);
+        assert_eq!((bytes.clone() + array.clone()).to_string(), "b'abcxy'");
+        assert_eq!((array.clone() * int(2)).to_string(),
+            "bytearray(b'xyxy')");
+
+        array.set_index(int(0), int(10));
+        let array = array.iadd(bytes.clone());
+        assert_eq!(array.to_string(), "bytearray(b'\\nyabc')");
+        assert_eq!(BytesType::new_bytes(b"it's\x00".to_vec()).to_string(),
+            "b\"it's\\x00\"");
+        assert_eq!(array == BytesType::new_bytes(b"\nyabc".to_vec()), true);
+    }
+
+    #[test]
+    fn bytes_value_methods() {
+        let bytes = |val: &[u8]| BytesType::new_bytes(val.to_vec());
+        let string = |val: &str| Value::Str(val.to_string());
+        let call = |value: &Value, attr, args| {
+            call_member(value.clone(), attr, args, HashMap::new())
+        };
+        let data = bytes(b"  a,b,,c ");
+
+        assert_eq!(call(&data, "strip", vec![]), bytes(b"a,b,,c"));
+        assert_eq!(call(&bytes(b"a,b,,c"), "split", vec![bytes(b",")])
+            .to_string(), "[b'a', b'b', b'', b'c']");
+        assert_eq!(call(&data, "split", vec![]).to_string(), "[b'a,b,,c']");
+        assert_eq!(call(&data, "find", vec![bytes(b",,")]),
+            Value::Number(NumericType::Integer(5)));
+        assert_eq!(call(&data, "startswith", vec![bytes(b"  a")]),
+            Value::Bool(true));
+        assert_eq!(call(&data, "replace", vec![bytes(b","), bytes(b";")]),
+            bytes(b"  a;b;;c "));
+        assert_eq!(call(&bytes(b"-"), "join",
+            vec![Value::Tuple(TupleType::new(vec![bytes(b"a"), bytes(b"b")]))]),
+            bytes(b"a-b"));
+        assert_eq!(call(&bytes(b"\xde\xad"), "hex", vec![]), string("dead"));
+        assert_eq!(call(&string("caf\u{e9}"), "encode", vec![]),
+            bytes(b"caf\xc3\xa9"));
+        assert_eq!(call(&bytes(b"caf\xc3\xa9"), "decode", vec![]),
+            string("caf\u{e9}"));
+        assert_eq!(call(&bytes(b"a\xffb"), "decode",
+            vec![string("ascii"), string("ignore")]), string("ab"));
+
+        let error = exception::catch(|| {
+            call(&bytes(b"a\xff"), "decode", vec![])
+        });
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("UnicodeDecodeError: 'utf-8' codec can't decode byte 0xff \
+                in position 1: invalid start byte".to_string()));
+    }
+
+    #[test]
+    fn bytearray_value_self_argument() {
+        let array = BytesType::new_bytearray(b"ab".to_vec());
+        let call = |attr, args| call_member(array.clone(), attr, args, HashMap::new());
+
+        call("extend", vec![array.clone()]);
+        assert_eq!(array.to_string(), "bytearray(b'abab')");
+        assert_eq!(call("find", vec![array.clone()]),
+            Value::Number(NumericType::Integer(0)));
+        assert_eq!(call("startswith", vec![array.clone()]), Value::Bool(true));
+        assert_eq!(array.contained_in(&array), true);
+        assert_eq!(call("replace", vec![array.clone(),
+            BytesType::new_bytes(b"x".to_vec())]).to_string(), "bytearray(b'x')");
+
+        let copy = call("copy", vec![]);
+        call("clear", vec![]);
+        assert_eq!(copy.to_string(), "bytearray(b'abab')");
+        assert_eq!(array.to_string(), "bytearray(b'')");
+    }
+
+    #[test]
+    fn bytes_value_constructors() {
+        let int = |val| Value::Number(NumericType::Integer(val));
+        let scope = ::builtin::get_scope();
+        let bytes = scope["bytes"].clone();
+        let bytearray = scope["bytearray"].clone();
+        let list = Value::List(Rc::new(RefCell::new(ListType::new(
+            vec![int(104), int(105)]))));
+
+        assert_eq!(bytes.call(vec![int(2)], HashMap::new()).to_string(),
+            "b'\\x00\\x00'");
+        assert_eq!(bytes.call(vec![list], HashMap::new()).to_string(), "b'hi'");
+        assert_eq!(bytearray.call(vec![Value::Str("hi".to_string()),
+            Value::Str("ascii".to_string())], HashMap::new()).to_string(),
+            "bytearray(b'hi')");
+        assert_eq!(call_member(bytes, "fromhex",
+            vec![Value::Str("de ad".to_string())], HashMap::new()),
+            BytesType::new_bytes(vec![0xde, 0xad]));
+    }
+
+    #[test]
+    fn file_value_close() {
+        let path = ::std::env::temp_dir().join("cannolib_value_close");
+        let path = Value::Str(path.to_str().unwrap().to_string());
+        let file = ::builtin::open(vec![path.clone(), Value::Str("w".to_string())],
+            HashMap::new());
+        let alias = file.clone();
+
+        call_member(file.clone(), "write", vec![Value::Str("data".to_string())],
+            HashMap::new());
+        call_member(file.clone(), "close", vec![], HashMap::new());
+        call_member(file.clone(), "close", vec![], HashMap::new());
+        let error = exception::catch(|| {
+            call_member(alias.clone(), "write",
+                vec![Value::Str("more".to_string())], HashMap::new())
+        });
+
+        assert_eq!(alias.get_attr("closed"), Value::Bool(true));
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("ValueError: I/O operation on closed file".to_string()));
+        assert_eq!(::std::fs::read_to_string(path.to_string()).unwrap(), "data");
+        let _ = ::std::fs::remove_file(path.to_string());
+    }
+
+    #[test]
+    fn file_value_binary_lines() {
+        let path = ::std::env::temp_dir().join("cannolib_value_binary_lines");
+        ::std::fs::write(&path, b"\xff\xfe\n\n").unwrap();
+        let path = Value::Str(path.to_str().unwrap().to_string());
+        let file = ::builtin::open(vec![path.clone(), Value::Str("rb".to_string())],
+            HashMap::new());
+        let bytes = |data: &[u8]| BytesType::new_bytes(data.to_vec());
+
+        // The repr of b'\xff\xfe\n' is far longer than the 3 bytes read
+        let lines = call_member(file.clone(), "readlines",
+            vec![Value::Number(NumericType::Integer(4))], HashMap::new());
+        assert_eq!(lines, Value::List(Rc::new(RefCell::new(ListType::new(
+            vec![bytes(b"\xff\xfe\n"), bytes(b"\n")])))));
+        assert_eq!(call_member(file.clone(), "readline", vec![], HashMap::new()),
+            bytes(b""));
+        call_member(file, "close", vec![], HashMap::new());
+        let _ = ::std::fs::remove_file(path.to_string());
+    }
+
+    #[test]
+    fn file_value_large_offsets() {
+        let path = ::std::env::temp_dir().join("cannolib_value_large_offsets");
+        ::std::fs::File::create(&path).unwrap().set_len(3 << 30).unwrap();
+        let path = Value::Str(path.to_str().unwrap().to_string());
+        let file = ::builtin::open(vec![path.clone(), Value::Str("rb".to_string())],
+            HashMap::new());
+        let int = |val| Value::Number(NumericType::Integer(val));
+
+        let error = exception::catch(|| call_member(file.clone(), "seek",
+            vec![int(0), int(2)], HashMap::new()));
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("OverflowError: Python int too large to convert to C long"
+                .to_string()));
+        let error = exception::catch(|| {
+            call_member(file.clone(), "tell", vec![], HashMap::new())
+        });
+        assert_eq!(error.err().map(|exc| exc.name().to_string()),
+            Some("OverflowError".to_string()));
+        call_member(file, "close", vec![], HashMap::new());
+        let _ = ::std::fs::remove_file(path.to_string());
+    }
+
+    #[test]
+    fn print_keywords() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let (write_log, flush_log) = (log.clone(), log.clone());
+        let mut tbl = HashMap::new();
+        tbl.insert("__name__".to_string(), Value::Str("Writer".to_string()));
+        tbl.insert("write".to_string(), Value::Function(Rc::new(
+            move |args: Vec<Value>, _| {
+                write_log.borrow_mut().push(args[1].to_string());
+                Value::None
+            })));
+        tbl.insert("flush".to_string(), Value::Function(Rc::new(move |_, _| {
+            flush_log.borrow_mut().push("flush".to_string());
+            Value::None
+        })));
+        let writer = Value::Object { tbl: Rc::new(RefCell::new(tbl)) };
+        let print = ::builtin::get_scope()["print"].clone();
+        let kwargs = |pairs: Vec<(&str, Value)>| pairs.into_iter()
+            .map(|(key, value)| (key.to_string(), value)).collect();
+        let args = vec![Value::Str("a".to_string()),
+            Value::Number(NumericType::Integer(1)), Value::Bool(true)];
+
+        print.call(args.clone(), kwargs(vec![("file", writer.clone())]));
+        print.call(args.clone(), kwargs(vec![("file", writer.clone()),
+            ("sep", Value::Str(", ".to_string())), ("end", Value::None),
+            ("flush", Value::Bool(true))]));
+        print.call(vec![], kwargs(vec![("file", writer.clone()),
+            ("end", Value::Str("".to_string()))]));
+        let error = exception::catch(|| {
+            print.call(args.clone(), kwargs(vec![("file", writer.clone()),
+                ("sep", Value::Number(NumericType::Integer(0)))]))
+        });
+
+        assert_eq!(*log.borrow(), vec!["a 1 True\n", "a, 1, True\n", "flush",
+            ""]);
+        assert_eq!(error.err().map(|exc| exc.to_string()), Some("TypeError: \
+            sep must be None or a string, not int".to_string()));
+    }
+
+    #[test]
+    fn io_memory_files() {
+        let io = ::builtin::io::import_module();
+        let contextlib = ::builtin::contextlib::import_module();
+        let print = ::builtin::get_scope()["print"].clone();
+        let text = |value: &str| Value::Str(value.to_string());
+
+        let out = call_member(io.clone(), "StringIO", vec![], HashMap::new());
+        let mut kwargs = HashMap::new();
+        kwargs.insert("file".to_string(), out.clone());
+        print.call(vec![text("first")], kwargs);
+        let redirect = call_member(contextlib, "redirect_stdout",
+            vec![out.clone()], HashMap::new());
+        ::context::with(redirect, |_| print.call(vec![text("second")],
+            HashMap::new()));
+
+        assert_eq!(out.type_name(), "_io.StringIO");
+        assert_eq!(call_member(out.clone(), "getvalue", vec![], HashMap::new()),
+            text("first\nsecond\n"));
+        call_member(out.clone(), "seek", vec![Value::Number(
+            NumericType::Integer(0))], HashMap::new());
+        let lines: Vec<Value> = out.clone().into_iter().collect();
+        assert_eq!(lines, vec![text("first\n"), text("second\n")]);
+
+        let data = Value::Bytes(BytesType::new(b"ab".to_vec()));
+        let buf = call_member(io, "BytesIO", vec![data], HashMap::new());
+        call_member(buf.clone(), "seek", vec![Value::Number(
+            NumericType::Integer(0)), Value::Number(NumericType::Integer(2))],
+            HashMap::new());
+        call_member(buf.clone(), "write", vec![Value::Bytes(BytesType::new(
+            b"c".to_vec()))], HashMap::new());
+        assert_eq!(buf.type_name(), "_io.BytesIO");
+        assert_eq!(call_member(buf.clone(), "getvalue", vec![], HashMap::new()),
+            Value::Bytes(BytesType::new(b"abc".to_vec())));
+        assert_eq!(call_member(buf.clone(), "readline", vec![], HashMap::new()),
+            Value::Bytes(BytesType::new(vec![])));
+        assert_eq!(call_member(out.clone(), "readline", vec![], HashMap::new()),
+            text(""));
+
+        ::context::with(buf.clone(), |_| ());
+        let error = exception::catch(|| {
+            call_member(buf.clone(), "getvalue", vec![], HashMap::new())
+        });
+        assert_eq!(buf.get_attr("closed"), Value::Bool(true));
+        assert_eq!(error.err().map(|exc| exc.to_string()),
+            Some("ValueError: I/O operation on closed file.".to_string()));
+    }
 }